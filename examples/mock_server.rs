@@ -29,7 +29,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ),
         // Client selects a newsgroup
         (
-            Command::Group("comp.lang.rust".to_string()),
+            Command::Group("comp.lang.rust".parse()?),
             Response::GroupSelected {
                 count: 42,
                 first: 1,
@@ -39,7 +39,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ),
         // Client retrieves an article
         (
-            Command::Article(ArticleSpec::number_in_group("comp.lang.rust", 1)),
+            Command::Article(ArticleSpec::number_in_group("comp.lang.rust".parse()?, 1)),
             Response::Article {
                 number: Some(1),
                 message_id: "<test@example.com>".to_string(),
@@ -72,7 +72,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Client state: {}", test.client().state());
 
     println!("\n3. Testing group selection:");
-    let response = test.send_command(Command::Group("comp.lang.rust".to_string()))?;
+    let response = test.send_command(Command::Group("comp.lang.rust".parse()?))?;
     match response {
         Response::GroupSelected {
             name,
@@ -89,7 +89,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("\n4. Testing article retrieval:");
     let response = test.send_command(Command::Article(ArticleSpec::number_in_group(
-        "comp.lang.rust",
+        "comp.lang.rust".parse()?,
         1,
     )))?;
     match response {
@@ -21,7 +21,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::str::from_utf8(&capabilities_cmd)?
     );
 
-    let group_cmd = client.encode_command(Command::Group("comp.lang.rust".to_string()))?;
+    let group_cmd = client.encode_command(Command::Group("comp.lang.rust".parse()?))?;
     println!("GROUP command: {:?}", std::str::from_utf8(&group_cmd)?);
 
     // Example 2: Parse responses
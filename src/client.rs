@@ -1,16 +1,93 @@
 //! Sans-IO NNTP client implementation.
 
-use crate::{Command, Error, Response, Result};
+use crate::codec;
+#[cfg(feature = "compression")]
+use crate::compressed::GzipBlock;
+use crate::sasl::{self, SaslMechanism};
+use crate::{Command, Error, GroupName, Response, Result};
 use bytes::{BufMut, Bytes, BytesMut};
+use std::collections::VecDeque;
 
 /// Sans-IO NNTP client.
 ///
 /// This client handles protocol logic without performing any I/O operations.
 /// Users must handle network connections and data transmission separately.
+///
+/// Every encoded command is remembered until its reply has been decoded, so
+/// replies are framed and parsed according to the command that produced them
+/// (e.g. a 211 reply is single-line after GROUP but multi-line after
+/// LISTGROUP). Data decoded while no command is outstanding, such as the
+/// server greeting, is framed by status code alone.
+///
+/// Several commands may be encoded before their replies arrive (pipelining,
+/// RFC 3977 Section 3.5). Replies are matched to commands in the order the
+/// commands were encoded. A command that changes the connection state, as
+/// reported by [`Command::is_pipeline_barrier`], must be the last one in a
+/// pipeline.
 pub struct Client {
     read_buffer: BytesMut,
+    /// Number of leading buffered bytes already searched for a line ending or
+    /// block terminator, so that scanning resumes instead of restarting
+    scanned: usize,
     state: ClientState,
+    /// Newsgroup selected by the last successful GROUP, kept while later
+    /// commands are outstanding
+    group: Option<String>,
     posting_allowed: bool,
+    in_flight: VecDeque<Command>,
+    event_state: EventState,
+    sasl: Option<SaslExchange>,
+    /// Set once XFEATURE COMPRESS GZIP was accepted, to whether the
+    /// server sends a terminator after compressed data
+    gzip_terminator: Option<bool>,
+    /// Compressed data block of the reply being decoded
+    #[cfg(feature = "compression")]
+    gzip_block: Option<Box<GzipBlock>>,
+}
+
+/// A SASL exchange started with [`Client::start_sasl`]
+struct SaslExchange {
+    mechanism: Box<dyn SaslMechanism>,
+    /// Initial response too long for the command line, sent in answer to
+    /// the first (empty) challenge instead
+    deferred: Option<Vec<u8>>,
+    /// Mechanism failure that made the client cancel the exchange
+    error: Option<Error>,
+}
+
+/// Incremental decoding events produced by [`Client::decode_event`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseEvent {
+    /// The status line of a reply.
+    Status {
+        /// Response code
+        code: u16,
+        /// Text following the response code
+        message: String,
+        /// Whether a multi-line data block follows
+        multiline: bool,
+    },
+    /// One line of a multi-line data block.
+    ///
+    /// The line is dot-unstuffed and keeps its original line ending, so
+    /// concatenating all lines of an article reproduces its exact content.
+    Line(Bytes),
+    /// The reply is complete.
+    End,
+}
+
+/// Progress of the reply currently being decoded by [`Client::decode_event`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EventState {
+    /// Waiting for a status line
+    Idle,
+    /// Inside a multi-line data block
+    Block,
+    /// A single-line reply was emitted and its `End` is still due
+    PendingEnd,
+    /// Inside a data block compressed after XFEATURE COMPRESS GZIP
+    #[cfg(feature = "compression")]
+    Compressed,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,7 +101,7 @@ enum ClientState {
     /// Authenticated
     Authenticated,
     /// Group selected
-    GroupSelected { group: String },
+    GroupSelected,
     /// Posting mode
     Posting,
     /// Connection closed
@@ -42,22 +119,115 @@ impl Client {
     pub fn new() -> Self {
         Self {
             read_buffer: BytesMut::new(),
+            scanned: 0,
             state: ClientState::Connected,
+            group: None,
             posting_allowed: false,
+            in_flight: VecDeque::new(),
+            event_state: EventState::Idle,
+            sasl: None,
+            gzip_terminator: None,
+            #[cfg(feature = "compression")]
+            gzip_block: None,
         }
     }
 
     /// Encode a command for transmission to the server.
     ///
     /// Returns the bytes that should be sent to the server.
+    ///
+    /// Fails with [`Error::InvalidCommand`] if a pipeline barrier is still
+    /// waiting for its reply, see [`Client::can_pipeline`].
     pub fn encode_command(&mut self, command: Command) -> Result<Bytes> {
+        if !self.barrier_cleared() {
+            return Err(Error::InvalidCommand(
+                "Cannot send a command before the reply to a state-changing command".to_string(),
+            ));
+        }
+
         // Update state based on command
         self.update_state_for_command(&command)?;
 
         let bytes = command.encode()?;
+        self.in_flight.push_back(command);
         Ok(Bytes::from(bytes))
     }
 
+    /// Start SASL authentication with the given mechanism (RFC 4643).
+    ///
+    /// Returns the AUTHINFO SASL command to send. The mechanism's initial
+    /// response is included unless it would make the command line longer
+    /// than 512 octets, in which case it is sent after the server's first
+    /// challenge. Pass every reply to [`Client::continue_sasl`] until the
+    /// exchange completes.
+    pub fn start_sasl(&mut self, mut mechanism: Box<dyn SaslMechanism>) -> Result<Bytes> {
+        let name = mechanism.name().to_string();
+        let mut initial_response = mechanism.initial_response()?;
+        let mut deferred = None;
+        if let Some(data) = &initial_response {
+            // "AUTHINFO SASL " + mechanism + " " + data + CRLF
+            if 14 + name.len() + 1 + sasl::encode_data(data).len() + 2 > 512 {
+                deferred = initial_response.take();
+            }
+        }
+
+        let bytes = self.encode_command(Command::AuthInfoSasl {
+            mechanism: name,
+            initial_response,
+        })?;
+        self.sasl = Some(SaslExchange {
+            mechanism,
+            deferred,
+            error: None,
+        });
+        Ok(bytes)
+    }
+
+    /// Continue a SASL exchange with the reply to the last command it sent.
+    ///
+    /// Returns `Ok(Some(bytes))` with the next command to send after a
+    /// challenge, or `Ok(None)` once authentication succeeded. If the
+    /// mechanism cannot answer a challenge, the exchange is cancelled and
+    /// its error is returned with the server's reply to the cancellation.
+    ///
+    /// An error after the server accepted the authentication means that
+    /// the mechanism could not verify the server, and the connection should
+    /// not be used.
+    pub fn continue_sasl(&mut self, response: &Response) -> Result<Option<Bytes>> {
+        let mut exchange = self
+            .sasl
+            .take()
+            .ok_or_else(|| Error::InvalidCommand("No SASL exchange in progress".to_string()))?;
+
+        match response {
+            Response::SaslChallenge(challenge) => {
+                let data = match exchange.deferred.take() {
+                    Some(initial) if challenge.is_empty() => Ok(initial),
+                    _ => exchange.mechanism.step(challenge),
+                };
+                let command = match data {
+                    Ok(data) => Command::SaslResponse(data),
+                    Err(e) => {
+                        exchange.error = Some(e);
+                        Command::SaslCancel
+                    }
+                };
+                let bytes = self.encode_command(command)?;
+                self.sasl = Some(exchange);
+                Ok(Some(bytes))
+            }
+            Response::AuthSuccess => exchange.mechanism.finish(&[]).map(|()| None),
+            Response::SaslSuccess(data) => exchange.mechanism.finish(data).map(|()| None),
+            Response::Error { code, message } => Err(exchange.error.unwrap_or(Error::Protocol {
+                code: *code,
+                message: message.clone(),
+            })),
+            other => Err(Error::InvalidResponse(format!(
+                "Unexpected reply during SASL authentication: {other:?}"
+            ))),
+        }
+    }
+
     /// Feed received data from the server into the client.
     ///
     /// Call this method with data received from the network connection.
@@ -65,20 +235,183 @@ impl Client {
         self.read_buffer.put_slice(data);
     }
 
+    /// Remove all data that has been fed but not decoded yet.
+    ///
+    /// After a [`Response::CompressionActive`] reply, data buffered behind
+    /// it is compressed and must be decompressed before it is fed again.
+    pub fn take_buffered_bytes(&mut self) -> Bytes {
+        self.scanned = 0;
+        self.read_buffer.split().freeze()
+    }
+
     /// Try to decode a complete response from buffered data.
     ///
     /// Returns `Ok(Some(response))` if a complete response is available,
     /// `Ok(None)` if more data is needed, or an error if parsing fails.
+    ///
+    /// The whole reply is buffered before it is parsed. Use
+    /// [`Client::decode_event`] to process very large replies incrementally.
     pub fn decode_response(&mut self) -> Result<Option<Response>> {
+        if self.event_state != EventState::Idle {
+            return Err(Error::InvalidResponse(
+                "A reply is being decoded incrementally with decode_event".to_string(),
+            ));
+        }
         if let Some(response_data) = self.extract_complete_response()? {
-            let response = Response::parse(&response_data)?;
-            self.update_state_for_response(&response)?;
+            let command = self.in_flight.pop_front();
+            let response = match &command {
+                Some(command) => Response::parse_for_command(&response_data, command)?,
+                None => Response::parse(&response_data)?,
+            };
+            self.update_state_for_response(command.as_ref(), &response)?;
             Ok(Some(response))
         } else {
             Ok(None)
         }
     }
 
+    /// Incrementally decode buffered data into response events.
+    ///
+    /// Emits [`ResponseEvent::Status`] once the status line of a reply is
+    /// available, then one [`ResponseEvent::Line`] per line of a multi-line
+    /// data block as the lines arrive, and finally [`ResponseEvent::End`].
+    /// Single-line replies produce `Status` followed by `End`. Lines are
+    /// removed from the internal buffer as soon as they are emitted, so
+    /// memory use is bounded by the longest line rather than the reply size.
+    ///
+    /// Returns `Ok(None)` if more data is needed. A reply must be consumed
+    /// completely with either this method or [`Client::decode_response`];
+    /// the two cannot be mixed within a single reply.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::{ArticleSpec, Client, Command, ResponseEvent};
+    ///
+    /// let mut client = Client::new();
+    /// client.encode_command(Command::Body(ArticleSpec::Current)).unwrap();
+    /// client.feed_bytes(b"222 1 <a@example.com>\r\nfirst line\r\n..second\r\n.\r\n");
+    ///
+    /// let mut body = Vec::new();
+    /// while let Some(event) = client.decode_event().unwrap() {
+    ///     match event {
+    ///         ResponseEvent::Status { code, .. } => assert_eq!(code, 222),
+    ///         ResponseEvent::Line(line) => body.extend_from_slice(&line),
+    ///         ResponseEvent::End => break,
+    ///     }
+    /// }
+    /// assert_eq!(body, b"first line\r\n.second\r\n");
+    /// ```
+    pub fn decode_event(&mut self) -> Result<Option<ResponseEvent>> {
+        match self.event_state {
+            EventState::PendingEnd => {
+                self.event_state = EventState::Idle;
+                Ok(Some(ResponseEvent::End))
+            }
+            EventState::Block => {
+                let line = match self.take_line(true) {
+                    Some(line) => line,
+                    None => return Ok(None),
+                };
+                if codec::is_terminator(&line) {
+                    self.event_state = EventState::Idle;
+                    return Ok(Some(ResponseEvent::End));
+                }
+                let line = if line.starts_with(b".") {
+                    line.slice(1..)
+                } else {
+                    line
+                };
+                Ok(Some(ResponseEvent::Line(line)))
+            }
+            #[cfg(feature = "compression")]
+            EventState::Compressed => loop {
+                let block = self
+                    .gzip_block
+                    .as_mut()
+                    .expect("compressed block is being decoded");
+                if let Some(line) = block.take_line() {
+                    // The decompressed data may carry its own terminator
+                    if codec::is_terminator(&line) {
+                        continue;
+                    }
+                    let line = if line.starts_with(b".") {
+                        line.slice(1..)
+                    } else {
+                        line
+                    };
+                    return Ok(Some(ResponseEvent::Line(line)));
+                }
+                if block.is_complete() {
+                    let rest = block.take_rest();
+                    if !rest.is_empty() {
+                        return Ok(Some(ResponseEvent::Line(rest)));
+                    }
+                    self.gzip_block = None;
+                    self.event_state = EventState::Idle;
+                    return Ok(Some(ResponseEvent::End));
+                }
+                if !block.advance(&mut self.read_buffer)? {
+                    return Ok(None);
+                }
+            },
+            EventState::Idle => {
+                #[cfg(feature = "compression")]
+                if self.gzip_block.is_some() {
+                    return Err(Error::InvalidResponse(
+                        "A reply is being decoded with decode_response".to_string(),
+                    ));
+                }
+                let line = match self.take_line(false) {
+                    Some(line) => line,
+                    None => return Ok(None),
+                };
+                let code = parse_status_code(&line).ok_or_else(|| {
+                    Error::Parse(format!(
+                        "Invalid status line: {}",
+                        String::from_utf8_lossy(&line).trim_end()
+                    ))
+                })?;
+
+                let command = self.in_flight.pop_front();
+                let multiline = match &command {
+                    Some(command) => command.is_multiline_response(code),
+                    None => is_multiline_response(code),
+                };
+
+                // The status line alone carries everything the state machine
+                // needs; the data block is never part of the parsed response
+                let parsed = match &command {
+                    Some(command) => Response::parse_for_command(&line, command),
+                    None => Response::parse(&line),
+                };
+                if let Ok(response) = parsed {
+                    self.update_state_for_response(command.as_ref(), &response)?;
+                }
+
+                let text = String::from_utf8_lossy(&line);
+                let message = text
+                    .trim_end_matches(&['\r', '\n'][..])
+                    .get(4..)
+                    .unwrap_or("")
+                    .to_string();
+
+                self.event_state = if multiline && self.is_compressed_block(&line) {
+                    self.start_compressed_block(Bytes::new())?
+                } else if multiline {
+                    EventState::Block
+                } else {
+                    EventState::PendingEnd
+                };
+                Ok(Some(ResponseEvent::Status {
+                    code,
+                    message,
+                    multiline,
+                }))
+            }
+        }
+    }
+
     /// Get the current client state.
     pub fn state(&self) -> &str {
         match self.state {
@@ -86,12 +419,25 @@ impl Client {
             ClientState::WaitingForResponse => "waiting",
             ClientState::Reader => "reader",
             ClientState::Authenticated => "authenticated",
-            ClientState::GroupSelected { .. } => "group_selected",
+            ClientState::GroupSelected => "group_selected",
             ClientState::Posting => "posting",
             ClientState::Closed => "closed",
         }
     }
 
+    /// Number of encoded commands whose replies have not been decoded yet.
+    pub fn pending_commands(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Check whether another command may be encoded now.
+    ///
+    /// This is the case unless the connection is closed or the last
+    /// outstanding command is a pipeline barrier.
+    pub fn can_pipeline(&self) -> bool {
+        self.state != ClientState::Closed && self.barrier_cleared()
+    }
+
     /// Check if the client is ready to send commands.
     pub fn is_ready(&self) -> bool {
         !matches!(
@@ -102,18 +448,31 @@ impl Client {
 
     /// Get the currently selected group, if any.
     pub fn current_group(&self) -> Option<&str> {
-        if let ClientState::GroupSelected { group } = &self.state {
-            Some(group)
-        } else {
-            None
+        self.group.as_deref()
+    }
+
+    /// Get the GROUP command that must precede `command`, if any.
+    ///
+    /// Article numbers are only meaningful in the currently selected group,
+    /// so a command naming an [`ArticleSpec::GroupNumber`] of another group
+    /// is rejected by [`Client::encode_command`] with
+    /// [`Error::GroupMismatch`]. Send the returned GROUP command and decode
+    /// its reply first.
+    ///
+    /// [`ArticleSpec::GroupNumber`]: crate::command::ArticleSpec::GroupNumber
+    pub fn group_selection_for(&self, command: &Command) -> Option<Command> {
+        let group = spec_group(command)?;
+        if self.current_group() == Some(group.as_str()) {
+            return None;
         }
+        Some(Command::Group(group.clone()))
     }
 
     /// Check if the client is authenticated.
     pub fn is_authenticated(&self) -> bool {
         matches!(
             self.state,
-            ClientState::Authenticated | ClientState::GroupSelected { .. }
+            ClientState::Authenticated | ClientState::GroupSelected
         )
     }
 
@@ -126,7 +485,83 @@ impl Client {
         self.posting_allowed
     }
 
+    /// Whether no pipeline barrier is waiting for its reply
+    fn barrier_cleared(&self) -> bool {
+        !self
+            .in_flight
+            .back()
+            .is_some_and(Command::is_pipeline_barrier)
+    }
+
+    /// Remove the next complete LF-terminated line from the buffer.
+    ///
+    /// With `resume`, the search continues after the bytes scanned by the
+    /// previous call; otherwise it starts at the beginning of the buffer.
+    fn take_line(&mut self, resume: bool) -> Option<Bytes> {
+        let start = if resume { self.scanned } else { 0 };
+        match self.read_buffer[start..].iter().position(|&b| b == b'\n') {
+            Some(pos) => {
+                self.scanned = 0;
+                Some(self.read_buffer.split_to(start + pos + 1).freeze())
+            }
+            None => {
+                self.scanned = self.read_buffer.len();
+                None
+            }
+        }
+    }
+
+    /// Whether the data block after this status line is compressed
+    /// (XFEATURE COMPRESS GZIP)
+    fn is_compressed_block(&self, status_line: &[u8]) -> bool {
+        self.gzip_terminator.is_some()
+            && status_line
+                .windows(13)
+                .any(|window| window.eq_ignore_ascii_case(b"COMPRESS=GZIP"))
+    }
+
+    /// Start decoding a compressed data block after the given status line.
+    #[cfg(feature = "compression")]
+    fn start_compressed_block(&mut self, status: Bytes) -> Result<EventState> {
+        let terminator = self.gzip_terminator.unwrap_or_default();
+        self.gzip_block = Some(Box::new(GzipBlock::new(status, terminator)));
+        Ok(EventState::Compressed)
+    }
+
+    /// Without the `compression` feature, compressed data cannot be decoded.
+    #[cfg(not(feature = "compression"))]
+    fn start_compressed_block(&mut self, _status: Bytes) -> Result<EventState> {
+        Err(Error::InvalidResponse(
+            "Compressed replies require the compression feature".to_string(),
+        ))
+    }
+
+    /// Continue receiving a compressed data block, returning the reply as
+    /// if it had been sent uncompressed once the block is complete.
+    #[cfg(feature = "compression")]
+    fn extract_compressed_response(&mut self) -> Result<Option<Vec<u8>>> {
+        let block = self
+            .gzip_block
+            .as_mut()
+            .expect("compressed block is being decoded");
+        while !block.is_complete() {
+            if !block.advance(&mut self.read_buffer)? {
+                return Ok(None);
+            }
+        }
+        let block = self
+            .gzip_block
+            .take()
+            .expect("compressed block is complete");
+        Ok(Some(block.into_response()))
+    }
+
     fn extract_complete_response(&mut self) -> Result<Option<Vec<u8>>> {
+        #[cfg(feature = "compression")]
+        if self.gzip_block.is_some() {
+            return self.extract_compressed_response();
+        }
+
         // Look for complete response in buffer using byte operations
         // to handle non-UTF-8 content in article headers/bodies
 
@@ -145,20 +580,36 @@ impl Client {
         // Extract the status code (first 3 bytes should be ASCII digits)
         let code = parse_status_code(&self.read_buffer[..3]);
         if let Some(code) = code {
-            if is_multiline_response(code) {
-                // Look for terminator "\r\n.\r\n"
-                if let Some(term_pos) = find_terminator(&self.read_buffer) {
-                    let response_len = term_pos + 5; // include terminator
+            let multiline = match self.in_flight.front() {
+                Some(command) => command.is_multiline_response(code),
+                None => is_multiline_response(code),
+            };
+            if multiline && self.is_compressed_block(&self.read_buffer[..end_pos]) {
+                let status = self.read_buffer.split_to(end_pos + 2).freeze();
+                self.scanned = 0;
+                self.start_compressed_block(status)?;
+                #[cfg(feature = "compression")]
+                return self.extract_compressed_response();
+            }
+            if multiline {
+                // Look for terminator "\r\n.\r\n", resuming where the last
+                // search stopped (minus the length of a partial terminator)
+                let start = self.scanned.saturating_sub(4).max(end_pos);
+                if let Some(term_pos) = find_terminator(&self.read_buffer[start..]) {
+                    let response_len = start + term_pos + 5; // include terminator
                     let response = self.read_buffer.split_to(response_len).to_vec();
+                    self.scanned = 0;
                     return Ok(Some(response));
                 } else {
                     // Need more data
+                    self.scanned = self.read_buffer.len();
                     return Ok(None);
                 }
             } else {
                 // Single-line response
                 let response_len = end_pos + 2; // include \r\n
                 let response = self.read_buffer.split_to(response_len).to_vec();
+                self.scanned = 0;
                 return Ok(Some(response));
             }
         }
@@ -170,42 +621,40 @@ impl Client {
     /// Validate that a command can be executed in the current state
     fn validate_command_requirements(&self, command: &Command) -> Result<()> {
         match command {
-            // Commands that require a group to be selected (RFC 3977)
-            Command::Last | Command::Next => {
-                if self.current_group().is_none() {
-                    return Err(Error::Protocol {
-                        code: 412,
-                        message: "No newsgroup has been selected".to_string(),
-                    });
-                }
-            }
-            Command::Over { range: None } | Command::Hdr { range: None, .. } => {
-                // OVER and HDR without range require current group selection
-                if self.current_group().is_none() {
-                    return Err(Error::Protocol {
-                        code: 412,
-                        message: "No newsgroup has been selected".to_string(),
-                    });
-                }
-            }
-            Command::Xover { range: None } | Command::Xhdr { range: None, .. } => {
-                // XOVER and XHDR without range require current group selection
-                if self.current_group().is_none() {
-                    return Err(Error::Protocol {
-                        code: 412,
-                        message: "No newsgroup has been selected".to_string(),
-                    });
-                }
+            // Commands that require a group to be selected (RFC 3977).
+            // OVER and HDR (and their XOVER/XHDR and XZVER/XZHDR forms)
+            // without a range operate on the current group as well.
+            Command::Last
+            | Command::Next
+            | Command::Over { range: None }
+            | Command::Hdr { range: None, .. }
+            | Command::Xover { range: None }
+            | Command::Xhdr { range: None, .. }
+            | Command::Xzver { range: None }
+            | Command::Xzhdr { range: None, .. }
+                if self.current_group().is_none() =>
+            {
+                Err(Error::Protocol {
+                    code: 412,
+                    message: "No newsgroup has been selected".to_string(),
+                })
             }
+            // Article numbers must refer to the selected group
+            _ if self.group_selection_for(command).is_some() => Err(Error::GroupMismatch {
+                requested: spec_group(command)
+                    .map(GroupName::to_string)
+                    .unwrap_or_default(),
+                selected: self.group.clone(),
+            }),
             // Commands that might require authentication based on server policy
             Command::Post => {
                 // Note: Some servers require authentication for posting
                 // This is server-dependent, so we don't enforce it here
+                Ok(())
             }
             // Most other commands don't have strict prerequisites
-            _ => {}
+            _ => Ok(()),
         }
-        Ok(())
     }
 
     fn update_state_for_command(&mut self, command: &Command) -> Result<()> {
@@ -219,7 +668,11 @@ impl Client {
             Command::ModeReader => {
                 self.state = ClientState::WaitingForResponse;
             }
-            Command::AuthInfoUser(_) | Command::AuthInfoPass(_) => {
+            Command::AuthInfoUser(_)
+            | Command::AuthInfoPass(_)
+            | Command::AuthInfoSasl { .. }
+            | Command::SaslResponse(_)
+            | Command::SaslCancel => {
                 self.state = ClientState::WaitingForResponse;
             }
             Command::Group(_) => {
@@ -235,19 +688,35 @@ impl Client {
         Ok(())
     }
 
-    fn update_state_for_response(&mut self, response: &Response) -> Result<()> {
+    fn update_state_for_response(
+        &mut self,
+        command: Option<&Command>,
+        response: &Response,
+    ) -> Result<()> {
+        if let (
+            Some(Command::XFeatureCompressGzip { terminator }),
+            Response::Success { code: 290, .. },
+        ) = (command, response)
+        {
+            self.gzip_terminator = Some(*terminator);
+        }
+
         match response {
             Response::ModeReader { posting_allowed } => {
                 self.state = ClientState::Reader;
                 self.posting_allowed = *posting_allowed;
             }
-            Response::AuthSuccess => {
+            Response::AuthSuccess | Response::SaslSuccess(_) => {
                 self.state = ClientState::Authenticated;
             }
             Response::GroupSelected { name, .. } => {
-                self.state = ClientState::GroupSelected {
-                    group: name.clone(),
-                };
+                self.state = ClientState::GroupSelected;
+                self.group = Some(name.clone());
+            }
+            // LISTGROUP selects the group it lists, like GROUP
+            Response::ArticleListing { name, .. } if !name.is_empty() => {
+                self.state = ClientState::GroupSelected;
+                self.group = Some(name.clone());
             }
             Response::PostAccepted => {
                 self.state = ClientState::Posting;
@@ -262,13 +731,19 @@ impl Client {
             Response::Quit => {
                 self.state = ClientState::Closed;
             }
+            Response::TlsReady => {
+                // Anything received after the 382 reply predates the TLS
+                // handshake and must not be trusted (RFC 4642 Section 2.2.2)
+                self.read_buffer.clear();
+                self.scanned = 0;
+            }
             Response::Error { code, .. } => {
                 // Some errors might change state
                 if *code >= 400 && *code < 500 {
                     // Temporary errors - keep current state
                 } else {
                     // Permanent errors - might need to reset state
-                    if self.state == ClientState::WaitingForResponse {
+                    if self.state == ClientState::WaitingForResponse && self.in_flight.is_empty() {
                         // Return to previous stable state
                         self.state = if self.is_authenticated() {
                             ClientState::Authenticated
@@ -279,8 +754,9 @@ impl Client {
                 }
             }
             _ => {
-                // Most responses return to ready state
-                if matches!(self.state, ClientState::WaitingForResponse) {
+                // Most responses return to ready state once no other
+                // pipelined reply is outstanding
+                if self.state == ClientState::WaitingForResponse && self.in_flight.is_empty() {
                     self.state = if self.is_authenticated() {
                         ClientState::Authenticated
                     } else {
@@ -293,6 +769,20 @@ impl Client {
     }
 }
 
+/// Get the group of the article number a command refers to, if any.
+fn spec_group(command: &Command) -> Option<&GroupName> {
+    match command {
+        Command::Article(spec)
+        | Command::Head(spec)
+        | Command::Body(spec)
+        | Command::Stat(spec) => spec.group(),
+        _ => None,
+    }
+}
+
+/// Guess whether a reply is multi-line from its status code alone.
+///
+/// Only used when no command is outstanding, e.g. for the server greeting.
 fn is_multiline_response(code: u16) -> bool {
     matches!(code, 100..=110 | 112..=199 | 215 | 220..=222 | 224..=225 | 230 | 231)
 }
@@ -333,6 +823,7 @@ fn parse_status_code(data: &[u8]) -> Option<u16> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ArticleRange, ArticleSpec, RangeSet};
 
     #[test]
     fn test_client_creation() {
@@ -406,7 +897,7 @@ mod tests {
 
         // Select group
         client
-            .encode_command(Command::Group("misc.test".to_string()))
+            .encode_command(Command::Group("misc.test".parse().unwrap()))
             .unwrap();
         client.feed_bytes(b"211 1234 3000 4234 misc.test\r\n");
 
@@ -430,6 +921,133 @@ mod tests {
         assert_eq!(client.current_group(), Some("misc.test"));
     }
 
+    #[test]
+    fn test_listgroup_reply_is_framed_as_multiline() {
+        let mut client = Client::new();
+
+        client
+            .encode_command(Command::ListGroup {
+                group: None,
+                range: None,
+            })
+            .unwrap();
+        client.encode_command(Command::Date).unwrap();
+        client.feed_bytes(b"211 3 3000 3002 misc.test\r\n3000\r\n3001\r\n3002\r\n.\r\n");
+        client.feed_bytes(b"111 20231106123456\r\n");
+
+        let response = client.decode_response().unwrap().unwrap();
+        assert!(matches!(
+            response,
+            Response::ArticleListing { ref articles, .. } if articles == &RangeSet::from(3000..=3002)
+        ));
+
+        // The article numbers must not leak into the next reply
+        let response = client.decode_response().unwrap().unwrap();
+        assert_eq!(response, Response::Date("20231106123456".parse().unwrap()));
+        assert!(client.decode_response().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_listgroup_with_group_selects_it() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::ListGroup {
+                group: Some("misc.test".parse().unwrap()),
+                range: Some(ArticleRange::StartingAt(3000)),
+            })
+            .unwrap();
+        assert!(!client.can_pipeline());
+
+        client.feed_bytes(b"211 3 3000 3002 misc.test list follows\r\n3000\r\n3002\r\n.\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        assert!(matches!(
+            response,
+            Response::ArticleListing { count: 3, ref name, .. } if name == "misc.test"
+        ));
+        assert_eq!(client.state(), "group_selected");
+        assert_eq!(client.current_group(), Some("misc.test"));
+
+        // A failed LISTGROUP keeps the selected group
+        client
+            .encode_command(Command::ListGroup {
+                group: Some("no.such.group".parse().unwrap()),
+                range: None,
+            })
+            .unwrap();
+        client.feed_bytes(b"411 No such newsgroup\r\n");
+        client.decode_response().unwrap();
+        assert_eq!(client.current_group(), Some("misc.test"));
+    }
+
+    #[test]
+    fn test_group_reply_is_framed_as_single_line() {
+        let mut client = Client::new();
+
+        client
+            .encode_command(Command::Group("misc.test".parse().unwrap()))
+            .unwrap();
+        client.feed_bytes(b"211 1234 3000 4234 misc.test\r\n");
+
+        let response = client.decode_response().unwrap().unwrap();
+        assert!(matches!(response, Response::GroupSelected { .. }));
+    }
+
+    #[test]
+    fn test_head_reply_uses_command_framing() {
+        let mut client = Client::new();
+
+        client
+            .encode_command(Command::Head(crate::ArticleSpec::MessageId(
+                "<a@example.com>".parse().unwrap(),
+            )))
+            .unwrap();
+        client.feed_bytes(b"221 0 <a@example.com>\r\nSubject: Test\r\n");
+        assert!(client.decode_response().unwrap().is_none());
+
+        client.feed_bytes(b".\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        assert!(matches!(response, Response::Article { number: None, .. }));
+    }
+
+    #[test]
+    fn test_group_number_requires_its_group() {
+        let mut client = Client::new();
+        let article =
+            Command::Article(ArticleSpec::number_in_group("alt.test".parse().unwrap(), 5));
+
+        assert_eq!(
+            client.encode_command(article.clone()),
+            Err(Error::GroupMismatch {
+                requested: "alt.test".to_string(),
+                selected: None,
+            })
+        );
+        assert_eq!(client.pending_commands(), 0);
+
+        let group = client.group_selection_for(&article).unwrap();
+        assert_eq!(group, Command::Group("alt.test".parse().unwrap()));
+        client.encode_command(group).unwrap();
+        client.feed_bytes(b"211 10 1 10 alt.test\r\n");
+        client.decode_response().unwrap();
+
+        assert_eq!(client.group_selection_for(&article), None);
+        assert!(client.encode_command(article).is_ok());
+
+        // Other groups are still rejected, other specs need no group
+        let other = Command::Stat(ArticleSpec::number_in_group(
+            "misc.test".parse().unwrap(),
+            5,
+        ));
+        assert!(matches!(
+            client.encode_command(other),
+            Err(Error::GroupMismatch { selected: Some(ref selected), .. }) if selected == "alt.test"
+        ));
+        assert_eq!(
+            client.group_selection_for(&Command::Stat(ArticleSpec::Current)),
+            None
+        );
+    }
+
     #[test]
     fn test_authentication_flow() {
         let mut client = Client::new();
@@ -518,7 +1136,7 @@ mod tests {
 
         // OVER with range is okay without group
         let result = client.encode_command(Command::Over {
-            range: Some("100-200".to_string()),
+            range: Some(ArticleRange::Between(100, 200)),
         });
         assert!(result.is_ok());
     }
@@ -547,7 +1165,7 @@ mod tests {
 
         // Send a command that results in error
         client
-            .encode_command(Command::Group("nonexistent.group".to_string()))
+            .encode_command(Command::Group("nonexistent.group".parse().unwrap()))
             .unwrap();
         client.feed_bytes(b"411 No such newsgroup\r\n");
         let response = client.decode_response().unwrap().unwrap();
@@ -640,7 +1258,7 @@ mod tests {
 
         // Send a command
         client
-            .encode_command(Command::Group("test.group".to_string()))
+            .encode_command(Command::Group("test.group".parse().unwrap()))
             .unwrap();
 
         // Get a 5xx permanent error
@@ -808,4 +1426,484 @@ mod tests {
         client.decode_response().unwrap();
         assert!(!client.is_posting_allowed());
     }
+
+    fn collect_events(client: &mut Client) -> Vec<ResponseEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = client.decode_event().unwrap() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_decode_event_multiline_in_chunks() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::ListGroup {
+                group: None,
+                range: None,
+            })
+            .unwrap();
+
+        let data = b"211 2 3000 3001 misc.test\r\n3000\r\n3001\r\n.\r\n";
+        let mut events = Vec::new();
+        for byte in data.iter() {
+            client.feed_bytes(&[*byte]);
+            events.extend(collect_events(&mut client));
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                ResponseEvent::Status {
+                    code: 211,
+                    message: "2 3000 3001 misc.test".to_string(),
+                    multiline: true,
+                },
+                ResponseEvent::Line(Bytes::from_static(b"3000\r\n")),
+                ResponseEvent::Line(Bytes::from_static(b"3001\r\n")),
+                ResponseEvent::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_event_single_line_updates_state() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::Group("misc.test".parse().unwrap()))
+            .unwrap();
+        client.feed_bytes(b"211 1234 3000 4234 misc.test\r\n");
+
+        let events = collect_events(&mut client);
+        assert_eq!(
+            events,
+            vec![
+                ResponseEvent::Status {
+                    code: 211,
+                    message: "1234 3000 4234 misc.test".to_string(),
+                    multiline: false,
+                },
+                ResponseEvent::End,
+            ]
+        );
+        assert_eq!(client.current_group(), Some("misc.test"));
+    }
+
+    #[test]
+    fn test_decode_event_unstuffs_and_keeps_binary_lines() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::Body(crate::ArticleSpec::Current))
+            .unwrap();
+        client.feed_bytes(b"222 1 <a@b>\r\n..dot\r\nbare lf\n\xe9\r\r\n.\r\n");
+
+        let lines: Vec<Bytes> = collect_events(&mut client)
+            .into_iter()
+            .filter_map(|event| match event {
+                ResponseEvent::Line(line) => Some(line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lines.concat(), b".dot\r\nbare lf\n\xe9\r\r\n");
+    }
+
+    #[test]
+    fn test_decode_event_bounded_buffer() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::Body(crate::ArticleSpec::Current))
+            .unwrap();
+        client.feed_bytes(b"222 1 <a@b>\r\n");
+        assert!(matches!(
+            client.decode_event().unwrap(),
+            Some(ResponseEvent::Status { code: 222, .. })
+        ));
+
+        let line = [b'x'; 78];
+        let mut total = 0;
+        for _ in 0..10_000 {
+            client.feed_bytes(&line);
+            client.feed_bytes(b"\r\n");
+            while let Some(event) = client.decode_event().unwrap() {
+                if let ResponseEvent::Line(data) = event {
+                    total += data.len();
+                }
+            }
+            // Emitted lines are dropped from the buffer right away
+            assert!(client.read_buffer.is_empty());
+        }
+        client.feed_bytes(b".\r\n");
+        assert_eq!(client.decode_event().unwrap(), Some(ResponseEvent::End));
+        assert_eq!(total, 800_000);
+    }
+
+    #[test]
+    fn test_decode_event_then_decode_response() {
+        let mut client = Client::new();
+        client.encode_command(Command::Help).unwrap();
+        client.encode_command(Command::Date).unwrap();
+        client.feed_bytes(b"100 Help follows\r\ntext\r\n");
+
+        assert!(client.decode_event().unwrap().is_some());
+        // Mixing modes inside a reply is rejected
+        assert!(client.decode_response().is_err());
+
+        client.feed_bytes(b".\r\n111 20231106123456\r\n");
+        assert_eq!(
+            client.decode_event().unwrap(),
+            Some(ResponseEvent::Line(Bytes::from_static(b"text\r\n")))
+        );
+        assert_eq!(client.decode_event().unwrap(), Some(ResponseEvent::End));
+
+        let response = client.decode_response().unwrap().unwrap();
+        assert_eq!(response, Response::Date("20231106123456".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_decode_response_byte_by_byte() {
+        // The terminator search resumes across feeds, including a terminator
+        // split between two feeds
+        let mut client = Client::new();
+        client.encode_command(Command::Capabilities).unwrap();
+        let data = b"101 Capability list:\r\nVERSION 2\r\nREADER\r\n.\r\n";
+
+        let mut response = None;
+        for byte in data.iter() {
+            assert!(response.is_none());
+            client.feed_bytes(&[*byte]);
+            response = client.decode_response().unwrap();
+        }
+        assert_eq!(
+            response,
+            Some(Response::Capabilities(vec![
+                "VERSION 2".to_string(),
+                "READER".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_decode_response_empty_multiline_block() {
+        let mut client = Client::new();
+        client.encode_command(Command::Capabilities).unwrap();
+        client.feed_bytes(b"101 Capability list:\r\n");
+        assert!(client.decode_response().unwrap().is_none());
+        client.feed_bytes(b".\r\n");
+        assert_eq!(
+            client.decode_response().unwrap(),
+            Some(Response::Capabilities(vec![]))
+        );
+    }
+
+    #[test]
+    fn test_starttls_discards_buffered_plaintext() {
+        let mut client = Client::new();
+        client.encode_command(Command::StartTls).unwrap();
+        client.feed_bytes(b"382 Continue with TLS negotiation\r\n111 19990101000000\r\n");
+
+        assert_eq!(client.decode_response().unwrap(), Some(Response::TlsReady));
+        assert_eq!(client.decode_response().unwrap(), None);
+    }
+
+    #[test]
+    fn test_take_buffered_bytes_after_compression_active() {
+        let mut client = Client::new();
+        client.encode_command(Command::Compress).unwrap();
+        assert!(!client.can_pipeline());
+        client.feed_bytes(b"206 Compression active\r\n\x4b\x4c\x02\x00");
+
+        assert_eq!(
+            client.decode_response().unwrap(),
+            Some(Response::CompressionActive)
+        );
+        assert_eq!(&client.take_buffered_bytes()[..], b"\x4b\x4c\x02\x00");
+        assert_eq!(client.decode_response().unwrap(), None);
+        assert!(client.can_pipeline());
+    }
+
+    #[test]
+    fn test_pipelined_replies_match_commands_in_order() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::Stat(ArticleSpec::MessageId(
+                "<a@x>".parse().unwrap(),
+            )))
+            .unwrap();
+        client
+            .encode_command(Command::Head(ArticleSpec::MessageId(
+                "<b@x>".parse().unwrap(),
+            )))
+            .unwrap();
+        client
+            .encode_command(Command::Stat(ArticleSpec::MessageId(
+                "<c@x>".parse().unwrap(),
+            )))
+            .unwrap();
+        assert_eq!(client.pending_commands(), 3);
+
+        client.feed_bytes(
+            b"430 No such article\r\n221 0 <b@x>\r\nSubject: b\r\n.\r\n223 0 <c@x>\r\n",
+        );
+
+        assert!(client
+            .decode_response()
+            .unwrap()
+            .unwrap()
+            .is_no_such_article());
+        assert!(matches!(
+            client.decode_response().unwrap(),
+            Some(Response::Article { .. })
+        ));
+        assert_eq!(client.state(), "waiting");
+        assert!(matches!(
+            client.decode_response().unwrap(),
+            Some(Response::ArticleStatus { number: 0, .. })
+        ));
+        assert_eq!(client.pending_commands(), 0);
+        assert_eq!(client.state(), "reader");
+    }
+
+    #[test]
+    fn test_streaming_commands_stay_outstanding() {
+        let mut client = Client::new();
+        client.encode_command(Command::ModeStream).unwrap();
+        assert!(!client.can_pipeline());
+        client.feed_bytes(b"203 Streaming permitted\r\n");
+        assert_eq!(
+            client.decode_response().unwrap(),
+            Some(Response::StreamingPermitted)
+        );
+
+        for id in ["<a@x>", "<b@x>", "<c@x>"] {
+            client
+                .encode_command(Command::Check {
+                    message_id: id.parse().unwrap(),
+                })
+                .unwrap();
+        }
+        client
+            .encode_command(Command::Takethis {
+                message_id: "<d@x>".parse().unwrap(),
+                article: b"Subject: d\r\n\r\nbody\r\n".to_vec(),
+            })
+            .unwrap();
+        assert!(client.can_pipeline());
+        assert_eq!(client.pending_commands(), 4);
+
+        client.feed_bytes(b"238 <a@x>\r\n431 <b@x>\r\n438 <c@x>\r\n239 <d@x>\r\n");
+        let mut replies = Vec::new();
+        while let Some(response) = client.decode_response().unwrap() {
+            replies.push(response);
+        }
+        assert!(
+            matches!(&replies[0], Response::CheckWanted { message_id } if message_id == "<a@x>")
+        );
+        assert!(matches!(replies[1], Response::CheckDeferred { .. }));
+        assert!(matches!(replies[2], Response::CheckNotWanted { .. }));
+        assert!(matches!(replies[3], Response::TakethisAccepted { .. }));
+        assert_eq!(client.pending_commands(), 0);
+    }
+
+    #[test]
+    fn test_pipeline_barrier_blocks_until_reply() {
+        let mut client = Client::new();
+        client.encode_command(Command::Date).unwrap();
+        client
+            .encode_command(Command::Group("misc.test".parse().unwrap()))
+            .unwrap();
+        assert!(!client.can_pipeline());
+        assert!(matches!(
+            client.encode_command(Command::Next),
+            Err(Error::InvalidCommand(_))
+        ));
+        assert_eq!(client.pending_commands(), 2);
+
+        client.feed_bytes(b"111 20231106123456\r\n211 3 1 3 misc.test\r\n");
+        client.decode_response().unwrap().unwrap();
+        assert!(!client.can_pipeline());
+        client.decode_response().unwrap().unwrap();
+        assert!(client.can_pipeline());
+        assert_eq!(client.current_group(), Some("misc.test"));
+        client.encode_command(Command::Next).unwrap();
+    }
+
+    #[test]
+    fn test_sasl_plain_with_initial_response() {
+        let mut client = Client::new();
+        let request = client
+            .start_sasl(Box::new(sasl::Plain::new("tim", "tanstaaf")))
+            .unwrap();
+        assert_eq!(
+            &request[..],
+            b"AUTHINFO SASL PLAIN AHRpbQB0YW5zdGFhZg==\r\n"
+        );
+
+        client.feed_bytes(b"281 Authentication accepted\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        assert_eq!(client.continue_sasl(&response).unwrap(), None);
+        assert!(client.is_authenticated());
+    }
+
+    #[test]
+    fn test_sasl_long_initial_response_waits_for_challenge() {
+        let mut client = Client::new();
+        let password = "x".repeat(400);
+        let request = client
+            .start_sasl(Box::new(sasl::Plain::new("tim", &password)))
+            .unwrap();
+        assert_eq!(&request[..], b"AUTHINFO SASL PLAIN\r\n");
+
+        client.feed_bytes(b"383 =\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        let next = client.continue_sasl(&response).unwrap().unwrap();
+        let expected = format!(
+            "{}\r\n",
+            sasl::encode_data(format!("\0tim\0{password}").as_bytes())
+        );
+        assert_eq!(&next[..], expected.as_bytes());
+
+        client.feed_bytes(b"283 =\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        assert_eq!(client.continue_sasl(&response).unwrap(), None);
+        assert!(client.is_authenticated());
+    }
+
+    #[test]
+    fn test_sasl_cancels_on_mechanism_error() {
+        let mut client = Client::new();
+        client
+            .start_sasl(Box::new(sasl::External::new(None)))
+            .unwrap();
+
+        client.feed_bytes(b"383 YWJj\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        let next = client.continue_sasl(&response).unwrap().unwrap();
+        assert_eq!(&next[..], b"*\r\n");
+
+        client.feed_bytes(b"481 Authentication cancelled\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        assert!(matches!(
+            client.continue_sasl(&response),
+            Err(Error::InvalidResponse(_))
+        ));
+        assert!(!client.is_authenticated());
+    }
+
+    #[test]
+    fn test_sasl_rejected() {
+        let mut client = Client::new();
+        client
+            .start_sasl(Box::new(sasl::Plain::new("tim", "wrong")))
+            .unwrap();
+        client.feed_bytes(b"481 Authentication failed\r\n");
+        let response = client.decode_response().unwrap().unwrap();
+        assert!(matches!(
+            client.continue_sasl(&response),
+            Err(Error::Protocol { code: 481, .. })
+        ));
+        assert!(matches!(
+            client.continue_sasl(&response),
+            Err(Error::InvalidCommand(_))
+        ));
+    }
+
+    #[cfg(feature = "compression")]
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_xfeature_gzip_reply_is_decompressed() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::XFeatureCompressGzip { terminator: true })
+            .unwrap();
+        client.feed_bytes(b"290 feature enabled\r\n");
+        client.decode_response().unwrap().unwrap();
+
+        client
+            .encode_command(Command::Xover {
+                range: Some(ArticleRange::Between(1, 2)),
+            })
+            .unwrap();
+        client.encode_command(Command::Date).unwrap();
+        let mut data = b"224 xover information follows [COMPRESS=GZIP]\r\n".to_vec();
+        data.extend_from_slice(&zlib(
+            b"1\tOne\tfrom@x\tDate\t<1@x>\t\t10\t1\r\n2\tTwo\tfrom@x\tDate\t<2@x>\t\t10\t1\r\n.\r\n",
+        ));
+        data.extend_from_slice(b".\r\n111 20240101000000\r\n");
+
+        // Deliver in pieces to exercise resumption
+        let mut responses = Vec::new();
+        for piece in data.chunks(7) {
+            client.feed_bytes(piece);
+            while let Some(response) = client.decode_response().unwrap() {
+                responses.push(response);
+            }
+        }
+        assert_eq!(responses.len(), 2);
+        match &responses[0] {
+            Response::OverviewData(overview) => {
+                assert_eq!(overview.len(), 2);
+                assert_eq!(overview[1].fields[1], "Two");
+            }
+            other => panic!("Expected OverviewData, got {other:?}"),
+        }
+        assert_eq!(
+            responses[1],
+            Response::Date("20240101000000".parse().unwrap())
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_xfeature_gzip_reply_events() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::XFeatureCompressGzip { terminator: false })
+            .unwrap();
+        client.feed_bytes(b"290 feature enabled\r\n");
+        client.decode_response().unwrap().unwrap();
+
+        client
+            .encode_command(Command::Xhdr {
+                field: "Subject".to_string(),
+                range: Some(ArticleRange::Between(1, 2)),
+            })
+            .unwrap();
+        client.feed_bytes(b"221 Header follows [COMPRESS=GZIP]\r\n");
+        client.feed_bytes(&zlib(b"1 One\r\n..2 Two\r\n.\r\n"));
+
+        let mut lines = Vec::new();
+        loop {
+            match client.decode_event().unwrap().unwrap() {
+                ResponseEvent::Status { code, .. } => assert_eq!(code, 221),
+                ResponseEvent::Line(line) => lines.push(line),
+                ResponseEvent::End => break,
+            }
+        }
+        assert_eq!(lines, [&b"1 One\r\n"[..], b".2 Two\r\n"]);
+        assert_eq!(client.decode_event().unwrap(), None);
+    }
+
+    #[test]
+    fn test_compressed_marker_ignored_without_xfeature() {
+        let mut client = Client::new();
+        client
+            .encode_command(Command::Xover {
+                range: Some(ArticleRange::Number(1)),
+            })
+            .unwrap();
+        client.feed_bytes(b"224 Overview [COMPRESS=GZIP]\r\n1\tOne\tf\td\t<1@x>\t\t1\t1\r\n.\r\n");
+        assert!(matches!(
+            client.decode_response().unwrap(),
+            Some(Response::OverviewData(overview)) if overview.len() == 1
+        ));
+    }
 }
@@ -0,0 +1,188 @@
+//! Multi-line data block encoding (RFC 3977 Section 3.1.1).
+//!
+//! Multi-line data blocks are terminated by a line containing a single ".".
+//! To keep that line unambiguous, every line of the block that starts with
+//! "." gets an extra "." prepended on the wire ("dot-stuffing"), which the
+//! receiver removes again ("dot-unstuffing").
+//!
+//! Both directions accept CRLF as well as bare LF line endings.
+
+use std::ops::{Index, RangeFrom};
+
+/// Encode data as a multi-line data block ready to be sent to the server.
+///
+/// Line endings are normalized to CRLF, lines starting with "." are
+/// dot-stuffed and the terminating ".\r\n" line is appended. This is the
+/// encoding used for articles sent with POST and IHAVE.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::codec::dot_stuff;
+///
+/// let block = dot_stuff(b"Subject: Test\n\n.hidden\nbody\n");
+/// assert_eq!(block, b"Subject: Test\r\n\r\n..hidden\r\nbody\r\n.\r\n");
+/// ```
+pub fn dot_stuff(data: &[u8]) -> Vec<u8> {
+    let mut block = Vec::with_capacity(data.len() + data.len() / 32 + 5);
+
+    let mut lines = data.split(|&b| b == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        // A trailing line ending leaves an empty final piece, not a line
+        if line.is_empty() && lines.peek().is_none() {
+            break;
+        }
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.starts_with(b".") {
+            block.push(b'.');
+        }
+        block.extend_from_slice(line);
+        block.extend_from_slice(b"\r\n");
+    }
+
+    block.extend_from_slice(b".\r\n");
+    block
+}
+
+/// Decode a multi-line data block received from the server.
+///
+/// `block` is the data following the status line. Decoding stops at the
+/// terminating "." line if present. The stuffed leading dots are removed,
+/// while all other bytes, including the original line endings, are kept
+/// unchanged.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::codec::dot_unstuff;
+///
+/// let data = dot_unstuff(b"..hidden\r\nbody\r\n.\r\n");
+/// assert_eq!(data, b".hidden\r\nbody\r\n");
+/// ```
+pub fn dot_unstuff(block: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(block.len());
+
+    let mut rest = block;
+    while !rest.is_empty() {
+        let line_len = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(rest.len(), |pos| pos + 1);
+        let (line, next) = rest.split_at(line_len);
+        rest = next;
+
+        if is_terminator(line) {
+            break;
+        }
+        data.extend_from_slice(unstuff_line(line));
+    }
+
+    data
+}
+
+/// Check whether a line is the terminator of a multi-line data block.
+///
+/// The line may include its line ending.
+pub fn is_terminator(line: &[u8]) -> bool {
+    matches!(line, b"." | b".\n" | b".\r\n")
+}
+
+/// Remove the stuffed leading dot from a single line of a data block.
+///
+/// Works on both `str` and byte slices. The terminator line itself should be
+/// detected with [`is_terminator`] before calling this.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::codec::unstuff_line;
+///
+/// assert_eq!(unstuff_line("..signature"), ".signature");
+/// assert_eq!(unstuff_line(&b"plain"[..]), b"plain");
+/// ```
+pub fn unstuff_line<L>(line: &L) -> &L
+where
+    L: AsRef<[u8]> + Index<RangeFrom<usize>, Output = L> + ?Sized,
+{
+    if line.as_ref().starts_with(b".") {
+        &line[1..]
+    } else {
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_stuff_crlf() {
+        let block = dot_stuff(b"line one\r\n.line two\r\n");
+        assert_eq!(block, b"line one\r\n..line two\r\n.\r\n");
+    }
+
+    #[test]
+    fn test_dot_stuff_bare_lf() {
+        let block = dot_stuff(b"line one\n.\nline three");
+        assert_eq!(block, b"line one\r\n..\r\nline three\r\n.\r\n");
+    }
+
+    #[test]
+    fn test_dot_stuff_empty() {
+        assert_eq!(dot_stuff(b""), b".\r\n");
+    }
+
+    #[test]
+    fn test_dot_stuff_keeps_empty_lines() {
+        let block = dot_stuff(b"Subject: x\r\n\r\nbody\r\n\r\n");
+        assert_eq!(block, b"Subject: x\r\n\r\nbody\r\n\r\n.\r\n");
+    }
+
+    #[test]
+    fn test_dot_unstuff_crlf() {
+        let data = dot_unstuff(b"..one\r\ntwo\r\n...\r\n.\r\n");
+        assert_eq!(data, b".one\r\ntwo\r\n..\r\n");
+    }
+
+    #[test]
+    fn test_dot_unstuff_bare_lf() {
+        let data = dot_unstuff(b"..one\ntwo\n.\n");
+        assert_eq!(data, b".one\ntwo\n");
+    }
+
+    #[test]
+    fn test_dot_unstuff_stops_at_terminator() {
+        let data = dot_unstuff(b"one\r\n.\r\n211 trailing\r\n");
+        assert_eq!(data, b"one\r\n");
+    }
+
+    #[test]
+    fn test_dot_unstuff_without_terminator() {
+        let data = dot_unstuff(b"one\r\n..two");
+        assert_eq!(data, b"one\r\n.two");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let article = b"Subject: dots\r\n\r\n.\r\n..\r\n.leading\r\nplain\r\n";
+        let block = dot_stuff(article);
+        assert_eq!(dot_unstuff(&block), article);
+    }
+
+    #[test]
+    fn test_is_terminator() {
+        assert!(is_terminator(b".\r\n"));
+        assert!(is_terminator(b".\n"));
+        assert!(is_terminator(b"."));
+        assert!(!is_terminator(b"..\r\n"));
+        assert!(!is_terminator(b"\r\n"));
+    }
+
+    #[test]
+    fn test_unstuff_line() {
+        assert_eq!(unstuff_line(".."), ".");
+        assert_eq!(unstuff_line(".x"), "x");
+        assert_eq!(unstuff_line("x."), "x.");
+        assert_eq!(unstuff_line(&b"..\r\n"[..]), b".\r\n");
+    }
+}
@@ -1,6 +1,15 @@
 //! NNTP command types and encoding.
 
+use std::fmt;
+use std::ops::{RangeFrom, RangeInclusive};
+use std::str::FromStr;
+
+use crate::codec;
 use crate::error::{Error, Result};
+use crate::ident::{GroupName, MessageId};
+use crate::sasl;
+use crate::timestamp::Timestamp;
+use crate::wildmat::Wildmat;
 
 /// LIST command variants as specified in RFC 3977
 #[derive(Debug, Clone, PartialEq)]
@@ -47,10 +56,19 @@ pub enum Command {
     AuthInfoPass(String),
 
     /// Select a newsgroup
-    Group(String),
+    Group(GroupName),
 
-    /// List articles in current group with optional range
-    ListGroup(Option<String>),
+    /// List the article numbers of a group (RFC 3977 Section 6.1.2)
+    ///
+    /// With a group, it is selected as by GROUP before it is listed.
+    /// Without one, the currently selected group is listed.
+    ListGroup {
+        /// Group to select and list, or `None` for the current group
+        group: Option<GroupName>,
+        /// Range of articles to list; requires `group` and cannot be a
+        /// message-id
+        range: Option<ArticleRange>,
+    },
 
     /// Retrieve full article by message-id or number
     Article(ArticleSpec),
@@ -69,12 +87,8 @@ pub enum Command {
 
     /// List new newsgroups since date/time
     NewGroups {
-        /// Date in YYMMDD or YYYYMMDD format
-        date: String,
-        /// Time in HHMMSS format
-        time: String,
-        /// Optional timezone (GMT)
-        gmt: bool,
+        /// Time after which groups were created, sent in UTC
+        since: Timestamp,
         /// Optional distributions parameter
         distributions: Option<String>,
     },
@@ -83,12 +97,8 @@ pub enum Command {
     NewNews {
         /// Wildcard pattern for newsgroups
         wildmat: String,
-        /// Date in YYMMDD or YYYYMMDD format  
-        date: String,
-        /// Time in HHMMSS format
-        time: String,
-        /// Optional timezone (GMT)
-        gmt: bool,
+        /// Time after which articles arrived, sent in UTC
+        since: Timestamp,
     },
 
     /// Post an article
@@ -114,13 +124,13 @@ pub enum Command {
         /// Header field name (e.g. "Subject", "From")
         field: String,
         /// Range specification (message-id, number, or range)
-        range: Option<String>,
+        range: Option<ArticleRange>,
     },
 
     /// Retrieve overview information for articles (RFC 3977)
     Over {
         /// Range specification (message-id, number, or range)
-        range: Option<String>,
+        range: Option<ArticleRange>,
     },
 
     /// Retrieve overview information for articles (RFC 2980 legacy command)
@@ -132,7 +142,7 @@ pub enum Command {
     /// The functionality is identical to [`Command::Over`].
     Xover {
         /// Range specification (number, or range)
-        range: Option<String>,
+        range: Option<ArticleRange>,
     },
 
     /// Retrieve specific header field for articles (RFC 2980 legacy command)
@@ -146,13 +156,94 @@ pub enum Command {
         /// Header field name (e.g. "Subject", "From")
         field: String,
         /// Range specification (message-id, number, or range)
-        range: Option<String>,
+        range: Option<ArticleRange>,
+    },
+
+    /// Find articles whose header field matches a pattern (RFC 2980 XPAT)
+    ///
+    /// The reply lists the matching articles with the field's value, like
+    /// XHDR.
+    Xpat {
+        /// Header field name (e.g. "Subject", "From")
+        field: String,
+        /// Range specification (message-id, number, or range)
+        range: ArticleRange,
+        /// Wildmat patterns, an article matches if any of them matches;
+        /// a pattern cannot contain whitespace
+        patterns: Vec<String>,
+    },
+
+    /// List newsgroup descriptions (RFC 2980 XGTITLE)
+    ///
+    /// The legacy equivalent of LIST NEWSGROUPS, with an optional wildmat.
+    Xgtitle(Option<String>),
+
+    /// Retrieve compressed overview information (XZVER)
+    ///
+    /// A non-standard command offered by many Usenet providers. The reply
+    /// carries yEnc-encoded deflate data, which is decoded into an ordinary
+    /// [`Response::OverviewData`] with the `compression` feature.
+    ///
+    /// [`Response::OverviewData`]: crate::response::Response::OverviewData
+    Xzver {
+        /// Range specification (number, or range)
+        range: Option<ArticleRange>,
+    },
+
+    /// Retrieve a compressed header field for articles (XZHDR)
+    ///
+    /// The compressed counterpart of [`Command::Xhdr`], decoded into an
+    /// ordinary [`Response::HeaderData`] with the `compression` feature.
+    ///
+    /// [`Response::HeaderData`]: crate::response::Response::HeaderData
+    Xzhdr {
+        /// Header field name (e.g. "Subject", "From")
+        field: String,
+        /// Range specification (message-id, number, or range)
+        range: Option<ArticleRange>,
+    },
+
+    /// Enable compression of reply data blocks (XFEATURE COMPRESS GZIP)
+    ///
+    /// A non-standard command offered by many Usenet providers. Once the
+    /// server accepts it, multi-line replies whose status line contains
+    /// `[COMPRESS=GZIP]` carry a zlib-compressed data block, which the
+    /// [`Client`] decompresses transparently with the `compression` feature.
+    ///
+    /// [`Client`]: crate::client::Client
+    XFeatureCompressGzip {
+        /// Ask the server to send a terminator line after the compressed
+        /// data
+        terminator: bool,
     },
 
     /// Offer an article to the server
     Ihave {
         /// Message-ID of the article being offered
-        message_id: String,
+        message_id: MessageId,
+    },
+
+    /// Switch to streaming mode for article transfer (RFC 4644 Section 2.3)
+    ModeStream,
+
+    /// Ask whether the server wants an article (RFC 4644 Section 2.4)
+    ///
+    /// Unlike IHAVE, CHECK may be pipelined: many can be outstanding and
+    /// each reply carries the message-id it refers to.
+    Check {
+        /// Message-ID of the article being offered
+        message_id: MessageId,
+    },
+
+    /// Send an article without waiting for permission (RFC 4644 Section 2.5)
+    ///
+    /// The encoded command includes the dot-stuffed article, so TAKETHIS
+    /// may be pipelined like CHECK.
+    Takethis {
+        /// Message-ID of the article being sent
+        message_id: MessageId,
+        /// The article, headers and body, with any line endings
+        article: Vec<u8>,
     },
 
     /// STARTTLS command (RFC 4642).
@@ -163,43 +254,193 @@ pub enum Command {
     ///
     /// # Protocol Notes
     ///
-    /// This library provides the protocol-level support for STARTTLS but does
-    /// not perform the actual TLS handshake. Callers using the sans-IO layer
-    /// should:
+    /// The sans-IO layer provides the protocol-level support for STARTTLS but
+    /// does not perform the actual TLS handshake. Callers using it should:
     ///
     /// 1. Send the `StartTls` command
     /// 2. Receive and parse the response
     /// 3. If `TlsReady` is received, wrap the underlying stream with a TLS layer
     /// 4. Continue NNTP communication over the now-encrypted connection
     ///
+    /// When decoding `TlsReady`, the client discards any data buffered after
+    /// it, since it was received before the TLS handshake. With the `rustls`
+    /// feature, `NntpClient::starttls` performs all of these steps.
+    ///
     /// [`Response::TlsReady`]: crate::response::Response::TlsReady
     StartTls,
+
+    /// Start SASL authentication (RFC 4643 Section 2.4)
+    ///
+    /// The initial response is sent base64-encoded on the command line.
+    /// Servers answer with [`Response::SaslChallenge`] until the exchange is
+    /// complete; [`Client::start_sasl`] drives the whole exchange.
+    ///
+    /// [`Response::SaslChallenge`]: crate::response::Response::SaslChallenge
+    /// [`Client::start_sasl`]: crate::client::Client::start_sasl
+    AuthInfoSasl {
+        /// The SASL mechanism name, e.g. "PLAIN"
+        mechanism: String,
+        /// Initial response data, if the mechanism sends data first
+        initial_response: Option<Vec<u8>>,
+    },
+
+    /// Answer a SASL challenge with response data (sent base64-encoded)
+    SaslResponse(Vec<u8>),
+
+    /// Cancel a SASL exchange in response to a challenge
+    SaslCancel,
+
+    /// Activate DEFLATE compression of the connection (RFC 8054)
+    ///
+    /// After a [`Response::CompressionActive`] reply, all further data in
+    /// both directions is compressed with raw deflate. The sans-IO layer
+    /// does not compress anything itself; callers must compress what they
+    /// send and decompress what they receive before feeding it to the
+    /// client, starting with [`Client::take_buffered_bytes`]. With the
+    /// `compression` feature, `NntpClient::compress` does this.
+    ///
+    /// [`Response::CompressionActive`]: crate::response::Response::CompressionActive
+    /// [`Client::take_buffered_bytes`]: crate::client::Client::take_buffered_bytes
+    Compress,
 }
 
 /// Article specification - either message-id or article number within a group
 #[derive(Debug, Clone, PartialEq)]
 pub enum ArticleSpec {
     /// Article number within a specific group
+    ///
+    /// The group must be the selected one; `NntpClient` selects it first,
+    /// see [`Client::group_selection_for`].
+    ///
+    /// [`Client::group_selection_for`]: crate::client::Client::group_selection_for
     GroupNumber {
         /// The newsgroup name
-        group: String,
+        group: GroupName,
         /// Article number within the group
         article_number: u64,
     },
     /// Message-ID in angle brackets (globally unique)
-    MessageId(String),
+    MessageId(MessageId),
     /// Current article (no parameter)
     Current,
 }
 
 impl ArticleSpec {
     /// Create an ArticleSpec for an article number within a group
-    pub fn number_in_group(group: impl Into<String>, number: u64) -> Self {
+    pub fn number_in_group(group: GroupName, number: u64) -> Self {
         Self::GroupNumber {
-            group: group.into(),
+            group,
             article_number: number,
         }
     }
+
+    /// Get the group an article number belongs to, if the spec has one
+    pub fn group(&self) -> Option<&GroupName> {
+        match self {
+            ArticleSpec::GroupNumber { group, .. } => Some(group),
+            _ => None,
+        }
+    }
+}
+
+/// Range of articles for LISTGROUP, OVER, HDR and their RFC 2980 forms
+/// (RFC 3977 Sections 6.1.2, 8.3 and 8.5)
+///
+/// Displays in the form sent to the server and parses from it.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::ArticleRange;
+///
+/// assert_eq!(ArticleRange::from(3000..=3100).to_string(), "3000-3100");
+/// assert_eq!(
+///     "500-".parse::<ArticleRange>().unwrap(),
+///     ArticleRange::StartingAt(500)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ArticleRange {
+    /// A single article number (`n`)
+    Number(u64),
+    /// All articles from a number on (`n-`)
+    StartingAt(u64),
+    /// All articles between two numbers, inclusive (`n-m`); empty if the
+    /// second number is lower
+    Between(u64, u64),
+    /// A single article by message-id, not allowed for LISTGROUP
+    MessageId(MessageId),
+}
+
+impl ArticleRange {
+    /// Check whether this range names an article by message-id
+    pub fn is_message_id(&self) -> bool {
+        matches!(self, ArticleRange::MessageId(_))
+    }
+
+    /// Check whether an article number is in this range
+    ///
+    /// Always `false` for a message-id.
+    pub fn contains(&self, number: u64) -> bool {
+        match self {
+            ArticleRange::Number(n) => number == *n,
+            ArticleRange::StartingAt(first) => number >= *first,
+            ArticleRange::Between(first, last) => (*first..=*last).contains(&number),
+            ArticleRange::MessageId(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for ArticleRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArticleRange::Number(n) => write!(f, "{n}"),
+            ArticleRange::StartingAt(first) => write!(f, "{first}-"),
+            ArticleRange::Between(first, last) => write!(f, "{first}-{last}"),
+            ArticleRange::MessageId(id) => f.write_str(id),
+        }
+    }
+}
+
+impl FromStr for ArticleRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.starts_with('<') {
+            return Ok(ArticleRange::MessageId(s.parse()?));
+        }
+
+        let number = |n: &str| {
+            if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(Error::Parse(format!("Invalid article range: {s}")));
+            }
+            n.parse::<u64>()
+                .map_err(|_| Error::Parse(format!("Invalid article range: {s}")))
+        };
+        match s.split_once('-') {
+            None => Ok(ArticleRange::Number(number(s)?)),
+            Some((first, "")) => Ok(ArticleRange::StartingAt(number(first)?)),
+            Some((first, last)) => Ok(ArticleRange::Between(number(first)?, number(last)?)),
+        }
+    }
+}
+
+impl From<u64> for ArticleRange {
+    fn from(number: u64) -> Self {
+        ArticleRange::Number(number)
+    }
+}
+
+impl From<RangeInclusive<u64>> for ArticleRange {
+    fn from(range: RangeInclusive<u64>) -> Self {
+        ArticleRange::Between(*range.start(), *range.end())
+    }
+}
+
+impl From<RangeFrom<u64>> for ArticleRange {
+    fn from(range: RangeFrom<u64>) -> Self {
+        ArticleRange::StartingAt(range.start)
+    }
 }
 
 impl Command {
@@ -216,26 +457,32 @@ impl Command {
                 validate_parameter(pass)?;
                 format!("AUTHINFO PASS {pass}")
             }
-            Command::Group(group) => {
-                validate_parameter(group)?;
-                format!("GROUP {group}")
-            }
-            Command::ListGroup(range) => {
-                if let Some(range) = range {
-                    validate_parameter(range)?;
-                    format!("LISTGROUP {range}")
-                } else {
-                    "LISTGROUP".to_string()
+            Command::Group(group) => format!("GROUP {group}"),
+            Command::ListGroup { group, range } => match (group, range) {
+                (Some(group), Some(range)) => {
+                    if range.is_message_id() {
+                        return Err(Error::InvalidCommand(
+                            "LISTGROUP range cannot be a message-id".to_string(),
+                        ));
+                    }
+                    format!("LISTGROUP {group} {range}")
                 }
-            }
-            Command::Article(spec) => format!("ARTICLE {}", spec.encode()?),
-            Command::Head(spec) => format!("HEAD {}", spec.encode()?),
-            Command::Body(spec) => format!("BODY {}", spec.encode()?),
-            Command::Stat(spec) => format!("STAT {}", spec.encode()?),
+                (Some(group), None) => format!("LISTGROUP {group}"),
+                (None, Some(_)) => {
+                    return Err(Error::InvalidCommand(
+                        "LISTGROUP with a range requires a group".to_string(),
+                    ));
+                }
+                (None, None) => "LISTGROUP".to_string(),
+            },
+            Command::Article(spec) => format!("ARTICLE {}", spec.encode()),
+            Command::Head(spec) => format!("HEAD {}", spec.encode()),
+            Command::Body(spec) => format!("BODY {}", spec.encode()),
+            Command::Stat(spec) => format!("STAT {}", spec.encode()),
             Command::List(variant) => match variant {
                 ListVariant::Active(pattern) => {
                     if let Some(pattern) = pattern {
-                        validate_parameter(pattern)?;
+                        validate_wildmat(pattern)?;
                         format!("LIST ACTIVE {pattern}")
                     } else {
                         "LIST ACTIVE".to_string()
@@ -243,7 +490,7 @@ impl Command {
                 }
                 ListVariant::Newsgroups(pattern) => {
                     if let Some(pattern) = pattern {
-                        validate_parameter(pattern)?;
+                        validate_wildmat(pattern)?;
                         format!("LIST NEWSGROUPS {pattern}")
                     } else {
                         "LIST NEWSGROUPS".to_string()
@@ -256,7 +503,7 @@ impl Command {
                 ListVariant::OverviewFmt => "LIST OVERVIEW.FMT".to_string(),
                 ListVariant::Counts(pattern) => {
                     if let Some(pattern) = pattern {
-                        validate_parameter(pattern)?;
+                        validate_wildmat(pattern)?;
                         format!("LIST COUNTS {pattern}")
                     } else {
                         "LIST COUNTS".to_string()
@@ -267,7 +514,7 @@ impl Command {
                 ListVariant::Subscriptions => "LIST SUBSCRIPTIONS".to_string(),
                 ListVariant::Basic(pattern) => {
                     if let Some(pattern) = pattern {
-                        validate_parameter(pattern)?;
+                        validate_wildmat(pattern)?;
                         format!("LIST {pattern}")
                     } else {
                         "LIST".to_string()
@@ -275,38 +522,19 @@ impl Command {
                 }
             },
             Command::NewGroups {
-                date,
-                time,
-                gmt,
+                since,
                 distributions,
             } => {
-                validate_parameter(date)?;
-                validate_parameter(time)?;
-                let mut cmd = if *gmt {
-                    format!("NEWGROUPS {date} {time} GMT")
-                } else {
-                    format!("NEWGROUPS {date} {time}")
-                };
+                let mut cmd = format!("NEWGROUPS {} GMT", since.to_command_arguments());
                 if let Some(dist) = distributions {
                     validate_parameter(dist)?;
                     cmd.push_str(&format!(" {dist}"));
                 }
                 cmd
             }
-            Command::NewNews {
-                wildmat,
-                date,
-                time,
-                gmt,
-            } => {
-                validate_parameter(wildmat)?;
-                validate_parameter(date)?;
-                validate_parameter(time)?;
-                if *gmt {
-                    format!("NEWNEWS {wildmat} {date} {time} GMT")
-                } else {
-                    format!("NEWNEWS {wildmat} {date} {time}")
-                }
+            Command::NewNews { wildmat, since } => {
+                validate_wildmat(wildmat)?;
+                format!("NEWNEWS {wildmat} {} GMT", since.to_command_arguments())
             }
             Command::Post => "POST".to_string(),
             Command::Quit => "QUIT".to_string(),
@@ -317,7 +545,6 @@ impl Command {
             Command::Hdr { field, range } => {
                 validate_parameter(field)?;
                 if let Some(range) = range {
-                    validate_parameter(range)?;
                     format!("HDR {field} {range}")
                 } else {
                     format!("HDR {field}")
@@ -325,7 +552,6 @@ impl Command {
             }
             Command::Over { range } => {
                 if let Some(range) = range {
-                    validate_parameter(range)?;
                     format!("OVER {range}")
                 } else {
                     "OVER".to_string()
@@ -333,7 +559,6 @@ impl Command {
             }
             Command::Xover { range } => {
                 if let Some(range) = range {
-                    validate_parameter(range)?;
                     format!("XOVER {range}")
                 } else {
                     "XOVER".to_string()
@@ -342,47 +567,172 @@ impl Command {
             Command::Xhdr { field, range } => {
                 validate_parameter(field)?;
                 if let Some(range) = range {
-                    validate_parameter(range)?;
                     format!("XHDR {field} {range}")
                 } else {
                     format!("XHDR {field}")
                 }
             }
-            Command::Ihave { message_id } => {
-                if !message_id.starts_with('<') || !message_id.ends_with('>') {
+            Command::Xpat {
+                field,
+                range,
+                patterns,
+            } => {
+                validate_word(field)?;
+                if patterns.is_empty() {
                     return Err(Error::InvalidCommand(
-                        "Message-ID must be enclosed in angle brackets".to_string(),
+                        "XPAT requires at least one pattern".to_string(),
                     ));
                 }
-                validate_parameter(message_id)?;
+                for pattern in patterns {
+                    validate_wildmat(pattern)?;
+                }
+                format!("XPAT {field} {range} {}", patterns.join(" "))
+            }
+            Command::Xgtitle(wildmat) => {
+                if let Some(wildmat) = wildmat {
+                    validate_wildmat(wildmat)?;
+                    format!("XGTITLE {wildmat}")
+                } else {
+                    "XGTITLE".to_string()
+                }
+            }
+            Command::Xzver { range } => {
+                if let Some(range) = range {
+                    format!("XZVER {range}")
+                } else {
+                    "XZVER".to_string()
+                }
+            }
+            Command::Xzhdr { field, range } => {
+                validate_parameter(field)?;
+                if let Some(range) = range {
+                    format!("XZHDR {field} {range}")
+                } else {
+                    format!("XZHDR {field}")
+                }
+            }
+            Command::XFeatureCompressGzip { terminator } => {
+                if *terminator {
+                    "XFEATURE COMPRESS GZIP TERMINATOR".to_string()
+                } else {
+                    "XFEATURE COMPRESS GZIP".to_string()
+                }
+            }
+            Command::Ihave { message_id } => {
                 format!("IHAVE {message_id}")
             }
+            Command::ModeStream => "MODE STREAM".to_string(),
+            Command::Check { message_id } => {
+                format!("CHECK {message_id}")
+            }
+            Command::Takethis { message_id, .. } => {
+                format!("TAKETHIS {message_id}")
+            }
             Command::StartTls => "STARTTLS".to_string(),
+            Command::AuthInfoSasl {
+                mechanism,
+                initial_response,
+            } => {
+                validate_sasl_mechanism(mechanism)?;
+                match initial_response {
+                    Some(data) => {
+                        format!("AUTHINFO SASL {mechanism} {}", sasl::encode_data(data))
+                    }
+                    None => format!("AUTHINFO SASL {mechanism}"),
+                }
+            }
+            Command::SaslResponse(data) => sasl::encode_data(data),
+            Command::SaslCancel => "*".to_string(),
+            Command::Compress => "COMPRESS DEFLATE".to_string(),
         };
 
-        // RFC 3977: Command lines MUST NOT exceed 512 octets including CRLF
-        validate_command_length(&command_line)?;
+        if matches!(self, Command::SaslResponse(_)) {
+            // RFC 4643 Section 2.4.1: client responses may be up to 12288
+            // octets including CRLF
+            if command_line.len() > 12286 {
+                return Err(Error::InvalidCommand(format!(
+                    "SASL response exceeds maximum length of 12286 octets (got {})",
+                    command_line.len()
+                )));
+            }
+        } else {
+            // RFC 3977: Command lines MUST NOT exceed 512 octets including CRLF
+            validate_command_length(&command_line)?;
+        }
 
         let mut bytes = command_line.into_bytes();
         bytes.extend_from_slice(b"\r\n");
+        if let Command::Takethis { article, .. } = self {
+            bytes.extend_from_slice(&codec::dot_stuff(article));
+        }
         Ok(bytes)
     }
+
+    /// Check whether a reply with the given status code to this command is
+    /// followed by a multi-line data block (RFC 3977 Section 3.1.1).
+    ///
+    /// Some status codes are shared by commands with different reply formats:
+    /// 211 is single-line after GROUP but multi-line after LISTGROUP, and
+    /// 282 is only multi-line after XGTITLE.
+    pub fn is_multiline_response(&self, code: u16) -> bool {
+        match code {
+            100 | 101 | 215 | 220..=222 | 224 | 225 | 230 | 231 => true,
+            211 => matches!(self, Command::ListGroup { .. }),
+            282 => matches!(self, Command::Xgtitle(_)),
+            _ => false,
+        }
+    }
+
+    /// Check whether this command must be the last one in a pipeline
+    /// (RFC 3977 Section 3.5).
+    ///
+    /// These commands change the connection state or are followed by data
+    /// from the client, so no further command may be sent until their reply
+    /// has been received.
+    pub fn is_pipeline_barrier(&self) -> bool {
+        matches!(
+            self,
+            Command::ModeReader
+                | Command::ModeStream
+                | Command::Group(_)
+                | Command::ListGroup { group: Some(_), .. }
+                | Command::AuthInfoUser(_)
+                | Command::AuthInfoPass(_)
+                | Command::Post
+                | Command::Ihave { .. }
+                | Command::StartTls
+                | Command::AuthInfoSasl { .. }
+                | Command::SaslResponse(_)
+                | Command::SaslCancel
+                | Command::Compress
+                | Command::XFeatureCompressGzip { .. }
+                | Command::Quit
+        )
+    }
+
+    /// Check whether the server's capability list may change after this
+    /// command (RFC 3977 Section 5.2), so that a cached list must be
+    /// discarded.
+    pub fn changes_capabilities(&self) -> bool {
+        matches!(
+            self,
+            Command::ModeReader
+                | Command::AuthInfoUser(_)
+                | Command::AuthInfoPass(_)
+                | Command::StartTls
+                | Command::AuthInfoSasl { .. }
+                | Command::SaslResponse(_)
+                | Command::Compress
+        )
+    }
 }
 
 impl ArticleSpec {
-    fn encode(&self) -> Result<String> {
+    fn encode(&self) -> String {
         match self {
-            ArticleSpec::GroupNumber { article_number, .. } => Ok(article_number.to_string()),
-            ArticleSpec::MessageId(id) => {
-                if !id.starts_with('<') || !id.ends_with('>') {
-                    return Err(Error::InvalidCommand(
-                        "Message-ID must be enclosed in angle brackets".to_string(),
-                    ));
-                }
-                validate_parameter(id)?;
-                Ok(id.clone())
-            }
-            ArticleSpec::Current => Ok(String::new()),
+            ArticleSpec::GroupNumber { article_number, .. } => article_number.to_string(),
+            ArticleSpec::MessageId(id) => id.to_string(),
+            ArticleSpec::Current => String::new(),
         }
     }
 }
@@ -402,6 +752,42 @@ fn validate_parameter(param: &str) -> Result<()> {
     Ok(())
 }
 
+/// Validate a parameter that must be sent as a single argument
+fn validate_word(param: &str) -> Result<()> {
+    validate_parameter(param)?;
+    if param.contains(char::is_whitespace) {
+        return Err(Error::InvalidCommand(format!(
+            "Parameter cannot contain whitespace: {param:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// Validate a wildmat parameter (RFC 3977 Section 4)
+fn validate_wildmat(wildmat: &str) -> Result<()> {
+    validate_parameter(wildmat)?;
+    match Wildmat::new(wildmat) {
+        Ok(_) => Ok(()),
+        Err(Error::Parse(reason)) => Err(Error::InvalidCommand(reason)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Validate a SASL mechanism name (RFC 4422 Section 3.1)
+fn validate_sasl_mechanism(mechanism: &str) -> Result<()> {
+    let valid = !mechanism.is_empty()
+        && mechanism.len() <= 20
+        && mechanism
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+    if !valid {
+        return Err(Error::InvalidCommand(format!(
+            "Invalid SASL mechanism name: {mechanism:?}"
+        )));
+    }
+    Ok(())
+}
+
 /// Validate command length according to RFC 3977
 /// Command lines MUST NOT exceed 512 octets, which includes the terminating CRLF pair
 fn validate_command_length(command: &str) -> Result<()> {
@@ -428,14 +814,17 @@ mod tests {
 
     #[test]
     fn test_group_command() {
-        let cmd = Command::Group("alt.test".to_string());
+        let cmd = Command::Group("alt.test".parse().unwrap());
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"GROUP alt.test\r\n");
     }
 
     #[test]
     fn test_article_by_number() {
-        let cmd = Command::Article(ArticleSpec::number_in_group("misc.test", 123));
+        let cmd = Command::Article(ArticleSpec::number_in_group(
+            "misc.test".parse().unwrap(),
+            123,
+        ));
         let encoded = cmd.encode().unwrap();
         // Note: group is for client-side context only, wire protocol only sends article number
         assert_eq!(encoded, b"ARTICLE 123\r\n");
@@ -444,7 +833,7 @@ mod tests {
     #[test]
     fn test_article_spec_group_number() {
         let spec = ArticleSpec::GroupNumber {
-            group: "alt.test".to_string(),
+            group: "alt.test".parse().unwrap(),
             article_number: 456,
         };
         let cmd = Command::Article(spec);
@@ -454,15 +843,19 @@ mod tests {
 
     #[test]
     fn test_article_by_message_id() {
-        let cmd = Command::Article(ArticleSpec::MessageId("<test@example.com>".to_string()));
+        let cmd = Command::Article(ArticleSpec::MessageId(
+            "<test@example.com>".parse().unwrap(),
+        ));
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"ARTICLE <test@example.com>\r\n");
     }
 
     #[test]
     fn test_invalid_parameter() {
-        let cmd = Command::Group("test\r\nQUIT".to_string());
-        assert!(cmd.encode().is_err());
+        assert!("test\r\nQUIT".parse::<GroupName>().is_err());
+        assert!(Command::AuthInfoUser("test\r\nQUIT".to_string())
+            .encode()
+            .is_err());
     }
 
     #[test]
@@ -507,7 +900,7 @@ mod tests {
     fn test_hdr_command_with_range() {
         let cmd = Command::Hdr {
             field: "From".to_string(),
-            range: Some("1-10".to_string()),
+            range: Some(ArticleRange::Between(1, 10)),
         };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"HDR From 1-10\r\n");
@@ -523,7 +916,7 @@ mod tests {
     #[test]
     fn test_over_command_with_range() {
         let cmd = Command::Over {
-            range: Some("3000-3002".to_string()),
+            range: Some(ArticleRange::Between(3000, 3002)),
         };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"OVER 3000-3002\r\n");
@@ -539,12 +932,39 @@ mod tests {
     #[test]
     fn test_xover_command_with_range() {
         let cmd = Command::Xover {
-            range: Some("3000-3002".to_string()),
+            range: Some(ArticleRange::Between(3000, 3002)),
         };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"XOVER 3000-3002\r\n");
     }
 
+    #[test]
+    fn test_xzver_and_xzhdr_commands() {
+        let cmd = Command::Xzver {
+            range: Some(ArticleRange::Between(3000, 3002)),
+        };
+        assert_eq!(cmd.encode().unwrap(), b"XZVER 3000-3002\r\n");
+
+        let cmd = Command::Xzhdr {
+            field: "Subject".to_string(),
+            range: None,
+        };
+        assert_eq!(cmd.encode().unwrap(), b"XZHDR Subject\r\n");
+    }
+
+    #[test]
+    fn test_xfeature_compress_gzip_command() {
+        let cmd = Command::XFeatureCompressGzip { terminator: false };
+        assert_eq!(cmd.encode().unwrap(), b"XFEATURE COMPRESS GZIP\r\n");
+
+        let cmd = Command::XFeatureCompressGzip { terminator: true };
+        assert_eq!(
+            cmd.encode().unwrap(),
+            b"XFEATURE COMPRESS GZIP TERMINATOR\r\n"
+        );
+        assert!(cmd.is_pipeline_barrier());
+    }
+
     #[test]
     fn test_xhdr_command_simple() {
         let cmd = Command::Xhdr {
@@ -559,7 +979,7 @@ mod tests {
     fn test_xhdr_command_with_range() {
         let cmd = Command::Xhdr {
             field: "From".to_string(),
-            range: Some("1-10".to_string()),
+            range: Some(ArticleRange::Between(1, 10)),
         };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"XHDR From 1-10\r\n");
@@ -569,7 +989,9 @@ mod tests {
     fn test_xhdr_command_with_message_id() {
         let cmd = Command::Xhdr {
             field: "Subject".to_string(),
-            range: Some("<test@example.com>".to_string()),
+            range: Some(ArticleRange::MessageId(
+                "<test@example.com>".parse().unwrap(),
+            )),
         };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"XHDR Subject <test@example.com>\r\n");
@@ -577,11 +999,7 @@ mod tests {
 
     #[test]
     fn test_xover_with_invalid_range() {
-        let cmd = Command::Xover {
-            range: Some("1-10\r\n".to_string()),
-        };
-        let result = cmd.encode();
-        assert!(result.is_err());
+        assert!("<a@b>\r\nQUIT".parse::<ArticleRange>().is_err());
     }
 
     #[test]
@@ -594,21 +1012,83 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_xpat_command() {
+        let cmd = Command::Xpat {
+            field: "Subject".to_string(),
+            range: ArticleRange::Between(1, 100),
+            patterns: vec!["*rust*".to_string(), "*Rust*".to_string()],
+        };
+        assert_eq!(
+            cmd.encode().unwrap(),
+            b"XPAT Subject 1-100 *rust* *Rust*\r\n"
+        );
+
+        let cmd = Command::Xpat {
+            field: "From".to_string(),
+            range: ArticleRange::MessageId("<a@example.com>".parse().unwrap()),
+            patterns: Vec::new(),
+        };
+        assert!(matches!(cmd.encode(), Err(Error::InvalidCommand(_))));
+
+        let cmd = Command::Xpat {
+            field: "Subject".to_string(),
+            range: ArticleRange::StartingAt(1),
+            patterns: vec!["foo bar".to_string()],
+        };
+        assert!(matches!(cmd.encode(), Err(Error::InvalidCommand(_))));
+
+        let cmd = Command::Xpat {
+            field: "X Header".to_string(),
+            range: ArticleRange::StartingAt(1),
+            patterns: vec!["*".to_string()],
+        };
+        assert!(matches!(cmd.encode(), Err(Error::InvalidCommand(_))));
+    }
+
+    #[test]
+    fn test_xgtitle_command() {
+        assert_eq!(Command::Xgtitle(None).encode().unwrap(), b"XGTITLE\r\n");
+        let cmd = Command::Xgtitle(Some("comp.lang.*".to_string()));
+        assert_eq!(cmd.encode().unwrap(), b"XGTITLE comp.lang.*\r\n");
+        assert!(cmd.is_multiline_response(282));
+        assert!(!Command::Date.is_multiline_response(282));
+    }
+
     #[test]
     fn test_ihave_command() {
         let cmd = Command::Ihave {
-            message_id: "<article@example.com>".to_string(),
+            message_id: "<article@example.com>".parse().unwrap(),
         };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"IHAVE <article@example.com>\r\n");
     }
 
     #[test]
-    fn test_ihave_invalid_message_id() {
-        let cmd = Command::Ihave {
-            message_id: "invalid_id".to_string(),
+    fn test_streaming_commands() {
+        assert_eq!(Command::ModeStream.encode().unwrap(), b"MODE STREAM\r\n");
+
+        let cmd = Command::Check {
+            message_id: "<article@example.com>".parse().unwrap(),
         };
-        assert!(cmd.encode().is_err());
+        assert_eq!(cmd.encode().unwrap(), b"CHECK <article@example.com>\r\n");
+
+        let cmd = Command::Takethis {
+            message_id: "<article@example.com>".parse().unwrap(),
+            article: b"Subject: Test\n\n.hidden\n".to_vec(),
+        };
+        assert_eq!(
+            cmd.encode().unwrap(),
+            b"TAKETHIS <article@example.com>\r\nSubject: Test\r\n\r\n..hidden\r\n.\r\n"
+        );
+    }
+
+    #[test]
+    fn test_ihave_invalid_message_id() {
+        assert!(matches!(
+            "invalid_id".parse::<MessageId>(),
+            Err(Error::Parse(_))
+        ));
     }
 
     #[test]
@@ -646,37 +1126,122 @@ mod tests {
         assert_eq!(encoded, b"AUTHINFO PASS testpass\r\n");
     }
 
+    #[test]
+    fn test_auth_info_sasl_command() {
+        let cmd = Command::AuthInfoSasl {
+            mechanism: "PLAIN".to_string(),
+            initial_response: Some(b"\0user\0pass".to_vec()),
+        };
+        assert_eq!(
+            cmd.encode().unwrap(),
+            b"AUTHINFO SASL PLAIN AHVzZXIAcGFzcw==\r\n"
+        );
+
+        let cmd = Command::AuthInfoSasl {
+            mechanism: "EXTERNAL".to_string(),
+            initial_response: Some(Vec::new()),
+        };
+        assert_eq!(cmd.encode().unwrap(), b"AUTHINFO SASL EXTERNAL =\r\n");
+
+        let cmd = Command::AuthInfoSasl {
+            mechanism: "SCRAM-SHA-256".to_string(),
+            initial_response: None,
+        };
+        assert_eq!(cmd.encode().unwrap(), b"AUTHINFO SASL SCRAM-SHA-256\r\n");
+
+        let cmd = Command::AuthInfoSasl {
+            mechanism: "PLAIN X".to_string(),
+            initial_response: None,
+        };
+        assert!(cmd.encode().is_err());
+    }
+
+    #[test]
+    fn test_compress_command() {
+        let cmd = Command::Compress;
+        assert_eq!(cmd.encode().unwrap(), b"COMPRESS DEFLATE\r\n");
+    }
+
+    #[test]
+    fn test_sasl_response_commands() {
+        assert_eq!(
+            Command::SaslResponse(b"abc".to_vec()).encode().unwrap(),
+            b"YWJj\r\n"
+        );
+        assert_eq!(
+            Command::SaslResponse(Vec::new()).encode().unwrap(),
+            b"=\r\n"
+        );
+        assert_eq!(Command::SaslCancel.encode().unwrap(), b"*\r\n");
+
+        // Responses may exceed the 512 octet command line limit
+        assert!(Command::SaslResponse(vec![b'x'; 3000]).encode().is_ok());
+        assert!(Command::SaslResponse(vec![b'x'; 10000]).encode().is_err());
+    }
+
     #[test]
     fn test_listgroup_no_range() {
-        let cmd = Command::ListGroup(None);
+        let cmd = Command::ListGroup {
+            group: None,
+            range: None,
+        };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"LISTGROUP\r\n");
     }
 
+    #[test]
+    fn test_listgroup_with_group() {
+        let cmd = Command::ListGroup {
+            group: Some("misc.test".parse().unwrap()),
+            range: None,
+        };
+        let encoded = cmd.encode().unwrap();
+        assert_eq!(encoded, b"LISTGROUP misc.test\r\n");
+    }
+
     #[test]
     fn test_listgroup_with_range() {
-        let cmd = Command::ListGroup(Some("misc.test 1-100".to_string()));
+        let cmd = Command::ListGroup {
+            group: Some("misc.test".parse().unwrap()),
+            range: Some(ArticleRange::Between(1, 100)),
+        };
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"LISTGROUP misc.test 1-100\r\n");
     }
 
+    #[test]
+    fn test_listgroup_range_requires_group() {
+        let cmd = Command::ListGroup {
+            group: None,
+            range: Some(ArticleRange::Between(1, 100)),
+        };
+        assert!(matches!(cmd.encode(), Err(Error::InvalidCommand(_))));
+    }
+
     #[test]
     fn test_head_command() {
-        let cmd = Command::Head(ArticleSpec::MessageId("<test@example.com>".to_string()));
+        let cmd = Command::Head(ArticleSpec::MessageId(
+            "<test@example.com>".parse().unwrap(),
+        ));
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"HEAD <test@example.com>\r\n");
     }
 
     #[test]
     fn test_body_command() {
-        let cmd = Command::Body(ArticleSpec::MessageId("<test@example.com>".to_string()));
+        let cmd = Command::Body(ArticleSpec::MessageId(
+            "<test@example.com>".parse().unwrap(),
+        ));
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"BODY <test@example.com>\r\n");
     }
 
     #[test]
     fn test_stat_command() {
-        let cmd = Command::Stat(ArticleSpec::number_in_group("misc.test", 42));
+        let cmd = Command::Stat(ArticleSpec::number_in_group(
+            "misc.test".parse().unwrap(),
+            42,
+        ));
         let encoded = cmd.encode().unwrap();
         assert_eq!(encoded, b"STAT 42\r\n");
     }
@@ -794,23 +1359,9 @@ mod tests {
     }
 
     #[test]
-    fn test_newgroups_no_gmt() {
+    fn test_newgroups_command() {
         let cmd = Command::NewGroups {
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: false,
-            distributions: None,
-        };
-        let encoded = cmd.encode().unwrap();
-        assert_eq!(encoded, b"NEWGROUPS 20240101 120000\r\n");
-    }
-
-    #[test]
-    fn test_newgroups_with_gmt() {
-        let cmd = Command::NewGroups {
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: true,
+            since: Timestamp::from_utc(2024, 1, 1, 12, 0, 0).unwrap(),
             distributions: None,
         };
         let encoded = cmd.encode().unwrap();
@@ -820,9 +1371,7 @@ mod tests {
     #[test]
     fn test_newgroups_with_distributions() {
         let cmd = Command::NewGroups {
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: true,
+            since: Timestamp::from_utc(2024, 1, 1, 12, 0, 0).unwrap(),
             distributions: Some("local".to_string()),
         };
         let encoded = cmd.encode().unwrap();
@@ -830,27 +1379,13 @@ mod tests {
     }
 
     #[test]
-    fn test_newnews_no_gmt() {
-        let cmd = Command::NewNews {
-            wildmat: "*".to_string(),
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: false,
-        };
-        let encoded = cmd.encode().unwrap();
-        assert_eq!(encoded, b"NEWNEWS * 20240101 120000\r\n");
-    }
-
-    #[test]
-    fn test_newnews_with_gmt() {
+    fn test_newnews_command() {
         let cmd = Command::NewNews {
             wildmat: "comp.*".to_string(),
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: true,
+            since: Timestamp::from_unix(1_704_067_199),
         };
         let encoded = cmd.encode().unwrap();
-        assert_eq!(encoded, b"NEWNEWS comp.* 20240101 120000 GMT\r\n");
+        assert_eq!(encoded, b"NEWNEWS comp.* 20231231 235959 GMT\r\n");
     }
 
     #[test]
@@ -869,19 +1404,18 @@ mod tests {
 
     #[test]
     fn test_invalid_message_id_no_brackets() {
-        let cmd = Command::Article(ArticleSpec::MessageId("test@example.com".to_string()));
-        let result = cmd.encode();
-        assert!(result.is_err());
-        if let Err(Error::InvalidCommand(msg)) = result {
-            assert!(msg.contains("angle brackets"));
+        let result = "test@example.com".parse::<MessageId>();
+        if let Err(Error::Parse(msg)) = result {
+            assert!(msg.contains("test@example.com"));
         } else {
-            panic!("Expected InvalidCommand error");
+            panic!("Expected Parse error");
         }
     }
 
     #[test]
     fn test_empty_parameter_error() {
-        let cmd = Command::Group(String::new());
+        assert!(GroupName::new("").is_err());
+        let cmd = Command::AuthInfoUser(String::new());
         let result = cmd.encode();
         assert!(result.is_err());
         if let Err(Error::InvalidCommand(msg)) = result {
@@ -895,7 +1429,7 @@ mod tests {
     fn test_command_too_long() {
         // Create a command that exceeds 510 bytes
         let long_param = "x".repeat(600);
-        let cmd = Command::Group(long_param);
+        let cmd = Command::Group(long_param.parse().unwrap());
         let result = cmd.encode();
         assert!(result.is_err());
         if let Err(Error::InvalidCommand(msg)) = result {
@@ -907,9 +1441,7 @@ mod tests {
 
     #[test]
     fn test_parameter_with_newline() {
-        let cmd = Command::Group("misc.test\nQUIT".to_string());
-        let result = cmd.encode();
-        assert!(result.is_err());
+        assert!("misc.test\nQUIT".parse::<GroupName>().is_err());
     }
 
     #[test]
@@ -931,21 +1463,13 @@ mod tests {
 
     #[test]
     fn test_hdr_with_invalid_range() {
-        let cmd = Command::Hdr {
-            field: "Subject".to_string(),
-            range: Some("1-10\r\nQUIT".to_string()),
-        };
-        let result = cmd.encode();
-        assert!(result.is_err());
+        assert!("<a@b>\r\nQUIT".parse::<ArticleRange>().is_err());
+        assert!("<a b@x>".parse::<ArticleRange>().is_err());
     }
 
     #[test]
     fn test_over_with_invalid_range() {
-        let cmd = Command::Over {
-            range: Some("1-10\r\n".to_string()),
-        };
-        let result = cmd.encode();
-        assert!(result.is_err());
+        assert!("<a@b>QUIT".parse::<ArticleRange>().is_err());
     }
 
     #[test]
@@ -962,28 +1486,9 @@ mod tests {
 
     #[test]
     fn test_listgroup_invalid_range() {
-        let cmd = Command::ListGroup(Some("misc.test\r\n".to_string()));
-        assert!(cmd.encode().is_err());
-    }
-
-    #[test]
-    fn test_newgroups_invalid_date() {
-        let cmd = Command::NewGroups {
-            date: "2024\r\n0101".to_string(),
-            time: "120000".to_string(),
-            gmt: false,
-            distributions: None,
-        };
-        assert!(cmd.encode().is_err());
-    }
-
-    #[test]
-    fn test_newgroups_invalid_time() {
-        let cmd = Command::NewGroups {
-            date: "20240101".to_string(),
-            time: "12\r\n0000".to_string(),
-            gmt: false,
-            distributions: None,
+        let cmd = Command::ListGroup {
+            group: Some("misc.test".parse().unwrap()),
+            range: Some(ArticleRange::MessageId("<a@b>".parse().unwrap())),
         };
         assert!(cmd.encode().is_err());
     }
@@ -991,9 +1496,7 @@ mod tests {
     #[test]
     fn test_newgroups_invalid_distributions() {
         let cmd = Command::NewGroups {
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: false,
+            since: Timestamp::from_unix(0),
             distributions: Some("local\r\n".to_string()),
         };
         assert!(cmd.encode().is_err());
@@ -1003,29 +1506,39 @@ mod tests {
     fn test_newnews_invalid_wildmat() {
         let cmd = Command::NewNews {
             wildmat: "*\r\n".to_string(),
-            date: "20240101".to_string(),
-            time: "120000".to_string(),
-            gmt: false,
+            since: Timestamp::from_unix(0),
         };
         assert!(cmd.encode().is_err());
     }
 
     #[test]
-    fn test_ihave_invalid_message_id_no_brackets() {
-        let cmd = Command::Ihave {
-            message_id: "article@example.com".to_string(),
+    fn test_wildmat_syntax_is_validated() {
+        for wildmat in ["comp.* alt.*", "!comp.*", "comp.*,", "comp.[a-"] {
+            let cmd = Command::List(ListVariant::Active(Some(wildmat.to_string())));
+            assert!(
+                matches!(cmd.encode(), Err(Error::InvalidCommand(_))),
+                "{wildmat:?} should be rejected"
+            );
+        }
+        let cmd = Command::NewNews {
+            wildmat: "comp.*,!comp.sys.*".to_string(),
+            since: Timestamp::from_utc(2024, 1, 1, 0, 0, 0).unwrap(),
         };
-        let result = cmd.encode();
-        assert!(result.is_err());
+        assert_eq!(
+            cmd.encode().unwrap(),
+            b"NEWNEWS comp.*,!comp.sys.* 20240101 000000 GMT\r\n"
+        );
+        assert!(Command::Xgtitle(Some("a b".to_string())).encode().is_err());
+    }
+
+    #[test]
+    fn test_ihave_invalid_message_id_no_brackets() {
+        assert!("article@example.com".parse::<MessageId>().is_err());
     }
 
     #[test]
     fn test_ihave_invalid_message_id_with_newline() {
-        let cmd = Command::Ihave {
-            message_id: "<article@example.com\r\n>".to_string(),
-        };
-        let result = cmd.encode();
-        assert!(result.is_err());
+        assert!("<article@example.com\r\n>".parse::<MessageId>().is_err());
     }
 
     #[test]
@@ -1039,4 +1552,66 @@ mod tests {
         let cmd = Command::List(ListVariant::Basic(Some("misc.*\r\n".to_string())));
         assert!(cmd.encode().is_err());
     }
+
+    #[test]
+    fn test_multiline_response_depends_on_command() {
+        let group = Command::Group("misc.test".parse().unwrap());
+        let listgroup = Command::ListGroup {
+            group: None,
+            range: None,
+        };
+        assert!(!group.is_multiline_response(211));
+        assert!(listgroup.is_multiline_response(211));
+
+        let head = Command::Head(ArticleSpec::Current);
+        assert!(head.is_multiline_response(221));
+        assert!(!Command::Date.is_multiline_response(111));
+        assert!(!Command::Stat(ArticleSpec::Current).is_multiline_response(223));
+        assert!(!listgroup.is_multiline_response(412));
+    }
+
+    #[test]
+    fn test_pipeline_barriers() {
+        assert!(Command::Group("misc.test".parse().unwrap()).is_pipeline_barrier());
+        assert!(Command::AuthInfoPass("secret".to_string()).is_pipeline_barrier());
+        assert!(Command::Post.is_pipeline_barrier());
+        assert!(Command::StartTls.is_pipeline_barrier());
+        assert!(Command::Quit.is_pipeline_barrier());
+        assert!(Command::Compress.is_pipeline_barrier());
+        assert!(Command::ModeStream.is_pipeline_barrier());
+        assert!(Command::ListGroup {
+            group: Some("misc.test".parse().unwrap()),
+            range: None,
+        }
+        .is_pipeline_barrier());
+        assert!(!Command::ListGroup {
+            group: None,
+            range: None,
+        }
+        .is_pipeline_barrier());
+        assert!(!Command::Stat(ArticleSpec::Current).is_pipeline_barrier());
+        assert!(
+            !Command::Body(ArticleSpec::MessageId("<a@b>".parse().unwrap())).is_pipeline_barrier()
+        );
+        assert!(!Command::Check {
+            message_id: "<a@b>".parse().unwrap()
+        }
+        .is_pipeline_barrier());
+        assert!(!Command::Takethis {
+            message_id: "<a@b>".parse().unwrap(),
+            article: Vec::new(),
+        }
+        .is_pipeline_barrier());
+    }
+
+    #[test]
+    fn test_changes_capabilities() {
+        assert!(Command::ModeReader.changes_capabilities());
+        assert!(Command::AuthInfoPass("secret".to_string()).changes_capabilities());
+        assert!(Command::StartTls.changes_capabilities());
+        assert!(Command::SaslResponse(Vec::new()).changes_capabilities());
+        assert!(Command::Compress.changes_capabilities());
+        assert!(!Command::Group("misc.test".parse().unwrap()).changes_capabilities());
+        assert!(!Command::Capabilities.changes_capabilities());
+    }
 }
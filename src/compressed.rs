@@ -0,0 +1,345 @@
+//! Decoding of compressed replies offered by many Usenet providers.
+//!
+//! - After `XFEATURE COMPRESS GZIP`, the data blocks of replies whose status
+//!   line carries `[COMPRESS=GZIP]` are sent as a zlib stream, optionally
+//!   followed by a terminator line when `TERMINATOR` was requested.
+//! - XZVER and XZHDR reply with a regular multi-line block holding
+//!   yEnc-encoded deflate data.
+//!
+//! Both decode to the data block an uncompressed reply would have carried.
+//! A block that inflates to more than 256 MiB is rejected as a parse error.
+
+use bytes::{Buf, Bytes, BytesMut};
+use flate2::{Decompress, FlushDecompress, Status};
+
+use crate::codec;
+use crate::{Error, Result};
+
+/// Smallest amount of output space reserved per inflate call
+const INFLATE_CHUNK: usize = 8 * 1024;
+
+/// Largest amount of output space reserved per inflate call
+const INFLATE_CHUNK_MAX: usize = 1024 * 1024;
+
+/// Largest data block a compressed reply may inflate to, so that a small
+/// hostile block cannot exhaust memory
+const MAX_DECOMPRESSED_SIZE: usize = 256 * 1024 * 1024;
+
+/// A zlib-compressed data block being received after XFEATURE COMPRESS GZIP.
+pub(crate) struct GzipBlock {
+    status: Bytes,
+    /// Whether a terminator line follows the compressed data
+    terminator: bool,
+    inflate: Decompress,
+    output: Vec<u8>,
+    /// Start of the output not yet taken by `take_line`
+    read_pos: usize,
+    /// The zlib stream has ended
+    inflated: bool,
+    complete: bool,
+    /// Largest number of bytes the block may inflate to
+    limit: usize,
+}
+
+impl GzipBlock {
+    /// Start receiving the data block of a reply with the given status line.
+    pub(crate) fn new(status: Bytes, terminator: bool) -> Self {
+        Self {
+            status,
+            terminator,
+            inflate: Decompress::new(true),
+            output: Vec::new(),
+            read_pos: 0,
+            inflated: false,
+            complete: false,
+            limit: MAX_DECOMPRESSED_SIZE,
+        }
+    }
+
+    /// Consume compressed data from the front of `input`.
+    ///
+    /// Returns `false` if nothing could be done without more input.
+    pub(crate) fn advance(&mut self, input: &mut BytesMut) -> Result<bool> {
+        if self.complete {
+            return Ok(false);
+        }
+
+        if self.inflated {
+            // Only the optional terminator line remains
+            for trailer in [&b"\r\n.\r\n"[..], b".\r\n"] {
+                if input.starts_with(trailer) {
+                    input.advance(trailer.len());
+                    self.complete = true;
+                    return Ok(true);
+                }
+                if input.len() < trailer.len() && trailer.starts_with(input) {
+                    return Ok(false);
+                }
+            }
+            self.complete = true;
+            return Ok(true);
+        }
+
+        if input.is_empty() {
+            return Ok(false);
+        }
+        if self.read_pos > 0 {
+            self.output.drain(..self.read_pos);
+            self.read_pos = 0;
+        }
+        self.output
+            .reserve((input.len() * 4).clamp(INFLATE_CHUNK, INFLATE_CHUNK_MAX));
+
+        let total_in = self.inflate.total_in();
+        let total_out = self.inflate.total_out();
+        let status = self
+            .inflate
+            .decompress_vec(input, &mut self.output, FlushDecompress::None)
+            .map_err(|e| Error::Parse(format!("Invalid compressed reply data: {e}")))?;
+        let consumed = (self.inflate.total_in() - total_in) as usize;
+        let produced = self.inflate.total_out() - total_out;
+        input.advance(consumed);
+        if self.inflate.total_out() > self.limit as u64 {
+            return Err(too_large(self.limit));
+        }
+
+        if status == Status::StreamEnd {
+            self.inflated = true;
+            self.complete = !self.terminator;
+            return Ok(true);
+        }
+        Ok(consumed > 0 || produced > 0)
+    }
+
+    /// Check whether the whole block has been received.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Take the next complete line of decompressed data.
+    pub(crate) fn take_line(&mut self) -> Option<Bytes> {
+        let pending = &self.output[self.read_pos..];
+        let len = pending.iter().position(|&b| b == b'\n')? + 1;
+        let line = Bytes::copy_from_slice(&pending[..len]);
+        self.read_pos += len;
+        Some(line)
+    }
+
+    /// Take the decompressed data left after the last complete line.
+    pub(crate) fn take_rest(&mut self) -> Bytes {
+        let rest = Bytes::copy_from_slice(&self.output[self.read_pos..]);
+        self.read_pos = self.output.len();
+        rest
+    }
+
+    /// Assemble the reply as if it had been sent uncompressed.
+    pub(crate) fn into_response(self) -> Vec<u8> {
+        let block = &self.output[self.read_pos..];
+        let mut response = Vec::with_capacity(self.status.len() + block.len() + 5);
+        response.extend_from_slice(&self.status);
+        response.extend_from_slice(block);
+        if !block.is_empty() && !block.ends_with(b"\n") {
+            response.extend_from_slice(b"\r\n");
+        }
+        if block != b".\r\n" && !block.ends_with(b"\n.\r\n") {
+            response.extend_from_slice(b".\r\n");
+        }
+        response
+    }
+}
+
+/// Decode the data block of an XZVER or XZHDR reply.
+///
+/// Returns `None` if the block is not yEnc-encoded, since some servers
+/// answer with plain data instead.
+pub(crate) fn decode_xz_block(block: &[u8]) -> Result<Option<Vec<u8>>> {
+    let data = codec::dot_unstuff(block);
+    if !data.starts_with(b"=ybegin ") {
+        return Ok(None);
+    }
+    let compressed = decode_yenc(&data);
+    // Servers differ in whether the deflate data has a zlib header
+    inflate(&compressed, true, MAX_DECOMPRESSED_SIZE)
+        .or_else(|_| inflate(&compressed, false, MAX_DECOMPRESSED_SIZE))
+        .map(Some)
+}
+
+/// Decode yEnc data, skipping its header and trailer lines.
+fn decode_yenc(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(data.len());
+    for line in data.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.starts_with(b"=ybegin ") || line.starts_with(b"=ypart ") {
+            continue;
+        }
+        if line.starts_with(b"=yend") {
+            break;
+        }
+        let mut bytes = line.iter();
+        while let Some(&b) = bytes.next() {
+            let b = if b == b'=' {
+                match bytes.next() {
+                    Some(&escaped) => escaped.wrapping_sub(64),
+                    None => break,
+                }
+            } else {
+                b
+            };
+            decoded.push(b.wrapping_sub(42));
+        }
+    }
+    decoded
+}
+
+/// Inflate complete deflate data to at most `limit` bytes.
+fn inflate(data: &[u8], zlib_header: bool, limit: usize) -> Result<Vec<u8>> {
+    let mut inflate = Decompress::new(zlib_header);
+    // One byte beyond the limit tells a block at the limit from a larger one
+    let mut output = Vec::with_capacity((data.len() * 4).max(INFLATE_CHUNK).min(limit + 1));
+    loop {
+        let consumed = inflate.total_in() as usize;
+        let status = inflate
+            .decompress_vec(&data[consumed..], &mut output, FlushDecompress::Finish)
+            .map_err(|e| Error::Parse(format!("Invalid compressed reply data: {e}")))?;
+        if output.len() > limit {
+            return Err(too_large(limit));
+        }
+        match status {
+            Status::StreamEnd => return Ok(output),
+            _ if inflate.total_in() as usize == data.len() && output.len() < output.capacity() => {
+                return Err(Error::Parse("Truncated compressed reply data".to_string()));
+            }
+            _ => output.reserve_exact(output.capacity().min(limit + 1 - output.len())),
+        }
+    }
+}
+
+fn too_large(limit: usize) -> Error {
+    Error::Parse(format!(
+        "Compressed reply data inflates to more than {limit} bytes"
+    ))
+}
+
+/// Build the data block of an XZVER or XZHDR reply, as a server would.
+#[cfg(test)]
+pub(crate) fn encode_xz_block(data: &[u8]) -> Vec<u8> {
+    tests::yenc(&tests::zlib(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::{DeflateEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+
+    pub(super) fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    pub(super) fn yenc(data: &[u8]) -> Vec<u8> {
+        let mut encoded = b"=ybegin line=128 size=0 name=xzver\r\n".to_vec();
+        for chunk in data.chunks(64) {
+            for &b in chunk {
+                let b = b.wrapping_add(42);
+                if matches!(b, 0 | b'\n' | b'\r' | b'=') {
+                    encoded.extend_from_slice(&[b'=', b.wrapping_add(64)]);
+                } else {
+                    encoded.push(b);
+                }
+            }
+            encoded.extend_from_slice(b"\r\n");
+        }
+        encoded.extend_from_slice(b"=yend size=0\r\n");
+        codec::dot_stuff(&encoded)
+    }
+
+    /// Feed a block in small pieces until it is complete.
+    fn receive(block: &mut GzipBlock, data: &[u8], piece: usize) -> BytesMut {
+        let mut input = BytesMut::new();
+        let mut pieces = data.chunks(piece);
+        while !block.is_complete() {
+            if !block.advance(&mut input).unwrap() {
+                input.extend_from_slice(pieces.next().expect("block ended early"));
+            }
+        }
+        input.extend_from_slice(&pieces.flatten().copied().collect::<Vec<u8>>());
+        input
+    }
+
+    #[test]
+    fn test_gzip_block_with_terminator() {
+        let block_data = b"1\tSubject\r\n2\tOther\r\n.\r\n";
+        let mut data = zlib(block_data);
+        data.extend_from_slice(b".\r\n223 1 <next@x>\r\n");
+
+        let mut block = GzipBlock::new(Bytes::from_static(b"224 [COMPRESS=GZIP]\r\n"), true);
+        let rest = receive(&mut block, &data, 3);
+        assert_eq!(&rest[..], b"223 1 <next@x>\r\n");
+        assert_eq!(
+            block.into_response(),
+            b"224 [COMPRESS=GZIP]\r\n1\tSubject\r\n2\tOther\r\n.\r\n"
+        );
+    }
+
+    #[test]
+    fn test_gzip_block_without_terminator() {
+        let mut data = zlib(b"1\tSubject\r\n2\tOther");
+        data.extend_from_slice(b"111 20240101000000\r\n");
+
+        let mut block = GzipBlock::new(Bytes::new(), false);
+        let rest = receive(&mut block, &data, 5);
+        assert_eq!(&rest[..], b"111 20240101000000\r\n");
+        assert_eq!(block.take_line().unwrap(), &b"1\tSubject\r\n"[..]);
+        assert_eq!(block.take_line(), None);
+        assert_eq!(block.take_rest(), &b"2\tOther"[..]);
+    }
+
+    #[test]
+    fn test_gzip_block_rejects_garbage() {
+        let mut block = GzipBlock::new(Bytes::new(), true);
+        let mut input = BytesMut::from(&b"not compressed at all\r\n"[..]);
+        assert!(block.advance(&mut input).is_err());
+    }
+
+    #[test]
+    fn test_gzip_block_size_limit() {
+        let data = zlib(&b"1\tSubject\r\n".repeat(1000));
+        let mut block = GzipBlock::new(Bytes::new(), true);
+        block.limit = 1000;
+        let mut input = BytesMut::from(&data[..]);
+        let result = std::iter::repeat_with(|| block.advance(&mut input))
+            .find(|result| !matches!(result, Ok(true)))
+            .unwrap();
+        assert!(matches!(result, Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_inflate_size_limit() {
+        let data = zlib(&[b'x'; 1000]);
+        assert_eq!(inflate(&data, true, 1000).unwrap().len(), 1000);
+        assert!(matches!(inflate(&data, true, 999), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_decode_xz_block_zlib_and_raw() {
+        let overview = b"1\tSubject\tfrom@x\tDate\t<a@x>\t\t100\t5\r\n".repeat(50);
+
+        let block = yenc(&zlib(&overview));
+        assert_eq!(decode_xz_block(&block).unwrap().unwrap(), overview);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&overview).unwrap();
+        let block = yenc(&encoder.finish().unwrap());
+        assert_eq!(decode_xz_block(&block).unwrap().unwrap(), overview);
+    }
+
+    #[test]
+    fn test_decode_xz_block_plain_or_corrupt() {
+        assert_eq!(decode_xz_block(b"1\tSubject\tfrom\r\n.\r\n").unwrap(), None);
+        let block = yenc(&zlib(b"overview")[..6]);
+        assert!(decode_xz_block(&block).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Stream compression with COMPRESS DEFLATE (RFC 8054).
+//!
+//! After [`NntpClient::compress`] succeeds, everything sent and received on
+//! the connection is compressed with raw deflate (RFC 1951). Compression
+//! runs above TLS, so it can be combined with [`NntpClient::starttls`] as
+//! long as TLS is started first.
+//!
+//! [`NntpClient::compression_stats`] reports how much data went over the
+//! wire compared to the uncompressed protocol stream.
+//!
+//! [`NntpClient::compress`]: crate::net_client::NntpClient::compress
+//! [`NntpClient::starttls`]: crate::net_client::NntpClient::starttls
+//! [`NntpClient::compression_stats`]: crate::net_client::NntpClient::compression_stats
+
+use std::io;
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+
+/// Byte counts of a compressed connection.
+///
+/// Only data exchanged after compression was activated is counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// Compressed bytes received from the server
+    pub received_compressed: u64,
+    /// Bytes received after decompression
+    pub received_uncompressed: u64,
+    /// Compressed bytes sent to the server
+    pub sent_compressed: u64,
+    /// Bytes sent before compression
+    pub sent_uncompressed: u64,
+}
+
+impl CompressionStats {
+    /// Ratio of decompressed to wire bytes received, e.g. 4.0 if the server
+    /// data shrank to a quarter. Returns `None` before any data arrived.
+    pub fn receive_ratio(&self) -> Option<f64> {
+        (self.received_compressed > 0)
+            .then(|| self.received_uncompressed as f64 / self.received_compressed as f64)
+    }
+}
+
+/// Raw deflate streams in both directions of a connection.
+pub(crate) struct DeflateSession {
+    compress: Compress,
+    decompress: Decompress,
+    /// Compressed data received but not yet inflated
+    input: Vec<u8>,
+    input_pos: usize,
+    /// The server ended its deflate stream
+    finished: bool,
+    stats: CompressionStats,
+}
+
+impl DeflateSession {
+    /// Start compression. `buffered` holds data received after the 206 reply,
+    /// which is already compressed.
+    pub(crate) fn new(buffered: &[u8]) -> Self {
+        let mut session = Self {
+            compress: Compress::new(Compression::default(), false),
+            decompress: Decompress::new(false),
+            input: Vec::new(),
+            input_pos: 0,
+            finished: false,
+            stats: CompressionStats::default(),
+        };
+        session.feed(buffered);
+        session
+    }
+
+    /// Compress data to be sent, flushing so that the server can decode all
+    /// of it at once (RFC 8054 Section 2.2.2).
+    pub(crate) fn compress(&mut self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let start = self.compress.total_in();
+        let mut output = Vec::with_capacity(data.len() / 2 + 64);
+        loop {
+            if output.capacity() - output.len() < 64 {
+                output.reserve(4096);
+            }
+            let consumed = (self.compress.total_in() - start) as usize;
+            self.compress
+                .compress_vec(&data[consumed..], &mut output, FlushCompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let consumed = (self.compress.total_in() - start) as usize;
+            // A full output buffer may leave flushed data behind
+            if consumed == data.len() && output.len() < output.capacity() {
+                break;
+            }
+        }
+
+        self.stats.sent_uncompressed += data.len() as u64;
+        self.stats.sent_compressed += output.len() as u64;
+        Ok(output)
+    }
+
+    /// Add compressed data received from the server.
+    pub(crate) fn feed(&mut self, data: &[u8]) {
+        self.input.extend_from_slice(data);
+        self.stats.received_compressed += data.len() as u64;
+    }
+
+    /// Decompress received data into `buf`.
+    ///
+    /// Returns 0 if more input is needed, see [`DeflateSession::is_finished`].
+    pub(crate) fn decompress(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.finished {
+            return Ok(0);
+        }
+        let total_in = self.decompress.total_in();
+        let total_out = self.decompress.total_out();
+        let status = self
+            .decompress
+            .decompress(&self.input[self.input_pos..], buf, FlushDecompress::None)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        self.input_pos += (self.decompress.total_in() - total_in) as usize;
+        if self.input_pos == self.input.len() {
+            self.input.clear();
+            self.input_pos = 0;
+        }
+        self.finished = status == Status::StreamEnd;
+
+        let produced = (self.decompress.total_out() - total_out) as usize;
+        self.stats.received_uncompressed += produced as u64;
+        Ok(produced)
+    }
+
+    /// Check whether the server ended its deflate stream, after which no
+    /// more data can be decompressed.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Byte counts so far.
+    pub(crate) fn stats(&self) -> CompressionStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decompress everything available using a small output buffer.
+    fn inflate_all(session: &mut DeflateSession) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut buf = [0u8; 7];
+        loop {
+            let n = session.decompress(&mut buf).unwrap();
+            if n == 0 {
+                return output;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_with_flushed_messages() {
+        let mut client = DeflateSession::new(&[]);
+        let mut server = DeflateSession::new(&[]);
+
+        for line in [&b"DATE\r\n"[..], b"OVER 1-100\r\n", b"QUIT\r\n"] {
+            let compressed = client.compress(line).unwrap();
+            // Each message can be decoded without waiting for more data
+            server.feed(&compressed);
+            assert_eq!(inflate_all(&mut server), line);
+        }
+        assert_eq!(client.stats().sent_uncompressed, 24);
+        assert_eq!(
+            client.stats().sent_compressed,
+            server.stats().received_compressed
+        );
+        assert_eq!(server.stats().received_uncompressed, 24);
+    }
+
+    #[test]
+    fn test_large_data_and_ratio() {
+        let mut client = DeflateSession::new(&[]);
+        let mut server = DeflateSession::new(&[]);
+        let data: Vec<u8> = b"1\tSubject\tposter@example.com\t<id@example.com>\r\n"
+            .iter()
+            .copied()
+            .cycle()
+            .take(100_000)
+            .collect();
+
+        let compressed = server.compress(&data).unwrap();
+        assert!(compressed.len() < data.len() / 10);
+
+        // Split delivery, starting with data buffered before activation
+        let mut receiver = DeflateSession::new(&compressed[..10]);
+        receiver.feed(&compressed[10..]);
+        assert_eq!(inflate_all(&mut receiver), data);
+        assert!(receiver.stats().receive_ratio().unwrap() > 10.0);
+
+        assert_eq!(client.stats().receive_ratio(), None);
+        client.feed(&[0xff, 0xff, 0xff]);
+        assert!(client.decompress(&mut [0u8; 16]).is_err());
+    }
+}
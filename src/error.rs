@@ -25,6 +25,14 @@ pub enum Error {
     /// Invalid command or parameters
     InvalidCommand(String),
 
+    /// An article number refers to a group other than the selected one
+    GroupMismatch {
+        /// Group the article number belongs to
+        requested: String,
+        /// Currently selected group, if any
+        selected: Option<String>,
+    },
+
     /// I/O error (when using runtime integrations)
     #[cfg(any(
         feature = "tokio-runtime",
@@ -49,6 +57,20 @@ impl fmt::Display for Error {
             Error::Protocol { code, message } => write!(f, "Protocol error {code}: {message}"),
             Error::Parse(msg) => write!(f, "Parse error: {msg}"),
             Error::InvalidCommand(msg) => write!(f, "Invalid command: {msg}"),
+            Error::GroupMismatch {
+                requested,
+                selected: Some(selected),
+            } => write!(
+                f,
+                "Article number in group {requested} requested while {selected} is selected"
+            ),
+            Error::GroupMismatch {
+                requested,
+                selected: None,
+            } => write!(
+                f,
+                "Article number in group {requested} requested while no group is selected"
+            ),
             #[cfg(any(
                 feature = "tokio-runtime",
                 feature = "async-std-runtime",
@@ -109,6 +131,18 @@ mod tests {
         assert_eq!(format!("{}", err), "Invalid command: bad command");
     }
 
+    #[test]
+    fn test_error_display_group_mismatch() {
+        let err = Error::GroupMismatch {
+            requested: "alt.test".to_string(),
+            selected: Some("misc.test".to_string()),
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Article number in group alt.test requested while misc.test is selected"
+        );
+    }
+
     #[cfg(any(
         feature = "tokio-runtime",
         feature = "async-std-runtime",
@@ -0,0 +1,278 @@
+//! Validated message-ids and newsgroup names.
+//!
+//! [`MessageId`] and [`GroupName`] can only hold values that are valid on
+//! the wire, so commands built from them cannot be malformed or smuggle in
+//! extra arguments.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::{GroupName, MessageId};
+//!
+//! let id: MessageId = "<abc.123@example.com>".parse().unwrap();
+//! assert_eq!(id, "<abc.123@example.com>");
+//! assert!(MessageId::new("<a b@example.com>").is_err());
+//!
+//! let group: GroupName = "comp.lang.rust".parse().unwrap();
+//! assert_eq!(group.components().count(), 3);
+//! assert!(GroupName::new("comp..rust").is_err());
+//! ```
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use crate::wildmat::is_exact;
+use crate::{Error, Result};
+
+/// Longest message-id allowed by RFC 3977 Section 3.6, in octets
+const MAX_MESSAGE_ID_LEN: usize = 250;
+
+/// A message-id, including its angle brackets.
+///
+/// Follows the syntax of RFC 3977 Section 3.6: 3 to 250 printable US-ASCII
+/// octets, starting with `<` and ending with the only `>`. The stricter
+/// `<left@right>` form of RFC 5536 is not required, so that identifiers of
+/// older articles remain usable.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MessageId(String);
+
+impl MessageId {
+    /// Validate a message-id.
+    pub fn new(id: impl Into<String>) -> Result<Self> {
+        let id = id.into();
+        let valid = (3..=MAX_MESSAGE_ID_LEN).contains(&id.len())
+            && id.starts_with('<')
+            && id.ends_with('>')
+            && !id[..id.len() - 1].contains('>')
+            && id.bytes().all(|b| b.is_ascii_graphic());
+        if !valid {
+            return Err(Error::Parse(format!("Invalid message-id: {id:?}")));
+        }
+        Ok(MessageId(id))
+    }
+
+    /// Get the message-id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert into the underlying string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+/// A newsgroup name.
+///
+/// Follows the syntax of RFC 3977 Section 4.1 with the structure of RFC
+/// 5536 Section 3.1.4: non-empty components separated by single periods,
+/// made of printable characters other than the wildmat specials
+/// `!*,?[\]`. UTF-8 names are allowed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupName(String);
+
+impl GroupName {
+    /// Validate a newsgroup name.
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        let valid = name
+            .split('.')
+            .all(|component| !component.is_empty() && component.chars().all(is_exact));
+        if !valid {
+            return Err(Error::Parse(format!("Invalid newsgroup name: {name:?}")));
+        }
+        Ok(GroupName(name))
+    }
+
+    /// Get the name as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert into the underlying string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Iterate over the period-separated components of the name.
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.0.split('.')
+    }
+}
+
+/// Implement the string conversions shared by both types
+macro_rules! string_newtype {
+    ($name:ident) => {
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                $name::new(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(s: String) -> Result<Self> {
+                $name::new(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(s: &str) -> Result<Self> {
+                $name::new(s)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    };
+}
+
+string_newtype!(MessageId);
+string_newtype!(GroupName);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_message_id_syntax() {
+        for valid in [
+            "<a@b>",
+            "<abc.123$x@news.example.com>",
+            "<[local]@[127.0.0.1]>",
+            "<no-at-sign>",
+            "<<@>",
+        ] {
+            assert!(MessageId::new(valid).is_ok(), "{valid} should be accepted");
+        }
+
+        let too_long = format!("<{}@example.com>", "x".repeat(237));
+        assert_eq!(too_long.len(), 251);
+        for invalid in [
+            "",
+            "<>",
+            "a@b",
+            "<a@b",
+            "a@b>",
+            "<a b@example.com>",
+            "<a>b@example.com>",
+            "<a@b>\r\nQUIT",
+            "<\tab@example.com>",
+            "<tést@example.com>",
+            &too_long,
+        ] {
+            assert!(
+                matches!(MessageId::new(invalid), Err(Error::Parse(_))),
+                "{invalid:?} should be rejected"
+            );
+        }
+        assert!(MessageId::new(&too_long[1..]).is_err());
+        assert!(MessageId::new(format!("<{}>", &too_long[2..too_long.len() - 1])).is_ok());
+    }
+
+    #[test]
+    fn test_group_name_syntax() {
+        for valid in [
+            "misc.test",
+            "alt.binaries.a+b_c-d",
+            "de.comp.übersicht",
+            "local",
+            "x.123",
+        ] {
+            assert!(GroupName::new(valid).is_ok(), "{valid} should be accepted");
+        }
+        for invalid in [
+            "",
+            ".misc",
+            "misc.",
+            "misc..test",
+            "misc test",
+            "misc.*",
+            "comp.lang.c,alt.test",
+            "alt.!x",
+            "misc.test\r\nQUIT",
+            "a[b]",
+            "a\\b",
+        ] {
+            assert!(
+                matches!(GroupName::new(invalid), Err(Error::Parse(_))),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_string_conversions() {
+        let id = MessageId::new("<a@b>").unwrap();
+        assert_eq!(id, "<a@b>");
+        assert_eq!(id.len(), 5);
+        assert_eq!(id.to_string(), "<a@b>");
+        assert_eq!(String::from(id.clone()), "<a@b>");
+
+        // Sets of identifiers can be queried with plain strings
+        let ids: HashSet<MessageId> = [id].into_iter().collect();
+        assert!(ids.contains("<a@b>"));
+
+        let group: GroupName = "comp.lang.rust".try_into().unwrap();
+        assert_eq!(
+            group.components().collect::<Vec<_>>(),
+            ["comp", "lang", "rust"]
+        );
+        assert_eq!(group.as_str(), "comp.lang.rust");
+    }
+}
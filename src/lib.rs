@@ -46,8 +46,22 @@
 #![warn(rust_2018_idioms)]
 
 pub mod client;
+pub mod codec;
 pub mod command;
+#[cfg(feature = "compression")]
+mod compressed;
+#[cfg(all(
+    feature = "compression",
+    any(
+        feature = "tokio-runtime",
+        feature = "async-std-runtime",
+        feature = "smol-runtime"
+    )
+))]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub mod compression;
 pub mod error;
+pub mod ident;
 #[cfg(any(
     feature = "tokio-runtime",
     feature = "async-std-runtime",
@@ -62,8 +76,44 @@ pub mod error;
     )))
 )]
 pub mod net_client;
+pub mod newsrc;
+pub mod range;
 pub mod response;
+pub mod sasl;
+#[cfg(any(
+    feature = "tokio-runtime",
+    feature = "async-std-runtime",
+    feature = "smol-runtime"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "tokio-runtime",
+        feature = "async-std-runtime",
+        feature = "smol-runtime"
+    )))
+)]
+pub mod sync;
+pub mod threading;
+pub mod timestamp;
+#[cfg(all(
+    feature = "rustls",
+    any(
+        feature = "tokio-runtime",
+        feature = "async-std-runtime",
+        feature = "smol-runtime"
+    )
+))]
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+pub mod tls;
+#[cfg(any(
+    feature = "tokio-runtime",
+    feature = "async-std-runtime",
+    feature = "smol-runtime"
+))]
+mod transport;
 pub mod utils;
+pub mod wildmat;
 
 // Async runtime integrations - access via runtime::tokio, runtime::async_std, runtime::smol
 #[cfg(any(
@@ -88,10 +138,19 @@ pub mod mock;
 
 // === Core Types (always available) ===
 
-pub use client::Client;
-pub use command::{ArticleSpec, Command, ListVariant};
+pub use client::{Client, ResponseEvent};
+pub use command::{ArticleRange, ArticleSpec, Command, ListVariant};
 pub use error::{Error, Result};
-pub use response::{Article, Attachment, HeaderEntry, NewsGroup, OverviewEntry, Response};
+pub use ident::{GroupName, MessageId};
+pub use newsrc::Newsrc;
+pub use range::RangeSet;
+pub use response::{
+    Article, Attachment, DecodedOverview, HeaderEntry, NewsGroup, OverviewEntry, OverviewSchema,
+    Response,
+};
+pub use threading::{Thread, Threadable, Threader};
+pub use timestamp::Timestamp;
+pub use wildmat::Wildmat;
 
 // Deprecated alias for backwards compatibility
 #[allow(deprecated)]
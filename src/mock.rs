@@ -3,7 +3,10 @@
 //! This module provides a mock server implementation that can simulate
 //! NNTP server responses for testing client functionality against the spec.
 
-use crate::{Client, Command, Error, Response, Result};
+use crate::codec;
+use crate::response::HeaderEntry;
+use crate::sasl;
+use crate::{Client, Command, Error, NewsGroup, Response, Result};
 use std::collections::VecDeque;
 
 /// A mock NNTP server that simulates server responses for testing.
@@ -125,7 +128,7 @@ impl ClientMockTest {
         let response = self.mock_server.handle_command(&command)?;
 
         // Encode response and feed back to client
-        let response_bytes = encode_response(&response)?;
+        let response_bytes = encode_reply(&command, &response)?;
         self.client.feed_bytes(&response_bytes);
 
         // Decode the response from client buffer
@@ -157,159 +160,172 @@ impl ClientMockTest {
     }
 }
 
+/// Encode the reply to a command, with the status code that command answers with.
+fn encode_reply(command: &Command, response: &Response) -> Result<Vec<u8>> {
+    match (command, response) {
+        // XHDR and XPAT answer with 221 rather than the 225 of HDR (RFC 2980)
+        (Command::Xhdr { .. } | Command::Xpat { .. }, Response::HeaderData(headers)) => {
+            Ok(header_data_reply("221 Header follows", headers))
+        }
+        _ => encode_response(response),
+    }
+}
+
 /// Encode a response as it would come from a real NNTP server.
 fn encode_response(response: &Response) -> Result<Vec<u8>> {
-    let response_str = match response {
-        Response::Capabilities(caps) => {
-            let mut result = "101 Capability list:\r\n".to_string();
-            for cap in caps {
-                result.push_str(cap);
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
-        }
+    let encoded = match response {
+        Response::Capabilities(caps) => multiline_reply("101 Capability list:", caps),
         Response::ModeReader { posting_allowed } => {
             if *posting_allowed {
-                "200 Reader mode, posting allowed\r\n".to_string()
+                single_line_reply("200 Reader mode, posting allowed")
             } else {
-                "201 Reader mode, posting prohibited\r\n".to_string()
+                single_line_reply("201 Reader mode, posting prohibited")
             }
         }
-        Response::AuthSuccess => "281 Authentication accepted\r\n".to_string(),
-        Response::AuthRequired => "381 More authentication information required\r\n".to_string(),
+        Response::AuthSuccess => single_line_reply("281 Authentication accepted"),
+        Response::AuthRequired => single_line_reply("381 More authentication information required"),
+        Response::SaslSuccess(data) => {
+            single_line_reply(&format!("283 {}", sasl::encode_data(data)))
+        }
+        Response::SaslChallenge(data) => {
+            single_line_reply(&format!("383 {}", sasl::encode_data(data)))
+        }
         Response::GroupSelected {
             count,
             first,
             last,
             name,
-        } => {
-            format!("211 {count} {first} {last} {name}\r\n")
-        }
-        Response::ArticleListing(articles) => {
-            let mut result = "211 Article list follows\r\n".to_string();
-            for article in articles {
-                result.push_str(&article.to_string());
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
-        }
+        } => single_line_reply(&format!("211 {count} {first} {last} {name}")),
+        Response::ArticleListing {
+            count,
+            first,
+            last,
+            name,
+            articles,
+        } => multiline_reply(
+            &format!("211 {count} {first} {last} {name} list follows"),
+            articles.iter().map(|article| article.to_string()),
+        ),
         Response::Article {
             number,
             message_id,
             content,
         } => {
             let num_str = number.map_or("0".to_string(), |n| n.to_string());
-            let mut result = format!("220 {num_str} {message_id} Article follows\r\n");
-            result.push_str(&String::from_utf8_lossy(content));
-            if !content.ends_with(b"\r\n") {
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
+            let mut result =
+                single_line_reply(&format!("220 {num_str} {message_id} Article follows"));
+            result.extend_from_slice(&codec::dot_stuff(content));
             result
         }
         Response::ArticleStatus { number, message_id } => {
-            format!("223 {number} {message_id}\r\n")
-        }
-        Response::NewsgroupList(groups) => {
-            let mut result = "215 Newsgroups follow:\r\n".to_string();
-            for group in groups {
-                result.push_str(&format!(
-                    "{} {} {} {}\r\n",
-                    group.name, group.last, group.first, group.posting_status
-                ));
-            }
-            result.push_str(".\r\n");
-            result
+            single_line_reply(&format!("223 {number} {message_id}"))
         }
-        Response::NewNewsgroups(groups) => {
-            let mut result = "231 New newsgroups follow:\r\n".to_string();
-            for group in groups {
-                result.push_str(&format!(
-                    "{} {} {} {}\r\n",
-                    group.name, group.last, group.first, group.posting_status
-                ));
-            }
-            result.push_str(".\r\n");
-            result
-        }
-        Response::NewArticles(articles) => {
-            let mut result = "230 New articles follow:\r\n".to_string();
-            for article in articles {
-                result.push_str(article);
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
-        }
-        Response::PostAccepted => "340 Send article to be posted\r\n".to_string(),
-        Response::PostSuccess => "240 Article posted successfully\r\n".to_string(),
-        Response::ArticleWanted => "335 Send article to be transferred\r\n".to_string(),
-        Response::ArticleNotWanted => "435 Article not wanted\r\n".to_string(),
-        Response::ArticleTransferred => "235 Article transferred successfully\r\n".to_string(),
-        Response::Quit => "205 Goodbye\r\n".to_string(),
-        Response::Help(help_lines) => {
-            let mut result = "100 Help text follows\r\n".to_string();
-            for line in help_lines {
-                result.push_str(line);
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
-        }
-        Response::Date(date) => {
-            format!("111 {date}\r\n")
-        }
-        Response::HeaderData(headers) => {
-            let mut result = "225 Header follows\r\n".to_string();
-            for header in headers {
-                result.push_str(&header.article);
-                result.push(' ');
-                result.push_str(&header.value);
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
+        Response::NewsgroupList(groups) => multiline_reply(
+            "215 Newsgroups follow:",
+            groups.iter().map(format_newsgroup),
+        ),
+        Response::NewNewsgroups(groups) => multiline_reply(
+            "231 New newsgroups follow:",
+            groups.iter().map(format_newsgroup),
+        ),
+        Response::NewArticles(articles) => multiline_reply("230 New articles follow:", articles),
+        Response::PostAccepted => single_line_reply("340 Send article to be posted"),
+        Response::PostSuccess => single_line_reply("240 Article posted successfully"),
+        Response::ArticleWanted => single_line_reply("335 Send article to be transferred"),
+        Response::ArticleNotWanted => single_line_reply("435 Article not wanted"),
+        Response::ArticleTransferred => single_line_reply("235 Article transferred successfully"),
+        Response::StreamingPermitted => single_line_reply("203 Streaming permitted"),
+        Response::CheckWanted { message_id } => single_line_reply(&format!("238 {message_id}")),
+        Response::CheckDeferred { message_id } => single_line_reply(&format!("431 {message_id}")),
+        Response::CheckNotWanted { message_id } => single_line_reply(&format!("438 {message_id}")),
+        Response::TakethisAccepted { message_id } => {
+            single_line_reply(&format!("239 {message_id}"))
         }
-        Response::OverviewData(overview) => {
-            let mut result = "224 Overview information follows\r\n".to_string();
-            for entry in overview {
-                // Format as tab-separated fields
-                result.push_str(&entry.fields.join("\t"));
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
+        Response::TakethisRejected { message_id } => {
+            single_line_reply(&format!("439 {message_id}"))
         }
+        Response::Quit => single_line_reply("205 Goodbye"),
+        Response::Help(help_lines) => multiline_reply("100 Help text follows", help_lines),
+        Response::Date(date) => single_line_reply(&format!("111 {date}")),
+        Response::HeaderData(headers) => header_data_reply("225 Header follows", headers),
+        Response::OverviewData(overview) => multiline_reply(
+            "224 Overview information follows",
+            // Format as tab-separated fields
+            overview.iter().map(|entry| entry.fields.join("\t")),
+        ),
         Response::OverviewFormat(format_fields) => {
-            let mut result = "215 Order of fields in overview database\r\n".to_string();
-            for field in format_fields {
-                result.push_str(field);
-                result.push_str("\r\n");
-            }
-            result.push_str(".\r\n");
-            result
-        }
-        Response::Success { code, message } => {
-            format!("{code} {message}\r\n")
-        }
-        Response::Error { code, message } => {
-            format!("{code} {message}\r\n")
-        }
-        Response::TlsReady => "382 Continue with TLS negotiation\r\n".to_string(),
-        Response::TlsNotAvailable { message } => {
-            format!("483 {message}\r\n")
+            multiline_reply("215 Order of fields in overview database", format_fields)
         }
+        Response::NewsgroupDescriptions(descriptions) => multiline_reply(
+            "282 List of groups and descriptions follows",
+            descriptions
+                .iter()
+                .map(|desc| format!("{}\t{}", desc.name, desc.description)),
+        ),
+        Response::ActiveTimes(times) => multiline_reply(
+            "215 Information follows",
+            times.iter().map(|entry| {
+                format!(
+                    "{} {} {}",
+                    entry.name,
+                    entry.timestamp.unix(),
+                    entry.creator
+                )
+            }),
+        ),
+        Response::Success { code, message } => single_line_reply(&format!("{code} {message}")),
+        Response::Error { code, message } => single_line_reply(&format!("{code} {message}")),
+        Response::TlsReady => single_line_reply("382 Continue with TLS negotiation"),
+        Response::CompressionActive => single_line_reply("206 Compression active"),
+        Response::TlsNotAvailable { message } => single_line_reply(&format!("483 {message}")),
     };
 
-    Ok(response_str.into_bytes())
+    Ok(encoded)
+}
+
+/// Encode a single-line reply.
+fn single_line_reply(status: &str) -> Vec<u8> {
+    format!("{status}\r\n").into_bytes()
+}
+
+/// Encode a status line followed by a dot-stuffed multi-line data block.
+fn multiline_reply<I>(status: &str, lines: I) -> Vec<u8>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut data = String::new();
+    for line in lines {
+        data.push_str(line.as_ref());
+        data.push_str("\r\n");
+    }
+    let mut result = single_line_reply(status);
+    result.extend_from_slice(&codec::dot_stuff(data.as_bytes()));
+    result
+}
+
+/// Encode header data as `article value` lines.
+fn header_data_reply(status: &str, headers: &[HeaderEntry]) -> Vec<u8> {
+    multiline_reply(
+        status,
+        headers
+            .iter()
+            .map(|header| format!("{} {}", header.article, header.value)),
+    )
+}
+
+/// Format a newsgroup as a LIST ACTIVE line.
+fn format_newsgroup(group: &NewsGroup) -> String {
+    format!(
+        "{} {} {} {}",
+        group.name, group.last, group.first, group.posting_status
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Command, Response};
+    use crate::{ArticleRange, Command, RangeSet, Response, Timestamp};
 
     #[test]
     fn test_mock_server_basic() {
@@ -395,7 +411,7 @@ mod tests {
                 },
             ),
             (
-                Command::Group("comp.lang.rust".to_string()),
+                Command::Group("comp.lang.rust".parse().unwrap()),
                 Response::GroupSelected {
                     count: 1234,
                     first: 3000,
@@ -429,7 +445,7 @@ mod tests {
 
         // Test group selection
         let response = test
-            .send_command(Command::Group("comp.lang.rust".to_string()))
+            .send_command(Command::Group("comp.lang.rust".parse().unwrap()))
             .unwrap();
         if let Response::GroupSelected { name, count, .. } = response {
             assert_eq!(name, "comp.lang.rust");
@@ -497,6 +513,14 @@ mod tests {
         let auth_required = Response::AuthRequired;
         let encoded = encode_response(&auth_required).unwrap();
         assert_eq!(encoded, b"381 More authentication information required\r\n");
+
+        let challenge = Response::SaslChallenge(b"abc".to_vec());
+        let encoded = encode_response(&challenge).unwrap();
+        assert_eq!(encoded, b"383 YWJj\r\n");
+
+        let sasl_success = Response::SaslSuccess(b"abc".to_vec());
+        let encoded = encode_response(&sasl_success).unwrap();
+        assert_eq!(encoded, b"283 YWJj\r\n");
     }
 
     #[test]
@@ -570,9 +594,16 @@ mod tests {
 
     #[test]
     fn test_response_encoding_article_listing() {
-        let listing = Response::ArticleListing(vec![100, 101, 102]);
+        let listing = Response::ArticleListing {
+            count: 3,
+            first: 100,
+            last: 102,
+            name: "misc.test".to_string(),
+            articles: RangeSet::from(100..=102),
+        };
         let encoded = encode_response(&listing).unwrap();
-        assert!(encoded.starts_with(b"211 Article list follows"));
+        assert!(encoded.starts_with(b"211 3 100 102 misc.test list follows\r\n100\r\n"));
+        assert_eq!(Response::parse(&encoded).unwrap(), listing);
     }
 
     #[test]
@@ -603,6 +634,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_response_encoding_streaming_responses() {
+        assert_eq!(
+            encode_response(&Response::StreamingPermitted).unwrap(),
+            b"203 Streaming permitted\r\n"
+        );
+        let responses = [
+            Response::CheckWanted {
+                message_id: "<a@b>".to_string(),
+            },
+            Response::CheckDeferred {
+                message_id: "<a@b>".to_string(),
+            },
+            Response::TakethisRejected {
+                message_id: "<a@b>".to_string(),
+            },
+        ];
+        for response in responses {
+            let encoded = encode_response(&response).unwrap();
+            assert_eq!(Response::parse(&encoded).unwrap(), response);
+        }
+    }
+
     #[test]
     fn test_response_encoding_quit() {
         assert_eq!(
@@ -621,7 +675,7 @@ mod tests {
 
     #[test]
     fn test_response_encoding_date() {
-        let date = Response::Date("20240101120000".to_string());
+        let date = Response::Date(Timestamp::from_utc(2024, 1, 1, 12, 0, 0).unwrap());
         let encoded = encode_response(&date).unwrap();
         assert_eq!(encoded, b"111 20240101120000\r\n");
     }
@@ -638,6 +692,45 @@ mod tests {
         assert!(String::from_utf8_lossy(&encoded).contains("100 Test Subject"));
     }
 
+    #[test]
+    fn test_xpat_and_xgtitle_round_trip() {
+        use crate::response::{HeaderEntry, NewsgroupDesc};
+        let xpat = Command::Xpat {
+            field: "Subject".to_string(),
+            range: ArticleRange::Between(1, 10),
+            patterns: vec!["*rust*".to_string()],
+        };
+        let matches = Response::HeaderData(vec![HeaderEntry {
+            article: "7".to_string(),
+            value: "Learning rust".to_string(),
+        }]);
+        let descriptions = Response::NewsgroupDescriptions(vec![NewsgroupDesc {
+            name: "comp.lang.rust".to_string(),
+            description: "The Rust programming language".to_string(),
+        }]);
+        let mut test = ClientMockTest::new(vec![
+            (xpat.clone(), matches.clone()),
+            (Command::Xgtitle(None), descriptions.clone()),
+        ]);
+
+        let encoded = encode_reply(&xpat, &matches).unwrap();
+        assert_eq!(encoded, b"221 Header follows\r\n7 Learning rust\r\n.\r\n");
+        let hdr = Command::Hdr {
+            field: "Subject".to_string(),
+            range: None,
+        };
+        assert!(encode_reply(&hdr, &matches)
+            .unwrap()
+            .starts_with(b"225 Header follows"));
+
+        assert_eq!(test.send_command(xpat).unwrap(), matches);
+        assert_eq!(
+            test.send_command(Command::Xgtitle(None)).unwrap(),
+            descriptions
+        );
+        assert!(test.is_complete());
+    }
+
     #[test]
     fn test_response_encoding_overview_data() {
         use crate::response::OverviewEntry;
@@ -647,6 +740,7 @@ mod tests {
                 "Subject".to_string(),
                 "from@x.com".to_string(),
             ],
+            ..Default::default()
         }]);
         let encoded = encode_response(&overview).unwrap();
         assert!(encoded.starts_with(b"224 Overview information follows"));
@@ -669,6 +763,14 @@ mod tests {
         assert_eq!(encoded, b"200 OK\r\n");
     }
 
+    #[test]
+    fn test_response_encoding_compression() {
+        assert_eq!(
+            encode_response(&Response::CompressionActive).unwrap(),
+            b"206 Compression active\r\n"
+        );
+    }
+
     #[test]
     fn test_response_encoding_tls() {
         assert_eq!(
@@ -775,7 +877,7 @@ mod tests {
                 },
             ),
             (
-                Command::Group("test.group".to_string()),
+                Command::Group("test.group".parse().unwrap()),
                 Response::GroupSelected {
                     count: 100,
                     first: 1,
@@ -798,7 +900,7 @@ mod tests {
 
         // Send second command
         let response = test
-            .send_command(Command::Group("test.group".to_string()))
+            .send_command(Command::Group("test.group".parse().unwrap()))
             .unwrap();
         if let Response::GroupSelected {
             name,
@@ -817,4 +919,116 @@ mod tests {
 
         assert!(test.is_complete());
     }
+
+    #[test]
+    fn test_dot_stuffed_article_round_trip() {
+        let content = b"Subject: dots\r\n\r\n.\r\n..double\r\n.leading\r\nplain\r\n".to_vec();
+        let interactions = vec![(
+            Command::Article(crate::ArticleSpec::MessageId(
+                "<dots@example>".parse().unwrap(),
+            )),
+            Response::Article {
+                number: None,
+                message_id: "<dots@example>".to_string(),
+                content: content.clone(),
+            },
+        )];
+
+        let mut test = ClientMockTest::new(interactions);
+        let response = test
+            .send_command(Command::Article(crate::ArticleSpec::MessageId(
+                "<dots@example>".parse().unwrap(),
+            )))
+            .unwrap();
+
+        if let Response::Article {
+            content: decoded, ..
+        } = response
+        {
+            assert_eq!(decoded, content);
+        } else {
+            panic!("Expected Article response");
+        }
+    }
+
+    #[test]
+    fn test_bare_lf_article_round_trip() {
+        let interactions = vec![(
+            Command::Body(crate::ArticleSpec::Current),
+            Response::Article {
+                number: Some(1),
+                message_id: "<lf@example>".to_string(),
+                content: b"first\n.second\n".to_vec(),
+            },
+        )];
+
+        let mut test = ClientMockTest::new(interactions);
+        let response = test
+            .send_command(Command::Body(crate::ArticleSpec::Current))
+            .unwrap();
+
+        if let Response::Article { content, .. } = response {
+            // Line endings are normalized to CRLF on the wire
+            assert_eq!(content, b"first\r\n.second\r\n");
+        } else {
+            panic!("Expected Article response");
+        }
+    }
+
+    #[test]
+    fn test_dot_stuffed_text_list_round_trip() {
+        let help = vec![
+            ".hidden topic".to_string(),
+            "..".to_string(),
+            "plain".to_string(),
+        ];
+        let interactions = vec![(Command::Help, Response::Help(help.clone()))];
+
+        let mut test = ClientMockTest::new(interactions);
+        let response = test.send_command(Command::Help).unwrap();
+        assert_eq!(response, Response::Help(help));
+    }
+
+    #[test]
+    fn test_encode_response_dot_stuffs_article() {
+        let encoded = encode_response(&Response::Article {
+            number: Some(1),
+            message_id: "<a@b>".to_string(),
+            content: b".line\r\n".to_vec(),
+        })
+        .unwrap();
+        assert_eq!(
+            encoded,
+            b"220 1 <a@b> Article follows\r\n..line\r\n.\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_binary_article_round_trip() {
+        let content: Vec<u8> = (0u8..=255).filter(|&b| b != b'\n').collect();
+        let mut content = content;
+        content.extend_from_slice(b"\r\n");
+        let interactions = vec![(
+            Command::Body(crate::ArticleSpec::Current),
+            Response::Article {
+                number: Some(7),
+                message_id: "<bin@example>".to_string(),
+                content: content.clone(),
+            },
+        )];
+
+        let mut test = ClientMockTest::new(interactions);
+        let response = test
+            .send_command(Command::Body(crate::ArticleSpec::Current))
+            .unwrap();
+
+        if let Response::Article {
+            content: decoded, ..
+        } = response
+        {
+            assert_eq!(decoded, content);
+        } else {
+            panic!("Expected Article response");
+        }
+    }
 }
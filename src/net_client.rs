@@ -74,15 +74,35 @@
 //! # }
 //! ```
 
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+
+use crate::codec;
+#[cfg(feature = "compression")]
+use crate::compression::CompressionStats;
 use crate::response::{
-    ActiveTimeEntry, ActiveTimesList, ArticleNumbers, ArticlePointer, Capabilities, CountsEntry,
-    CountsList, DistribPat, DistribPatsList, DistributionEntry, DistributionsList, GroupStats,
+    ActiveTimesList, ArticleNumbers, ArticlePointer, Capabilities, CountsEntry, CountsList,
+    DistribPat, DistribPatsList, DistributionEntry, DistributionsList, FeedOutcome, GroupStats,
     HeaderData, HeadersList, HelpText, MessageIdList, ModeratorEntry, ModeratorsList,
-    NewsgroupDesc, NewsgroupDescList, NewsgroupList, OverviewData, OverviewFormat, PostingStatus,
-    ServerDate,
+    NewsgroupDesc, NewsgroupDescList, NewsgroupList, OverviewData, OverviewFormat, OverviewSchema,
+    PostingStatus, ServerDate,
 };
 use crate::runtime::AsyncStream;
-use crate::{Client, Command, Error, Response, Result};
+use crate::sasl::{self, SaslMechanism};
+#[cfg(feature = "rustls")]
+use crate::tls::TlsConfig;
+use crate::transport::Transport;
+use crate::{
+    ArticleRange, Client, Command, Error, GroupName, MessageId, Response, Result, Timestamp,
+    Wildmat,
+};
+
+/// Default number of commands kept in flight by the batch methods of
+/// [`NntpClient`].
+pub const DEFAULT_PIPELINE_DEPTH: usize = 16;
 
 /// Generic NNTP client that works with any async stream implementation.
 ///
@@ -131,10 +151,40 @@ use crate::{Client, Command, Error, Response, Result};
 pub struct NntpClient<S: AsyncStream> {
     /// The sans-io client handling protocol logic.
     client: Client,
-    /// The async stream for network I/O.
-    stream: S,
+    /// The async stream for network I/O, with any negotiated layers.
+    stream: Transport<S>,
     /// Whether posting is allowed on this connection.
     posting_allowed: bool,
+    /// Maximum number of outstanding commands when pipelining.
+    pipeline_depth: usize,
+    /// Capability list cached until the server may change it.
+    capabilities: Option<Capabilities>,
+    /// Whether the server accepts OVER or only XOVER.
+    over_form: CommandForm,
+    /// Whether the server accepts HDR or only XHDR.
+    hdr_form: CommandForm,
+    /// Overview format cached until the server may change it.
+    overview_format: Option<OverviewFormat>,
+    /// Schema built from the cached overview format.
+    overview_schema: Option<Arc<OverviewSchema>>,
+    /// Whether XFEATURE COMPRESS GZIP has been sent.
+    #[cfg(feature = "compression")]
+    xfeature_gzip_sent: bool,
+    /// Host the client connected to, the default TLS server name.
+    #[cfg(feature = "rustls")]
+    host: String,
+}
+
+/// Which form of a command standardized by RFC 3977 the server accepts, for
+/// commands that have an RFC 2980 predecessor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CommandForm {
+    /// Not known yet
+    Unknown,
+    /// The RFC 3977 form, e.g. OVER
+    Standard,
+    /// The RFC 2980 form, e.g. XOVER
+    Legacy,
 }
 
 impl<S: AsyncStream> NntpClient<S> {
@@ -168,23 +218,205 @@ impl<S: AsyncStream> NntpClient<S> {
     /// # }
     /// ```
     pub async fn connect(addr: &str) -> Result<Self> {
+        let mut client = Self::open(addr).await?;
+        client.read_greeting().await?;
+        Ok(client)
+    }
+
+    /// Connect to an NNTP server using implicit TLS (NNTPS).
+    ///
+    /// Establishes a TCP connection, performs the TLS handshake and reads
+    /// the server greeting over the encrypted connection. NNTPS servers
+    /// usually listen on port 563.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Server address in format "host:port"
+    /// * `config` - TLS settings; the server name defaults to the host of `addr`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection or the TLS handshake fails, e.g.
+    /// because the server certificate is not trusted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "tokio-runtime")]
+    /// # {
+    /// use nntp_rs::net_client::NntpClient;
+    /// use nntp_rs::runtime::TokioStream;
+    /// use nntp_rs::tls::TlsConfig;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = TlsConfig::new();
+    /// let client = NntpClient::<TokioStream>::connect_tls("news.example.com:563", &config).await?;
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    #[cfg(feature = "rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+    pub async fn connect_tls(addr: &str, config: &TlsConfig) -> Result<Self> {
+        let mut client = Self::open(addr).await?;
+        let session = config.session(&client.host)?;
+        client
+            .stream
+            .start_tls(session)
+            .await
+            .map_err(|e| Error::Connection(format!("TLS handshake failed: {e}")))?;
+        client.read_greeting().await?;
+        Ok(client)
+    }
+
+    /// Upgrade the connection to TLS with STARTTLS (RFC 4642).
+    ///
+    /// Sends STARTTLS and performs the TLS handshake after the server's 382
+    /// reply. Any data the server sent after the 382 reply, before the
+    /// handshake, is discarded so that it cannot be injected into the
+    /// protected session. The cached capabilities are discarded as well and
+    /// should be requested again.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - TLS settings; the server name defaults to the host the
+    ///   client connected to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if TLS or compression is already active, the server
+    /// refuses STARTTLS, or the TLS handshake fails.
+    #[cfg(feature = "rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+    pub async fn starttls(&mut self, config: &TlsConfig) -> Result<()> {
+        if self.stream.is_tls() {
+            return Err(Error::InvalidCommand("TLS is already active".to_string()));
+        }
+        // RFC 8054 requires TLS to be negotiated before compression
+        #[cfg(feature = "compression")]
+        if self.is_compressed() {
+            return Err(Error::InvalidCommand(
+                "TLS cannot be started after compression".to_string(),
+            ));
+        }
+        // Check the configuration before the server starts expecting a handshake
+        let session = config.session(&self.host)?;
+
+        let response = self.send_command(Command::StartTls).await?;
+        match response {
+            Response::TlsReady => {}
+            Response::Error { code, message } => return Err(Error::Protocol { code, message }),
+            _ => {
+                return Err(Error::InvalidResponse(
+                    "Expected STARTTLS response".to_string(),
+                ))
+            }
+        }
+
+        self.stream
+            .start_tls(session)
+            .await
+            .map_err(|e| Error::Connection(format!("TLS handshake failed: {e}")))
+    }
+
+    /// Check if the connection is protected by TLS.
+    #[cfg(feature = "rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+    pub fn is_tls(&self) -> bool {
+        self.stream.is_tls()
+    }
+
+    /// Compress the connection with COMPRESS DEFLATE (RFC 8054).
+    ///
+    /// All later commands and replies are compressed, which mostly pays off
+    /// for large OVER, HDR and LIST replies. If TLS is wanted as well, it
+    /// must be started first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidCommand`] if compression is already active,
+    /// and [`Error::Protocol`] if the server refuses, e.g. with 403 if it
+    /// cannot compress or 502 if it does not allow it.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub async fn compress(&mut self) -> Result<()> {
+        if self.stream.compression_stats().is_some() {
+            return Err(Error::InvalidCommand(
+                "Compression is already active".to_string(),
+            ));
+        }
+
+        let response = self.send_command(Command::Compress).await?;
+        match response {
+            Response::CompressionActive => {
+                let buffered = self.client.take_buffered_bytes();
+                self.stream.start_compression(&buffered);
+                Ok(())
+            }
+            Response::Error { code, message } => Err(Error::Protocol { code, message }),
+            _ => Err(Error::InvalidResponse(
+                "Expected COMPRESS response".to_string(),
+            )),
+        }
+    }
+
+    /// Check if the connection is compressed.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn is_compressed(&self) -> bool {
+        self.stream.compression_stats().is_some()
+    }
+
+    /// Get the byte counts of the compressed connection, or `None` if
+    /// compression is not active.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn compression_stats(&self) -> Option<CompressionStats> {
+        self.stream.compression_stats()
+    }
+
+    /// Establish the TCP connection.
+    async fn open(addr: &str) -> Result<Self> {
         let stream = S::connect(addr)
             .await
             .map_err(|e| Error::Connection(format!("Failed to connect: {e}")))?;
 
-        let mut client = Self {
+        let client = Self::with_transport(Transport::new(stream));
+        #[cfg(feature = "rustls")]
+        let client = Self {
+            host: host_from_addr(addr).to_string(),
+            ..client
+        };
+        Ok(client)
+    }
+
+    /// Create a client on an established transport.
+    fn with_transport(stream: Transport<S>) -> Self {
+        Self {
             client: Client::new(),
             stream,
             posting_allowed: false,
-        };
+            pipeline_depth: DEFAULT_PIPELINE_DEPTH,
+            capabilities: None,
+            over_form: CommandForm::Unknown,
+            hdr_form: CommandForm::Unknown,
+            overview_format: None,
+            overview_schema: None,
+            #[cfg(feature = "compression")]
+            xfeature_gzip_sent: false,
+            #[cfg(feature = "rustls")]
+            host: String::new(),
+        }
+    }
 
-        // Read initial server greeting and extract posting permission
-        let greeting = client.read_response().await?;
+    /// Read the initial server greeting and extract posting permission.
+    async fn read_greeting(&mut self) -> Result<()> {
+        let greeting = self.read_response().await?;
         if let Response::ModeReader { posting_allowed } = greeting {
-            client.posting_allowed = posting_allowed;
+            self.posting_allowed = posting_allowed;
         }
-
-        Ok(client)
+        Ok(())
     }
 
     /// Request server capabilities.
@@ -192,6 +424,10 @@ impl<S: AsyncStream> NntpClient<S> {
     /// Sends a CAPABILITIES command and returns the list of capabilities
     /// supported by the server.
     ///
+    /// The list is cached and returned without contacting the server until
+    /// MODE READER, STARTTLS or authentication may have changed it
+    /// (RFC 3977 Section 5.2).
+    ///
     /// # Returns
     ///
     /// A [`Capabilities`] wrapper containing capability strings as reported by the server.
@@ -200,6 +436,10 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// Returns an error if the command fails or the response is invalid.
     pub async fn capabilities(&mut self) -> Result<Capabilities> {
+        if let Some(capabilities) = &self.capabilities {
+            return Ok(capabilities.clone());
+        }
+
         let response = self.send_command(Command::Capabilities).await?;
         if let Response::Error { code, message } = &response {
             return Err(Error::Protocol {
@@ -207,7 +447,14 @@ impl<S: AsyncStream> NntpClient<S> {
                 message: message.clone(),
             });
         }
-        response.try_into()
+        let capabilities: Capabilities = response.try_into()?;
+        self.capabilities = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Get the cached capability list, if it is still valid.
+    pub fn cached_capabilities(&self) -> Option<&Capabilities> {
+        self.capabilities.as_ref()
     }
 
     /// Switch to reader mode.
@@ -279,6 +526,66 @@ impl<S: AsyncStream> NntpClient<S> {
         }
     }
 
+    /// Authenticate with SASL (AUTHINFO SASL, RFC 4643).
+    ///
+    /// Uses the first of `mechanisms` that the server lists in the SASL
+    /// capability, fetching the capabilities if they are not cached. For
+    /// password authentication, pass [`sasl::password_mechanisms`], which
+    /// prefers SCRAM over PLAIN.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidCommand`] if the server offers none of the
+    /// mechanisms, and an error if authentication fails or the server
+    /// could not be verified by the mechanism.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "tokio-runtime")]
+    /// # {
+    /// use nntp_rs::net_client::NntpClient;
+    /// use nntp_rs::runtime::TokioStream;
+    /// use nntp_rs::sasl;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut client = NntpClient::<TokioStream>::connect("news.example.com:119").await?;
+    /// client
+    ///     .authenticate_sasl(sasl::password_mechanisms("user", "secret"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    pub async fn authenticate_sasl(
+        &mut self,
+        mechanisms: Vec<Box<dyn SaslMechanism>>,
+    ) -> Result<()> {
+        let capabilities = self.capabilities().await?;
+        let offered = capabilities.sasl_mechanisms();
+        let mechanism = sasl::select_mechanism(mechanisms, offered).ok_or_else(|| {
+            Error::InvalidCommand(format!(
+                "None of the SASL mechanisms is offered by the server (offered: {})",
+                offered.join(" ")
+            ))
+        })?;
+
+        self.discard_cached_state();
+        let mut request = self.client.start_sasl(mechanism)?;
+        loop {
+            self.stream
+                .write_all(&request)
+                .await
+                .map_err(|e| Error::Io(format!("Failed to send command: {e}")))?;
+            let response = self.read_response().await?;
+            match self.client.continue_sasl(&response)? {
+                Some(next) => request = next,
+                None => return Ok(()),
+            }
+        }
+    }
+
     /// Select a newsgroup.
     ///
     /// Sends a GROUP command to select the specified newsgroup.
@@ -293,9 +600,10 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the group doesn't exist or the command fails.
+    /// Returns an error if the name is not a valid newsgroup name, if the
+    /// group doesn't exist or if the command fails.
     pub async fn group(&mut self, name: &str) -> Result<GroupStats> {
-        let response = self.send_command(Command::Group(name.to_string())).await?;
+        let response = self.send_command(Command::Group(name.parse()?)).await?;
         if let Response::Error { code, message } = &response {
             return Err(Error::Protocol {
                 code: *code,
@@ -305,19 +613,34 @@ impl<S: AsyncStream> NntpClient<S> {
         response.try_into()
     }
 
-    /// List articles in current group with optional range.
+    /// List the article numbers of a group with optional range.
     ///
-    /// Sends a LISTGROUP command to get article numbers in the current group.
+    /// Sends a LISTGROUP command. A given group is selected as by
+    /// [`group`](Self::group), so no separate round trip is needed.
     ///
     /// # Arguments
     ///
-    /// * `range` - Optional range specification (e.g., "1-100" or "500-")
+    /// * `group` - Group to select and list, or `None` for the current group
+    /// * `range` - Optional range of article numbers to list
     ///
     /// # Returns
     ///
-    /// An [`ArticleNumbers`] containing article numbers.
-    pub async fn listgroup(&mut self, range: Option<String>) -> Result<ArticleNumbers> {
-        let response = self.send_command(Command::ListGroup(range)).await?;
+    /// An [`ArticleNumbers`] containing article numbers and the group's
+    /// statistics.
+    pub async fn listgroup(
+        &mut self,
+        group: Option<String>,
+        range: Option<ArticleRange>,
+    ) -> Result<ArticleNumbers> {
+        // The range can only follow a group name on the command line
+        let group = match (group, &range) {
+            (None, Some(_)) => self.client.current_group().map(str::to_string),
+            (group, _) => group,
+        };
+        let group = group.map(GroupName::new).transpose()?;
+        let response = self
+            .send_command(Command::ListGroup { group, range })
+            .await?;
         if let Response::Error { code, message } = &response {
             return Err(Error::Protocol {
                 code: *code,
@@ -391,6 +714,167 @@ impl<S: AsyncStream> NntpClient<S> {
         }
     }
 
+    /// Retrieve several articles, pipelining the requests.
+    ///
+    /// Sends up to [`pipeline_depth`](Self::pipeline_depth) ARTICLE commands
+    /// before waiting for replies, which avoids a round trip per article.
+    ///
+    /// # Returns
+    ///
+    /// One result per spec, in the order of `specs`. A missing article only
+    /// fails its own entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails.
+    pub async fn article_batch(
+        &mut self,
+        specs: Vec<crate::ArticleSpec>,
+    ) -> Result<Vec<Result<crate::Article>>> {
+        let commands = specs.into_iter().map(Command::Article).collect();
+        let responses = self.pipeline(commands).await?;
+        Ok(responses.into_iter().map(reply_into).collect())
+    }
+
+    /// Retrieve the headers of several articles, pipelining the requests.
+    ///
+    /// See [`article_batch`](Self::article_batch) for how the replies are
+    /// reported.
+    pub async fn head_batch(
+        &mut self,
+        specs: Vec<crate::ArticleSpec>,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        let commands = specs.into_iter().map(Command::Head).collect();
+        let responses = self.pipeline(commands).await?;
+        Ok(responses
+            .into_iter()
+            .map(|response| article_content(response, "Expected headers response"))
+            .collect())
+    }
+
+    /// Retrieve the bodies of several articles, pipelining the requests.
+    ///
+    /// See [`article_batch`](Self::article_batch) for how the replies are
+    /// reported.
+    pub async fn body_batch(
+        &mut self,
+        specs: Vec<crate::ArticleSpec>,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        let commands = specs.into_iter().map(Command::Body).collect();
+        let responses = self.pipeline(commands).await?;
+        Ok(responses
+            .into_iter()
+            .map(|response| article_content(response, "Expected body response"))
+            .collect())
+    }
+
+    /// Check the status of several articles, pipelining the requests.
+    ///
+    /// See [`article_batch`](Self::article_batch) for how the replies are
+    /// reported.
+    pub async fn stat_batch(
+        &mut self,
+        specs: Vec<crate::ArticleSpec>,
+    ) -> Result<Vec<Result<ArticlePointer>>> {
+        let commands = specs.into_iter().map(Command::Stat).collect();
+        let responses = self.pipeline(commands).await?;
+        Ok(responses.into_iter().map(reply_into).collect())
+    }
+
+    /// Send several commands, pipelining them (RFC 3977 Section 3.5).
+    ///
+    /// Up to [`pipeline_depth`](Self::pipeline_depth) commands are sent
+    /// before waiting for replies. Commands that change the connection
+    /// state, such as GROUP or AUTHINFO, are pipeline barriers: their reply
+    /// is awaited before any later command is sent.
+    ///
+    /// # Returns
+    ///
+    /// The replies in the order of `commands`. Error replies are returned
+    /// as [`Response::Error`] rather than failing the whole pipeline.
+    ///
+    /// A command naming an article number in another group than the
+    /// selected one is preceded by a GROUP command. If that fails, its reply
+    /// stands in for the command's reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `commands` contains POST or IHAVE, which need the
+    /// article to be sent in between, or if the connection fails.
+    pub async fn pipeline(&mut self, commands: Vec<Command>) -> Result<Vec<Response>> {
+        if commands
+            .iter()
+            .any(|command| matches!(command, Command::Post | Command::Ihave { .. }))
+        {
+            return Err(Error::InvalidCommand(
+                "POST and IHAVE cannot be pipelined".to_string(),
+            ));
+        }
+
+        // Reject malformed commands before anything is sent, so that no
+        // reply is left unread
+        for command in &commands {
+            command.encode()?;
+        }
+
+        let mut responses = Vec::with_capacity(commands.len());
+        let mut request = Vec::new();
+        for command in commands {
+            // Select the group of an article number once the replies in
+            // flight, which may depend on the current group, are read
+            if let Some(group) = self.client.group_selection_for(&command) {
+                self.flush_request(&mut request).await?;
+                while self.client.pending_commands() > 0 {
+                    responses.push(self.read_response().await?);
+                }
+                let response = self.exchange(group).await?;
+                if response.is_error() {
+                    // The command cannot be sent, it fails like GROUP did
+                    responses.push(response);
+                    continue;
+                }
+            }
+
+            // Wait for replies while the pipeline is full or a barrier is
+            // outstanding
+            while self.client.pending_commands() > 0
+                && (self.client.pending_commands() >= self.pipeline_depth
+                    || !self.client.can_pipeline())
+            {
+                self.flush_request(&mut request).await?;
+                responses.push(self.read_response().await?);
+            }
+            match self.encode(command) {
+                Ok(bytes) => request.extend_from_slice(&bytes),
+                Err(e) => {
+                    // Later replies must be paired with the right commands
+                    self.drain_pending(&mut request).await?;
+                    return Err(e);
+                }
+            }
+        }
+
+        self.flush_request(&mut request).await?;
+        while self.client.pending_commands() > 0 {
+            responses.push(self.read_response().await?);
+        }
+        Ok(responses)
+    }
+
+    /// Get the maximum number of commands kept in flight when pipelining.
+    ///
+    /// Defaults to [`DEFAULT_PIPELINE_DEPTH`].
+    pub fn pipeline_depth(&self) -> usize {
+        self.pipeline_depth
+    }
+
+    /// Set the maximum number of commands kept in flight when pipelining.
+    ///
+    /// A depth of 1 disables pipelining; 0 is treated as 1.
+    pub fn set_pipeline_depth(&mut self, depth: usize) {
+        self.pipeline_depth = depth.max(1);
+    }
+
     /// Get article status by message-id or number.
     ///
     /// Sends a STAT command to check if an article exists without retrieving it.
@@ -534,6 +1018,22 @@ impl<S: AsyncStream> NntpClient<S> {
         }
     }
 
+    /// List newsgroup descriptions on servers predating LIST NEWSGROUPS.
+    ///
+    /// Sends an RFC 2980 XGTITLE command.
+    ///
+    /// # Arguments
+    ///
+    /// * `wildmat` - Optional wildmat pattern to filter newsgroups
+    ///
+    /// # Returns
+    ///
+    /// A [`NewsgroupDescList`] containing newsgroup descriptions.
+    pub async fn xgtitle(&mut self, wildmat: Option<String>) -> Result<NewsgroupDescList> {
+        let response = self.send_command(Command::Xgtitle(wildmat)).await?;
+        reply_into(response)
+    }
+
     /// List overview format specification.
     ///
     /// Sends a LIST OVERVIEW.FMT command to retrieve the order of fields
@@ -564,6 +1064,58 @@ impl<S: AsyncStream> NntpClient<S> {
         response.try_into()
     }
 
+    /// Get the overview format used by the server.
+    ///
+    /// Sends LIST OVERVIEW.FMT the first time it is called. Servers that
+    /// lack the command, according to their capabilities or by rejecting it,
+    /// use the default format of RFC 3977 Section 8.4, which is returned
+    /// instead. The result is cached like the capability list.
+    ///
+    /// # Returns
+    ///
+    /// An [`OverviewFormat`] describing the fields of OVER responses.
+    pub async fn overview_format(&mut self) -> Result<OverviewFormat> {
+        if let Some(format) = &self.overview_format {
+            return Ok(format.clone());
+        }
+
+        let unsupported = self
+            .capabilities
+            .as_ref()
+            .is_some_and(|caps| !caps.supports_list("OVERVIEW.FMT"));
+        let format = if unsupported {
+            OverviewFormat::default()
+        } else {
+            match self.list_overview_fmt().await {
+                Ok(format) => format,
+                Err(Error::Protocol { code, .. }) if code >= 500 => OverviewFormat::default(),
+                Err(e) => return Err(e),
+            }
+        };
+
+        self.overview_format = Some(format.clone());
+        Ok(format)
+    }
+
+    /// Get the schema for reading overview entries by field name.
+    ///
+    /// Built from [`overview_format`](Self::overview_format) the first time
+    /// it is called and cached with it.
+    ///
+    /// # Returns
+    ///
+    /// An [`OverviewSchema`] shared by the entries [`over`](Self::over)
+    /// returns.
+    pub async fn overview_schema(&mut self) -> Result<Arc<OverviewSchema>> {
+        if let Some(schema) = &self.overview_schema {
+            return Ok(schema.clone());
+        }
+
+        let schema = Arc::new(OverviewSchema::from(&self.overview_format().await?));
+        self.overview_schema = Some(schema.clone());
+        Ok(schema)
+    }
+
     /// List available headers for HDR command.
     ///
     /// Sends a LIST HEADERS command to retrieve the list of header/metadata
@@ -625,36 +1177,7 @@ impl<S: AsyncStream> NntpClient<S> {
         let response = self
             .send_command(Command::List(crate::ListVariant::ActiveTimes))
             .await?;
-        if let Response::Error { code, message } = &response {
-            return Err(Error::Protocol {
-                code: *code,
-                message: message.clone(),
-            });
-        }
-        // Parse as ActiveTimesList from the generic newsgroup list response
-        // Format: groupname timestamp creator
-        match response {
-            Response::NewsgroupList(groups) => {
-                // The parser puts data in the NewsGroup fields, but ACTIVE.TIMES
-                // has format: groupname timestamp creator
-                // We need to re-parse or handle this specially
-                // For now, return what we can extract
-                let times: Vec<ActiveTimeEntry> = groups
-                    .into_iter()
-                    .map(|g| ActiveTimeEntry {
-                        name: g.name,
-                        // last field contains timestamp in some parsings
-                        timestamp: g.last,
-                        // first field or posting_status might contain creator info
-                        creator: String::new(),
-                    })
-                    .collect();
-                Ok(ActiveTimesList(times))
-            }
-            _ => Err(Error::InvalidResponse(
-                "Expected active times response".to_string(),
-            )),
-        }
+        reply_into(response)
     }
 
     /// List valid distribution values.
@@ -708,7 +1231,8 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// # Returns
     ///
-    /// A [`DistribPatsList`] containing distribution patterns.
+    /// A [`DistribPatsList`] containing distribution patterns. Entries whose
+    /// pattern is not a valid wildmat are skipped.
     ///
     /// # Example
     ///
@@ -732,24 +1256,24 @@ impl<S: AsyncStream> NntpClient<S> {
         // Format: weight:wildmat:distribution
         match response {
             Response::NewsgroupList(groups) => {
-                let pats: Vec<DistribPat> = groups
+                let pats = groups
                     .into_iter()
-                    .map(|g| {
+                    .filter_map(|g| {
                         // The name field might contain "weight:wildmat:distribution"
                         let parts: Vec<&str> = g.name.splitn(3, ':').collect();
-                        if parts.len() >= 3 {
+                        Some(if parts.len() >= 3 {
                             DistribPat {
                                 weight: parts[0].parse().unwrap_or(0),
-                                wildmat: parts[1].to_string(),
+                                wildmat: Wildmat::new(parts[1]).ok()?,
                                 distribution: parts[2].to_string(),
                             }
                         } else {
                             DistribPat {
                                 weight: 0,
-                                wildmat: g.name,
+                                wildmat: Wildmat::new(&g.name).ok()?,
                                 distribution: String::new(),
                             }
-                        }
+                        })
                     })
                     .collect();
                 Ok(DistribPatsList(pats))
@@ -810,7 +1334,8 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// # Returns
     ///
-    /// A [`ModeratorsList`] containing moderator patterns.
+    /// A [`ModeratorsList`] containing moderator patterns. Entries whose
+    /// pattern is not a valid wildmat are skipped.
     pub async fn list_moderators(&mut self) -> Result<ModeratorsList> {
         let response = self
             .send_command(Command::List(crate::ListVariant::Moderators))
@@ -825,21 +1350,21 @@ impl<S: AsyncStream> NntpClient<S> {
         // Format: wildmat:template
         match response {
             Response::NewsgroupList(groups) => {
-                let mods: Vec<ModeratorEntry> = groups
+                let mods = groups
                     .into_iter()
-                    .map(|g| {
+                    .filter_map(|g| {
                         let parts: Vec<&str> = g.name.splitn(2, ':').collect();
-                        if parts.len() >= 2 {
+                        Some(if parts.len() >= 2 {
                             ModeratorEntry {
-                                wildmat: parts[0].to_string(),
+                                wildmat: Wildmat::new(parts[0]).ok()?,
                                 template: parts[1].to_string(),
                             }
                         } else {
                             ModeratorEntry {
-                                wildmat: g.name,
+                                wildmat: Wildmat::new(&g.name).ok()?,
                                 template: String::new(),
                             }
-                        }
+                        })
                     })
                     .collect();
                 Ok(ModeratorsList(mods))
@@ -915,9 +1440,7 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// # Arguments
     ///
-    /// * `date` - Date in YYMMDD or YYYYMMDD format
-    /// * `time` - Time in HHMMSS format
-    /// * `gmt` - Whether the time is in GMT
+    /// * `since` - Time after which the groups were created
     /// * `distributions` - Optional distributions parameter
     ///
     /// # Returns
@@ -925,16 +1448,12 @@ impl<S: AsyncStream> NntpClient<S> {
     /// A [`NewsgroupList`] containing new newsgroups.
     pub async fn newgroups(
         &mut self,
-        date: String,
-        time: String,
-        gmt: bool,
+        since: Timestamp,
         distributions: Option<String>,
     ) -> Result<NewsgroupList> {
         let response = self
             .send_command(Command::NewGroups {
-                date,
-                time,
-                gmt,
+                since,
                 distributions,
             })
             .await?;
@@ -954,27 +1473,15 @@ impl<S: AsyncStream> NntpClient<S> {
     /// # Arguments
     ///
     /// * `wildmat` - Wildcard pattern for newsgroups
-    /// * `date` - Date in YYMMDD or YYYYMMDD format
-    /// * `time` - Time in HHMMSS format
-    /// * `gmt` - Whether the time is in GMT
+    /// * `since` - Time after which the articles arrived, in server time;
+    ///   see [`clock_skew`](Self::clock_skew)
     ///
     /// # Returns
     ///
     /// A [`MessageIdList`] containing message-ids for new articles.
-    pub async fn newnews(
-        &mut self,
-        wildmat: String,
-        date: String,
-        time: String,
-        gmt: bool,
-    ) -> Result<MessageIdList> {
+    pub async fn newnews(&mut self, wildmat: String, since: Timestamp) -> Result<MessageIdList> {
         let response = self
-            .send_command(Command::NewNews {
-                wildmat,
-                date,
-                time,
-                gmt,
-            })
+            .send_command(Command::NewNews { wildmat, since })
             .await?;
         if let Response::Error { code, message } = &response {
             return Err(Error::Protocol {
@@ -987,7 +1494,8 @@ impl<S: AsyncStream> NntpClient<S> {
 
     /// Post an article.
     ///
-    /// Sends a POST command followed by the article content.
+    /// Sends a POST command followed by the article content. Line endings
+    /// are normalized to CRLF and lines starting with "." are dot-stuffed.
     ///
     /// # Arguments
     ///
@@ -1000,9 +1508,8 @@ impl<S: AsyncStream> NntpClient<S> {
         let response = self.send_command(Command::Post).await?;
         match response {
             Response::PostAccepted => {
-                // Send article content followed by a line with just a dot
-                let mut content = article.into_bytes();
-                content.extend_from_slice(b"\r\n.\r\n");
+                // Send the dot-stuffed article followed by a line with just a dot
+                let content = codec::dot_stuff(article.as_bytes());
                 self.stream
                     .write_all(&content)
                     .await
@@ -1047,16 +1554,25 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// # Returns
     ///
-    /// A [`ServerDate`] containing the date/time in YYYYMMDDHHMMSS format.
+    /// A [`ServerDate`] holding the server's current UTC time.
     pub async fn date(&mut self) -> Result<ServerDate> {
         let response = self.send_command(Command::Date).await?;
-        if let Response::Error { code, message } = &response {
-            return Err(Error::Protocol {
-                code: *code,
-                message: message.clone(),
-            });
-        }
-        response.try_into()
+        reply_into(response)
+    }
+
+    /// Measure how far the server clock is ahead of the local clock.
+    ///
+    /// Sends a DATE command and compares the reply with the local time in
+    /// the middle of the round trip. The result is in seconds, negative if
+    /// the server clock is behind. Add it to a local time to get the server
+    /// time to pass to [`newnews`](Self::newnews) or
+    /// [`newgroups`](Self::newgroups), so that incremental polling neither
+    /// misses nor repeats articles when the clocks disagree.
+    pub async fn clock_skew(&mut self) -> Result<i64> {
+        let sent = SystemTime::now();
+        let date = self.date().await?;
+        let round_trip = sent.elapsed().unwrap_or_default();
+        Ok(date.clock_skew((sent + round_trip / 2).into()))
     }
 
     /// Move to previous article in current group.
@@ -1099,16 +1615,37 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// Sends an HDR command to retrieve a specific header field for articles.
     ///
+    /// Servers that only know the RFC 2980 XHDR command are handled like in
+    /// [`over`](Self::over), and so are compressed replies, using XZHDR
+    /// instead of XZVER.
+    ///
     /// # Arguments
     ///
     /// * `field` - The header field name (e.g., "Subject", "From")
-    /// * `range` - Optional range specification
+    /// * `range` - Optional article range, or `None` for the current article
     ///
     /// # Returns
     ///
     /// A [`HeaderData`] containing header entries.
-    pub async fn hdr(&mut self, field: String, range: Option<String>) -> Result<HeaderData> {
-        let response = self.send_command(Command::Hdr { field, range }).await?;
+    pub async fn hdr(&mut self, field: String, range: Option<ArticleRange>) -> Result<HeaderData> {
+        #[cfg(feature = "compression")]
+        if self
+            .use_compressed_command(Capabilities::supports_xzhdr)
+            .await?
+        {
+            let response = self.send_command(Command::Xzhdr { field, range }).await?;
+            return reply_into(response);
+        }
+
+        let form = self.command_form(self.hdr_form, Capabilities::supports_hdr);
+        let standard = Command::Hdr {
+            field: field.clone(),
+            range: range.clone(),
+        };
+        let (response, form) = self
+            .send_with_fallback(form, standard, Command::Xhdr { field, range })
+            .await?;
+        self.hdr_form = form;
         if let Response::Error { code, message } = &response {
             return Err(Error::Protocol {
                 code: *code,
@@ -1122,15 +1659,54 @@ impl<S: AsyncStream> NntpClient<S> {
     ///
     /// Sends an OVER command to retrieve overview data for articles.
     ///
+    /// Servers that only know the RFC 2980 XOVER command are detected from
+    /// the cached capabilities or from a 500 reply to OVER, in which case
+    /// XOVER is sent instead for the rest of the connection.
+    ///
+    /// With the `compression` feature, compressed replies are used when the
+    /// cached capabilities advertise them: XFEATURE COMPRESS GZIP is sent
+    /// once before the first request, or else XZVER replaces OVER. The
+    /// result is the same as for an uncompressed reply.
+    ///
+    /// The entries carry the server's [`OverviewSchema`], fetched with
+    /// [`overview_schema`](Self::overview_schema) before the first request,
+    /// so that fields such as `Xref` can be read by name.
+    ///
     /// # Arguments
     ///
-    /// * `range` - Optional range specification
+    /// * `range` - Optional article range, or `None` for the current article
     ///
     /// # Returns
     ///
     /// An [`OverviewData`] containing overview entries.
-    pub async fn over(&mut self, range: Option<String>) -> Result<OverviewData> {
-        let response = self.send_command(Command::Over { range }).await?;
+    pub async fn over(&mut self, range: Option<ArticleRange>) -> Result<OverviewData> {
+        let schema = self.overview_schema().await?;
+        let mut overview = self.over_entries(range).await?;
+        for entry in &mut overview.0 {
+            entry.schema = schema.clone();
+        }
+        Ok(overview)
+    }
+
+    /// Send OVER or one of its alternatives and decode the entries.
+    async fn over_entries(&mut self, range: Option<ArticleRange>) -> Result<OverviewData> {
+        #[cfg(feature = "compression")]
+        if self
+            .use_compressed_command(Capabilities::supports_xzver)
+            .await?
+        {
+            let response = self.send_command(Command::Xzver { range }).await?;
+            return reply_into(response);
+        }
+
+        let form = self.command_form(self.over_form, Capabilities::supports_over);
+        let standard = Command::Over {
+            range: range.clone(),
+        };
+        let (response, form) = self
+            .send_with_fallback(form, standard, Command::Xover { range })
+            .await?;
+        self.over_form = form;
         if let Response::Error { code, message } = &response {
             return Err(Error::Protocol {
                 code: *code,
@@ -1140,25 +1716,55 @@ impl<S: AsyncStream> NntpClient<S> {
         response.try_into()
     }
 
-    /// Offer an article to the server.
+    /// Find articles whose header field matches a pattern.
     ///
-    /// Sends an IHAVE command to offer an article to the server for transfer.
+    /// Sends an RFC 2980 XPAT command, which lets the server search headers
+    /// that are not in the overview database.
     ///
     /// # Arguments
     ///
-    /// * `message_id` - The message-id of the article being offered
-    /// * `article` - The article content
+    /// * `field` - The header field name (e.g., "Subject", "From")
+    /// * `range` - Article range or message-id to search
+    /// * `patterns` - Wildmat patterns; an article matches if any matches
     ///
-    /// # Errors
+    /// # Returns
     ///
-    /// Returns an error if the server rejects the article.
-    pub async fn ihave(&mut self, message_id: String, article: String) -> Result<()> {
-        let response = self.send_command(Command::Ihave { message_id }).await?;
+    /// A [`HeaderData`] with the matching articles and their field values.
+    pub async fn xpat(
+        &mut self,
+        field: String,
+        range: ArticleRange,
+        patterns: Vec<String>,
+    ) -> Result<HeaderData> {
+        let response = self
+            .send_command(Command::Xpat {
+                field,
+                range,
+                patterns,
+            })
+            .await?;
+        reply_into(response)
+    }
+
+    /// Offer an article to the server.
+    ///
+    /// Sends an IHAVE command to offer an article to the server for transfer.
+    /// The article is sent dot-stuffed with CRLF line endings.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - The message-id of the article being offered
+    /// * `article` - The article content
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server rejects the article.
+    pub async fn ihave(&mut self, message_id: MessageId, article: String) -> Result<()> {
+        let response = self.send_command(Command::Ihave { message_id }).await?;
         match response {
             Response::ArticleWanted => {
-                // Send article content followed by a line with just a dot
-                let mut content = article.into_bytes();
-                content.extend_from_slice(b"\r\n.\r\n");
+                // Send the dot-stuffed article followed by a line with just a dot
+                let content = codec::dot_stuff(article.as_bytes());
                 self.stream
                     .write_all(&content)
                     .await
@@ -1185,6 +1791,118 @@ impl<S: AsyncStream> NntpClient<S> {
         }
     }
 
+    /// Switch to streaming mode for article transfer (RFC 4644).
+    ///
+    /// Sends a MODE STREAM command. Afterwards, articles can be fed with
+    /// [`stream_articles`](Self::stream_articles).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server does not permit streaming.
+    pub async fn mode_stream(&mut self) -> Result<()> {
+        let response = self.send_command(Command::ModeStream).await?;
+        match response {
+            Response::StreamingPermitted => Ok(()),
+            Response::Error { code, message } => Err(Error::Protocol { code, message }),
+            _ => Err(Error::InvalidResponse(
+                "Expected streaming permitted response".to_string(),
+            )),
+        }
+    }
+
+    /// Feed articles to the server with CHECK and TAKETHIS (RFC 4644).
+    ///
+    /// Each article is offered with CHECK and sent with TAKETHIS once the
+    /// server wants it. Up to [`pipeline_depth`](Self::pipeline_depth) CHECK
+    /// commands are kept in flight, so a slow round trip does not limit the
+    /// feed rate. Call [`mode_stream`](Self::mode_stream) first.
+    ///
+    /// # Arguments
+    ///
+    /// * `articles` - Pairs of message-id and article content
+    ///
+    /// # Returns
+    ///
+    /// The message-id and [`FeedOutcome`] of every article, in the order the
+    /// server decided on them. Deferred articles may be offered again later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server answers with an error such as 500
+    /// (streaming not supported), if a reply names another article than the
+    /// one it answers, or if the connection fails. The replies
+    /// to the commands still in flight are read first, so the connection
+    /// stays usable.
+    pub async fn stream_articles<I>(&mut self, articles: I) -> Result<Vec<(MessageId, FeedOutcome)>>
+    where
+        I: IntoIterator<Item = (MessageId, Vec<u8>)>,
+    {
+        let mut request = Vec::new();
+        let result = self.feed_articles(articles, &mut request).await;
+        if result.is_err() {
+            // Read the replies still due so that the connection can be used
+            // again; the first error is the one worth reporting
+            let _ = self.drain_pending(&mut request).await;
+        }
+        result
+    }
+
+    /// Offer articles with CHECK and send the wanted ones with TAKETHIS.
+    async fn feed_articles<I>(
+        &mut self,
+        articles: I,
+        request: &mut Vec<u8>,
+    ) -> Result<Vec<(MessageId, FeedOutcome)>>
+    where
+        I: IntoIterator<Item = (MessageId, Vec<u8>)>,
+    {
+        let mut articles = articles.into_iter().fuse();
+        // Message-ids of the commands awaiting a reply, with the article
+        // for CHECK
+        let mut offered: VecDeque<(MessageId, Option<Vec<u8>>)> = VecDeque::new();
+        let mut outcomes = Vec::new();
+        loop {
+            while self.client.pending_commands() < self.pipeline_depth {
+                let Some((message_id, article)) = articles.next() else {
+                    break;
+                };
+                let check = Command::Check {
+                    message_id: message_id.clone(),
+                };
+                request.extend_from_slice(&self.encode(check)?);
+                offered.push_back((message_id, Some(article)));
+            }
+            let Some((message_id, article)) = offered.pop_front() else {
+                break;
+            };
+
+            self.flush_request(request).await?;
+            let response = self.read_response().await?;
+            if let Some(reply_id) = feed_reply_id(&response) {
+                if reply_id != message_id.as_str() {
+                    return Err(Error::InvalidResponse(format!(
+                        "Streaming reply for {reply_id} while waiting for {message_id}"
+                    )));
+                }
+            }
+            match (response, article) {
+                (Response::CheckWanted { .. }, Some(article)) => {
+                    let takethis = Command::Takethis {
+                        message_id: message_id.clone(),
+                        article,
+                    };
+                    request.extend_from_slice(&self.encode(takethis)?);
+                    offered.push_back((message_id, None));
+                }
+                (Response::Error { code, message }, _) => {
+                    return Err(Error::Protocol { code, message });
+                }
+                (response, _) => outcomes.push((message_id, response.try_into()?)),
+            }
+        }
+        Ok(outcomes)
+    }
+
     /// Quit and close connection.
     ///
     /// Sends a QUIT command and shuts down the connection.
@@ -1210,8 +1928,22 @@ impl<S: AsyncStream> NntpClient<S> {
     }
 
     /// Send a command and wait for response.
+    ///
+    /// An article number in a group other than the selected one is preceded
+    /// by a GROUP command for that group.
     async fn send_command(&mut self, command: Command) -> Result<Response> {
-        let request = self.client.encode_command(command)?;
+        if let Some(group) = self.client.group_selection_for(&command) {
+            let response = self.exchange(group).await?;
+            if let Response::Error { code, message } = response {
+                return Err(Error::Protocol { code, message });
+            }
+        }
+        self.exchange(command).await
+    }
+
+    /// Send a single command as is and wait for its reply.
+    async fn exchange(&mut self, command: Command) -> Result<Response> {
+        let request = self.encode(command)?;
 
         self.stream
             .write_all(&request)
@@ -1221,6 +1953,117 @@ impl<S: AsyncStream> NntpClient<S> {
         self.read_response().await
     }
 
+    /// Pick the form of a command with an RFC 2980 predecessor, using the
+    /// cached capabilities until the server's behaviour has been observed.
+    fn command_form(
+        &self,
+        learned: CommandForm,
+        advertised: fn(&Capabilities) -> bool,
+    ) -> CommandForm {
+        match (learned, &self.capabilities) {
+            (CommandForm::Unknown, Some(caps)) if advertised(caps) => CommandForm::Standard,
+            (CommandForm::Unknown, Some(_)) => CommandForm::Legacy,
+            (learned, _) => learned,
+        }
+    }
+
+    /// Send the RFC 3977 form of a command, or its RFC 2980 form if the
+    /// server is known or found to lack the former.
+    ///
+    /// Returns the reply and the form the server accepts.
+    async fn send_with_fallback(
+        &mut self,
+        form: CommandForm,
+        standard: Command,
+        legacy: Command,
+    ) -> Result<(Response, CommandForm)> {
+        if form == CommandForm::Legacy {
+            let response = self.send_command(legacy).await?;
+            return Ok((response, CommandForm::Legacy));
+        }
+
+        let response = self.send_command(standard).await?;
+        match response {
+            // 500: the command is unknown to the server
+            Response::Error { code: 500, .. } if form == CommandForm::Unknown => {
+                let response = self.send_command(legacy).await?;
+                let form = match response {
+                    Response::Error { code: 500, .. } => CommandForm::Unknown,
+                    _ => CommandForm::Legacy,
+                };
+                Ok((response, form))
+            }
+            response => Ok((response, CommandForm::Standard)),
+        }
+    }
+
+    /// Prepare compressed replies as advertised by the cached capabilities.
+    ///
+    /// Sends XFEATURE COMPRESS GZIP the first time it is advertised, which
+    /// compresses the replies of the regular commands. Otherwise returns
+    /// whether the compressed command checked by `advertised` should be
+    /// used instead of the regular one.
+    #[cfg(feature = "compression")]
+    async fn use_compressed_command(
+        &mut self,
+        advertised: fn(&Capabilities) -> bool,
+    ) -> Result<bool> {
+        let Some(caps) = &self.capabilities else {
+            return Ok(false);
+        };
+        if caps.supports_xfeature_gzip() {
+            if !self.xfeature_gzip_sent {
+                let terminator = caps.has_argument("XFEATURE-COMPRESS", "TERMINATOR");
+                self.xfeature_gzip_sent = true;
+                // If the server refuses after all, replies stay uncompressed
+                self.send_command(Command::XFeatureCompressGzip { terminator })
+                    .await?;
+            }
+            return Ok(false);
+        }
+        Ok(advertised(caps))
+    }
+
+    /// Encode a command, discarding cached state it may invalidate.
+    fn encode(&mut self, command: Command) -> Result<Bytes> {
+        if command.changes_capabilities() {
+            self.discard_cached_state();
+        }
+        self.client.encode_command(command)
+    }
+
+    /// Forget what was learned about the server's capabilities.
+    fn discard_cached_state(&mut self) {
+        self.capabilities = None;
+        self.over_form = CommandForm::Unknown;
+        self.hdr_form = CommandForm::Unknown;
+        self.overview_format = None;
+        self.overview_schema = None;
+    }
+
+    /// Write encoded commands that have not been sent yet.
+    async fn flush_request(&mut self, request: &mut Vec<u8>) -> Result<()> {
+        if request.is_empty() {
+            return Ok(());
+        }
+        self.stream
+            .write_all(request)
+            .await
+            .map_err(|e| Error::Io(format!("Failed to send command: {e}")))?;
+        request.clear();
+        Ok(())
+    }
+
+    /// Send encoded commands that have not been sent yet and read every
+    /// reply still due, discarding them.
+    async fn drain_pending(&mut self, request: &mut Vec<u8>) -> Result<()> {
+        self.flush_request(request).await?;
+        while self.client.pending_commands() > 0 {
+            self.read_response().await?;
+        }
+        Ok(())
+    }
+
     /// Read a complete response from the server.
     async fn read_response(&mut self) -> Result<Response> {
         loop {
@@ -1246,11 +2089,920 @@ impl<S: AsyncStream> NntpClient<S> {
     }
 }
 
+/// Extract the host from a "host:port" address.
+#[cfg(feature = "rustls")]
+fn host_from_addr(addr: &str) -> &str {
+    let host = addr.rsplit_once(':').map_or(addr, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Convert a reply into the expected type, turning error replies into
+/// [`Error::Protocol`].
+fn reply_into<T>(response: Response) -> Result<T>
+where
+    T: TryFrom<Response, Error = Error>,
+{
+    if let Response::Error { code, message } = response {
+        return Err(Error::Protocol { code, message });
+    }
+    response.try_into()
+}
+
+/// Get the message-id a CHECK or TAKETHIS reply is about.
+fn feed_reply_id(response: &Response) -> Option<&str> {
+    match response {
+        Response::CheckWanted { message_id }
+        | Response::CheckDeferred { message_id }
+        | Response::CheckNotWanted { message_id }
+        | Response::TakethisAccepted { message_id }
+        | Response::TakethisRejected { message_id } => Some(message_id),
+        _ => None,
+    }
+}
+
+/// Extract the content of a HEAD or BODY reply.
+fn article_content(response: Response, expected: &str) -> Result<Vec<u8>> {
+    match response {
+        Response::Article { content, .. } => Ok(content),
+        Response::Error { code, message } => Err(Error::Protocol { code, message }),
+        _ => Err(Error::InvalidResponse(expected.to_string())),
+    }
+}
+
+/// A scripted in-memory server for tests of code built on [`NntpClient`].
+#[cfg(test)]
+pub(crate) mod scripted {
+    use std::collections::VecDeque;
+
+    use async_trait::async_trait;
+
+    use super::NntpClient;
+    use crate::runtime::AsyncStream;
+    use crate::transport::Transport;
+
+    /// In-memory stream answering every command line with the next scripted
+    /// reply.
+    pub(crate) struct ScriptedStream {
+        /// Replies to commands not received yet
+        pub(crate) replies: VecDeque<Vec<u8>>,
+        /// Replies released by written commands but not read yet
+        readable: VecDeque<Vec<u8>>,
+        /// Command lines received so far
+        pub(crate) commands: Vec<String>,
+        /// Largest number of commands written but not yet answered
+        pub(crate) max_outstanding: usize,
+        /// Data block bytes received so far, as written on the wire
+        pub(crate) data: Vec<u8>,
+        /// A data block following TAKETHIS, or an accepted POST or IHAVE,
+        /// is being written
+        in_data: bool,
+        /// The data block being written gets its own reply (POST and IHAVE)
+        data_answered: bool,
+    }
+
+    impl ScriptedStream {
+        pub(crate) fn new(replies: &[&str]) -> Self {
+            Self {
+                replies: replies.iter().map(|r| r.as_bytes().to_vec()).collect(),
+                readable: VecDeque::new(),
+                commands: Vec::new(),
+                max_outstanding: 0,
+                data: Vec::new(),
+                in_data: false,
+                data_answered: false,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AsyncStream for ScriptedStream {
+        async fn connect(_addr: &str) -> std::io::Result<Self> {
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+
+        async fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            // One reply per read; an empty queue means the client waits for
+            // a reply to a command it has not sent
+            let Some(mut reply) = self.readable.pop_front() else {
+                return Ok(0);
+            };
+            let n = reply.len().min(buf.len());
+            buf[..n].copy_from_slice(&reply[..n]);
+            if n < reply.len() {
+                self.readable.push_front(reply.split_off(n));
+            }
+            Ok(n)
+        }
+
+        async fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+            let text = String::from_utf8_lossy(buf);
+            for line in text.split_terminator("\r\n") {
+                if self.in_data {
+                    self.data.extend_from_slice(line.as_bytes());
+                    self.data.extend_from_slice(b"\r\n");
+                    self.in_data = line != ".";
+                    if !self.in_data && self.data_answered {
+                        let reply = self.replies.pop_front().expect("unexpected data block");
+                        self.readable.push_back(reply);
+                    }
+                    continue;
+                }
+                let reply = self.replies.pop_front().expect("unexpected command");
+                self.data_answered = (line == "POST" && reply.starts_with(b"340"))
+                    || (line.starts_with("IHAVE ") && reply.starts_with(b"335"));
+                self.in_data = self.data_answered || line.starts_with("TAKETHIS ");
+                self.commands.push(line.to_string());
+                self.readable.push_back(reply);
+            }
+            self.max_outstanding = self.max_outstanding.max(self.readable.len());
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn scripted_client(replies: &[&str]) -> NntpClient<ScriptedStream> {
+        NntpClient::with_transport(Transport::new(ScriptedStream::new(replies)))
+    }
+
+    /// Get the command lines a scripted client has sent.
+    pub(crate) fn sent_commands(client: &NntpClient<ScriptedStream>) -> &[String] {
+        &client.stream.get_ref().commands
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::scripted::scripted_client;
+    #[cfg(feature = "compression")]
+    use super::scripted::ScriptedStream;
+    use super::*;
+    use crate::ArticleSpec;
+
     #[test]
     fn test_net_client_module_compiles() {
         // Basic compilation test
         // Integration tests would require a test NNTP server
     }
+
+    #[cfg(feature = "rustls")]
+    #[test]
+    fn test_host_from_addr() {
+        assert_eq!(host_from_addr("news.example.com:563"), "news.example.com");
+        assert_eq!(host_from_addr("[::1]:563"), "::1");
+        assert_eq!(host_from_addr("localhost"), "localhost");
+    }
+
+    fn message_ids(ids: &[&str]) -> Vec<ArticleSpec> {
+        ids.iter()
+            .map(|id| ArticleSpec::MessageId(id.parse().unwrap()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_rejects_invalid_command_before_sending() {
+        let mut client = scripted_client(&["223 0 <b@x>\r\n"]);
+
+        let result = client
+            .pipeline(vec![
+                Command::Stat(ArticleSpec::MessageId("<a@x>".parse().unwrap())),
+                Command::Hdr {
+                    field: "Bad\r\nField".to_string(),
+                    range: None,
+                },
+                Command::Stat(ArticleSpec::MessageId("<b@x>".parse().unwrap())),
+            ])
+            .await;
+        assert!(matches!(result, Err(Error::InvalidCommand(_))));
+        assert!(client.stream.get_ref().commands.is_empty());
+
+        // The next reply is paired with the next command
+        let pointer = client
+            .stat(ArticleSpec::MessageId("<b@x>".parse().unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(pointer.message_id.unwrap(), "<b@x>");
+        assert_eq!(client.stream.get_ref().commands, ["STAT <b@x>"]);
+    }
+
+    #[tokio::test]
+    async fn test_stat_batch_pipelines_commands() {
+        let mut client = scripted_client(&[
+            "223 0 <a@x>\r\n",
+            "430 No such article\r\n",
+            "223 0 <c@x>\r\n",
+        ]);
+
+        let results = client
+            .stat_batch(message_ids(&["<a@x>", "<b@x>", "<c@x>"]))
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0].as_ref().unwrap().message_id.as_ref().unwrap(),
+            "<a@x>"
+        );
+        assert!(matches!(results[1], Err(Error::Protocol { code: 430, .. })));
+        assert_eq!(
+            results[2].as_ref().unwrap().message_id.as_ref().unwrap(),
+            "<c@x>"
+        );
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["STAT <a@x>", "STAT <b@x>", "STAT <c@x>"]
+        );
+        assert_eq!(client.stream.get_ref().max_outstanding, 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_respects_pipeline_depth() {
+        let mut client = scripted_client(&[
+            "222 0 <a@x>\r\nbody a\r\n.\r\n",
+            "222 0 <b@x>\r\n..body b\r\n.\r\n",
+            "222 0 <c@x>\r\nbody c\r\n.\r\n",
+        ]);
+        client.set_pipeline_depth(2);
+
+        let bodies = client
+            .body_batch(message_ids(&["<a@x>", "<b@x>", "<c@x>"]))
+            .await
+            .unwrap();
+
+        let bodies: Vec<Vec<u8>> = bodies.into_iter().map(|body| body.unwrap()).collect();
+        assert_eq!(
+            bodies,
+            [
+                b"body a\r\n".to_vec(),
+                b".body b\r\n".to_vec(),
+                b"body c\r\n".to_vec()
+            ]
+        );
+        assert_eq!(client.stream.get_ref().max_outstanding, 2);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_waits_for_barrier_reply() {
+        let mut client = scripted_client(&[
+            "111 20240101000000\r\n",
+            "211 3 1 3 misc.test\r\n",
+            "223 1 <a@x>\r\n",
+            "223 2 <b@x>\r\n",
+        ]);
+
+        let responses = client
+            .pipeline(vec![
+                Command::Date,
+                Command::Group("misc.test".parse().unwrap()),
+                Command::Next,
+                Command::Next,
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(responses.len(), 4);
+        assert!(matches!(responses[1], Response::GroupSelected { .. }));
+        assert!(matches!(
+            responses[3],
+            Response::ArticleStatus { number: 2, .. }
+        ));
+        // DATE and GROUP go out together, the NEXTs only after GROUP's reply
+        assert_eq!(client.stream.get_ref().max_outstanding, 2);
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_cached_until_mode_reader() {
+        let mut client = scripted_client(&[
+            "101 Capability list:\r\nVERSION 2\r\nMODE-READER\r\n.\r\n",
+            "200 Reader mode, posting allowed\r\n",
+            "101 Capability list:\r\nVERSION 2\r\nREADER\r\n.\r\n",
+        ]);
+
+        let caps = client.capabilities().await.unwrap();
+        assert!(caps.has_mode_reader());
+        let caps = client.capabilities().await.unwrap();
+        assert!(caps.has_mode_reader());
+        assert_eq!(client.stream.get_ref().commands, ["CAPABILITIES"]);
+
+        client.mode_reader().await.unwrap();
+        assert!(client.cached_capabilities().is_none());
+        let caps = client.capabilities().await.unwrap();
+        assert!(caps.is_reader());
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["CAPABILITIES", "MODE READER", "CAPABILITIES"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_discarded_after_authentication() {
+        let mut client = scripted_client(&[
+            "101 Capability list:\r\nVERSION 2\r\nAUTHINFO USER\r\n.\r\n",
+            "381 Password required\r\n",
+            "281 Authentication accepted\r\n",
+        ]);
+
+        client.capabilities().await.unwrap();
+        client.authenticate("user", "secret").await.unwrap();
+        assert!(client.cached_capabilities().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_sasl_chooses_offered_mechanism() {
+        let mut client = scripted_client(&[
+            "101 Capability list:\r\nVERSION 2\r\nAUTHINFO SASL\r\nSASL PLAIN\r\n.\r\n",
+            "281 Authentication accepted\r\n",
+        ]);
+
+        client
+            .authenticate_sasl(sasl::password_mechanisms("tim", "tanstaaf"))
+            .await
+            .unwrap();
+        assert!(client.cached_capabilities().is_none());
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["CAPABILITIES", "AUTHINFO SASL PLAIN AHRpbQB0YW5zdGFhZg=="]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_sasl_without_common_mechanism() {
+        let mut client =
+            scripted_client(&["101 Capability list:\r\nVERSION 2\r\nSASL GSSAPI\r\n.\r\n"]);
+
+        let result = client
+            .authenticate_sasl(sasl::password_mechanisms("tim", "tanstaaf"))
+            .await;
+        assert!(matches!(result, Err(Error::InvalidCommand(_))));
+        assert_eq!(client.stream.get_ref().commands, ["CAPABILITIES"]);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_over_enables_xfeature_gzip_once() {
+        use std::io::Write;
+
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(b"1\tOne\tfrom@x\tDate\t<1@x>\t\t10\t1\r\n.\r\n")
+            .unwrap();
+        let mut compressed = b"224 Overview follows [COMPRESS=GZIP]\r\n".to_vec();
+        compressed.extend_from_slice(&encoder.finish().unwrap());
+        compressed.extend_from_slice(b".\r\n");
+
+        let mut stream = ScriptedStream::new(&[
+            "101 Capability list:\r\nVERSION 2\r\nOVER\r\nXFEATURE-COMPRESS GZIP TERMINATOR\r\n.\r\n",
+            "290 feature enabled\r\n",
+        ]);
+        stream.replies.push_back(compressed.clone());
+        stream.replies.push_back(compressed);
+        let mut client = NntpClient::with_transport(Transport::new(stream));
+
+        client.capabilities().await.unwrap();
+        for _ in 0..2 {
+            let overview = client.over(Some(ArticleRange::Number(1))).await.unwrap();
+            assert_eq!(overview[0].fields[1], "One");
+        }
+        assert_eq!(
+            client.stream.get_ref().commands,
+            [
+                "CAPABILITIES",
+                "XFEATURE COMPRESS GZIP TERMINATOR",
+                "OVER 1",
+                "OVER 1"
+            ]
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_over_and_hdr_use_xzver_and_xzhdr() {
+        let mut xzver = b"224 Compressed overview follows\r\n".to_vec();
+        xzver.extend_from_slice(&crate::compressed::encode_xz_block(
+            b"1\tOne\tfrom@x\tDate\t<1@x>\t\t10\t1\r\n",
+        ));
+        let mut xzhdr = b"221 Compressed headers follow\r\n".to_vec();
+        xzhdr.extend_from_slice(&crate::compressed::encode_xz_block(b"1 One\r\n"));
+
+        let mut stream = ScriptedStream::new(&[
+            "101 Capability list:\r\nVERSION 2\r\nOVER\r\nHDR\r\nXZVER\r\nXZHDR\r\n.\r\n",
+        ]);
+        stream.replies.push_back(xzver);
+        stream.replies.push_back(xzhdr);
+        let mut client = NntpClient::with_transport(Transport::new(stream));
+
+        client.capabilities().await.unwrap();
+        let overview = client.over(Some(ArticleRange::Number(1))).await.unwrap();
+        assert_eq!(overview[0].fields[1], "One");
+        let headers = client
+            .hdr("Subject".to_string(), Some(ArticleRange::Number(1)))
+            .await
+            .unwrap();
+        assert_eq!(headers[0].value, "One");
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["CAPABILITIES", "XZVER 1", "XZHDR Subject 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_over_falls_back_to_xover() {
+        let mut client = scripted_client(&[
+            "503 No overview format\r\n",
+            "500 What?\r\n",
+            "224 Overview follows\r\n1\tSubject\tFrom\tDate\t<a@x>\t\t10\t1\r\n.\r\n",
+            "224 Overview follows\r\n2\tSubject\tFrom\tDate\t<b@x>\t\t10\t1\r\n.\r\n",
+        ]);
+
+        let overview = client.over(Some(ArticleRange::Number(1))).await.unwrap();
+        assert_eq!(overview[0].message_id(), Some("<a@x>"));
+        let overview = client.over(Some(ArticleRange::Number(2))).await.unwrap();
+        assert_eq!(overview[0].message_id(), Some("<b@x>"));
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["LIST OVERVIEW.FMT", "OVER 1", "XOVER 1", "XOVER 2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_over_reads_fields_by_server_format() {
+        let mut client = scripted_client(&[
+            "215 Order of fields\r\nSubject:\r\nMessage-ID:\r\nBytes:\r\nXref:full\r\n.\r\n",
+            "224 Overview follows\r\n3\tHi\t<a@x>\t512\tXref: host misc.test:3\r\n.\r\n",
+            "224 Overview follows\r\n4\tHo\t<b@x>\t256\tXref: host misc.test:4\r\n.\r\n",
+        ]);
+
+        let overview = client.over(Some(ArticleRange::Number(3))).await.unwrap();
+        assert_eq!(overview[0].message_id(), Some("<a@x>"));
+        assert_eq!(overview[0].byte_count(), Some(512));
+        assert_eq!(overview[0].get("Xref"), Some("host misc.test:3"));
+        let overview = client.over(Some(ArticleRange::Number(4))).await.unwrap();
+        assert_eq!(overview[0].get("Xref"), Some("host misc.test:4"));
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["LIST OVERVIEW.FMT", "OVER 3", "OVER 4"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hdr_form_from_capabilities() {
+        let mut client = scripted_client(&[
+            "101 Capability list:\r\nVERSION 2\r\nREADER\r\n.\r\n",
+            "221 Header follows\r\n1 Hello\r\n.\r\n",
+        ]);
+
+        client.capabilities().await.unwrap();
+        let headers = client
+            .hdr("Subject".to_string(), Some(ArticleRange::Number(1)))
+            .await
+            .unwrap();
+        assert_eq!(headers[0].value, "Hello");
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["CAPABILITIES", "XHDR Subject 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hdr_keeps_standard_form_after_other_errors() {
+        let mut client = scripted_client(&[
+            "423 No such article\r\n",
+            "225 Headers follow\r\n1 Hello\r\n.\r\n",
+        ]);
+
+        let result = client
+            .hdr("Subject".to_string(), Some(ArticleRange::Number(1)))
+            .await;
+        assert!(matches!(result, Err(Error::Protocol { code: 423, .. })));
+        client
+            .hdr("Subject".to_string(), Some(ArticleRange::Number(1)))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["HDR Subject 1", "HDR Subject 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_overview_format_defaults_when_unsupported() {
+        let mut client = scripted_client(&["503 Not supported\r\n"]);
+
+        let format = client.overview_format().await.unwrap();
+        assert_eq!(format, OverviewFormat::default());
+        client.overview_format().await.unwrap();
+        assert_eq!(client.stream.get_ref().commands, ["LIST OVERVIEW.FMT"]);
+    }
+
+    #[tokio::test]
+    async fn test_overview_format_from_server() {
+        let mut client =
+            scripted_client(&["215 Order of fields\r\nSubject:\r\nFrom:\r\nXref:full\r\n.\r\n"]);
+
+        let format = client.overview_format().await.unwrap();
+        assert_eq!(format[2], "Xref:full");
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_rejects_post() {
+        let mut client = scripted_client(&[]);
+        let result = client.pipeline(vec![Command::Date, Command::Post]).await;
+        assert!(matches!(result, Err(Error::InvalidCommand(_))));
+        assert!(client.stream.get_ref().commands.is_empty());
+    }
+
+    fn feed(ids: &[&str]) -> Vec<(MessageId, Vec<u8>)> {
+        ids.iter()
+            .map(|id| {
+                (
+                    id.parse().unwrap(),
+                    format!("Message-ID: {id}\n\nbody\n").into_bytes(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_articles_pipelines_check_and_takethis() {
+        let mut client = scripted_client(&[
+            "203 Streaming permitted\r\n",
+            "238 <a@x>\r\n",
+            "431 <b@x>\r\n",
+            "438 <c@x>\r\n",
+            "238 <d@x>\r\n",
+            "239 <a@x>\r\n",
+            "439 <d@x>\r\n",
+        ]);
+        client.mode_stream().await.unwrap();
+
+        let outcomes = client
+            .stream_articles(feed(&["<a@x>", "<b@x>", "<c@x>", "<d@x>"]))
+            .await
+            .unwrap();
+
+        let outcomes: Vec<_> = outcomes
+            .iter()
+            .map(|(id, outcome)| (id.as_str(), *outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("<b@x>", FeedOutcome::Deferred),
+                ("<c@x>", FeedOutcome::Rejected),
+                ("<a@x>", FeedOutcome::Accepted),
+                ("<d@x>", FeedOutcome::Rejected),
+            ]
+        );
+        assert_eq!(
+            client.stream.get_ref().commands,
+            [
+                "MODE STREAM",
+                "CHECK <a@x>",
+                "CHECK <b@x>",
+                "CHECK <c@x>",
+                "CHECK <d@x>",
+                "TAKETHIS <a@x>",
+                "TAKETHIS <d@x>",
+            ]
+        );
+        assert_eq!(client.stream.get_ref().max_outstanding, 4);
+    }
+
+    #[tokio::test]
+    async fn test_stream_articles_respects_pipeline_depth() {
+        let mut client = scripted_client(&[
+            "238 <a@x>\r\n",
+            "438 <b@x>\r\n",
+            "239 <a@x>\r\n",
+            "431 <c@x>\r\n",
+        ]);
+        client.set_pipeline_depth(2);
+
+        let outcomes = client
+            .stream_articles(feed(&["<a@x>", "<b@x>", "<c@x>"]))
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(
+            client.stream.get_ref().commands,
+            [
+                "CHECK <a@x>",
+                "CHECK <b@x>",
+                "TAKETHIS <a@x>",
+                "CHECK <c@x>"
+            ]
+        );
+        assert_eq!(client.stream.get_ref().max_outstanding, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_articles_fails_on_error_reply() {
+        let mut client = scripted_client(&["500 Unknown command\r\n"]);
+        let result = client.stream_articles(feed(&["<a@x>"])).await;
+        assert!(matches!(result, Err(Error::Protocol { code: 500, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_stream_articles_rejects_mismatched_reply() {
+        let mut client = scripted_client(&[
+            "238 <a@x>\r\n",
+            "438 <c@x>\r\n",
+            "431 <b@x>\r\n",
+            "239 <a@x>\r\n",
+            "111 20240101120000\r\n",
+        ]);
+
+        let result = client
+            .stream_articles(feed(&["<a@x>", "<b@x>", "<c@x>"]))
+            .await;
+        assert!(matches!(result, Err(Error::InvalidResponse(_))));
+        let date = client.date().await.unwrap();
+        assert_eq!(date.0.to_string(), "20240101120000");
+    }
+
+    #[tokio::test]
+    async fn test_stream_articles_error_leaves_connection_usable() {
+        let mut client = scripted_client(&[
+            "238 <a@x>\r\n",
+            "501 Syntax error\r\n",
+            "438 <c@x>\r\n",
+            "239 <a@x>\r\n",
+            "111 20240101120000\r\n",
+        ]);
+
+        let result = client
+            .stream_articles(feed(&["<a@x>", "<b@x>", "<c@x>"]))
+            .await;
+        assert!(matches!(result, Err(Error::Protocol { code: 501, .. })));
+
+        // The replies to the commands in flight were read, so DATE gets its own
+        let date = client.date().await.unwrap();
+        assert_eq!(date.0.to_string(), "20240101120000");
+        assert_eq!(
+            client.stream.get_ref().commands,
+            [
+                "CHECK <a@x>",
+                "CHECK <b@x>",
+                "CHECK <c@x>",
+                "TAKETHIS <a@x>",
+                "DATE"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_sends_dot_stuffed_crlf_block() {
+        let mut client = scripted_client(&[
+            "340 Send article\r\n",
+            "240 Article received\r\n",
+            "111 20240101120000\r\n",
+        ]);
+
+        let article = "Subject: x\n\n.leading dot\nbody\r\n..two dots\n.\nlast";
+        client.post(article.to_string()).await.unwrap();
+        client.date().await.unwrap();
+
+        let stream = client.stream.get_ref();
+        assert_eq!(stream.commands, ["POST", "DATE"]);
+        assert_eq!(
+            stream.data,
+            b"Subject: x\r\n\r\n..leading dot\r\nbody\r\n...two dots\r\n..\r\nlast\r\n.\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ihave_sends_dot_stuffed_crlf_block() {
+        let mut client = scripted_client(&[
+            "335 Send it\r\n",
+            "235 Article transferred\r\n",
+            "435 Not wanted\r\n",
+        ]);
+
+        let article = "Message-ID: <a@x>\n\n.\n.hidden\n";
+        client
+            .ihave("<a@x>".parse().unwrap(), article.to_string())
+            .await
+            .unwrap();
+        // Not wanted: no data block may follow
+        assert!(client
+            .ihave("<b@x>".parse().unwrap(), article.to_string())
+            .await
+            .is_err());
+
+        let stream = client.stream.get_ref();
+        assert_eq!(stream.commands, ["IHAVE <a@x>", "IHAVE <b@x>"]);
+        assert_eq!(
+            stream.data,
+            b"Message-ID: <a@x>\r\n\r\n..\r\n..hidden\r\n.\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_moderators_and_distrib_pats() {
+        let mut client = scripted_client(&[
+            "215 Moderators\r\n\
+             comp.lang.*,!comp.lang.c:%s@lang.example.org\r\n\
+             comp.[lang:%s@broken.example.org\r\n\
+             *:%s@example.org\r\n\
+             .\r\n",
+            "215 Distribution patterns\r\n\
+             10:local.*:local\r\n\
+             20:!local.*:bad\r\n\
+             3:*:world\r\n\
+             .\r\n",
+        ]);
+
+        // Malformed server patterns are skipped, the others are kept
+        let mods = client.list_moderators().await.unwrap();
+        assert_eq!(mods.len(), 2);
+        assert_eq!(mods[0].wildmat.as_str(), "comp.lang.*,!comp.lang.c");
+        assert!(mods[0].matches("comp.lang.rust"));
+        assert!(!mods[0].matches("comp.lang.c"));
+        assert_eq!(mods[1].wildmat.as_str(), "*");
+
+        let pats = client.list_distrib_pats().await.unwrap();
+        assert_eq!(pats.len(), 2);
+        assert_eq!(pats.distribution("local.test"), Some("local"));
+        assert_eq!(pats.distribution("misc.test"), Some("world"));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_date_keeps_session_in_sync() {
+        let mut client = scripted_client(&["111 yesterday\r\n", "111 20240101120000\r\n"]);
+
+        assert!(matches!(
+            client.date().await,
+            Err(Error::InvalidResponse(_))
+        ));
+        let date = client.date().await.unwrap();
+        assert_eq!(date.0.to_string(), "20240101120000");
+    }
+
+    #[tokio::test]
+    async fn test_xpat_and_xgtitle() {
+        let mut client = scripted_client(&[
+            "221 Header follows\r\n12 Rust news\r\n.\r\n",
+            "282 list follows\r\ncomp.lang.rust\tRust\r\n.\r\n",
+            "481 Permission denied\r\n",
+        ]);
+
+        let matches = client
+            .xpat(
+                "Subject".to_string(),
+                ArticleRange::Between(1, 20),
+                vec!["*Rust*".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(matches[0].article, "12");
+        assert_eq!(matches[0].value, "Rust news");
+
+        let descriptions = client.xgtitle(None).await.unwrap();
+        assert_eq!(descriptions[0].name, "comp.lang.rust");
+        assert_eq!(descriptions[0].description, "Rust");
+
+        let result = client.xgtitle(Some("alt.*".to_string())).await;
+        assert!(matches!(result, Err(Error::Protocol { code: 481, .. })));
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["XPAT Subject 1-20 *Rust*", "XGTITLE", "XGTITLE alt.*"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dates_and_clock_skew() {
+        let server_now = Timestamp::now() + std::time::Duration::from_secs(3600);
+        let date_reply = format!("111 {server_now}\r\n");
+        let mut client = scripted_client(&[
+            &date_reply,
+            "215 information follows\r\nmisc.test 1704067200 admin@example.com\r\n.\r\n",
+            "230 list follows\r\n<new@example.com>\r\n.\r\n",
+        ]);
+
+        let skew = client.clock_skew().await.unwrap();
+        assert!((3599..=3601).contains(&skew), "skew {skew}");
+
+        let times = client.list_active_times().await.unwrap();
+        assert_eq!(times[0].timestamp.to_string(), "20240101000000");
+        assert_eq!(times[0].creator, "admin@example.com");
+
+        let since = times[0].timestamp;
+        let ids = client.newnews("misc.*".to_string(), since).await.unwrap();
+        assert_eq!(*ids, ["<new@example.com>"]);
+        assert_eq!(
+            client.stream.get_ref().commands,
+            [
+                "DATE",
+                "LIST ACTIVE.TIMES",
+                "NEWNEWS misc.* 20240101 000000 GMT"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_listgroup_selects_group() {
+        let mut client = scripted_client(&[
+            "211 3 10 12 misc.test list follows\r\n10\r\n11\r\n12\r\n.\r\n",
+            "211 3 10 12 misc.test list follows\r\n11\r\n12\r\n.\r\n",
+        ]);
+
+        let articles = client
+            .listgroup(Some("misc.test".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(articles.to_string(), "10-12");
+        assert_eq!(articles.name, "misc.test");
+        assert_eq!(articles.stats().last, 12);
+        assert_eq!(client.client.current_group(), Some("misc.test"));
+
+        // A range alone applies to the current group
+        let articles = client
+            .listgroup(None, Some(ArticleRange::StartingAt(11)))
+            .await
+            .unwrap();
+        assert_eq!(articles.to_string(), "11-12");
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["LISTGROUP misc.test", "LISTGROUP misc.test 11-"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_article_number_selects_its_group() {
+        let mut client = scripted_client(&[
+            "211 10 1 10 alt.test\r\n",
+            "223 5 <five@x>\r\n",
+            "223 6 <six@x>\r\n",
+            "411 No such newsgroup\r\n",
+        ]);
+
+        let pointer = client
+            .stat(ArticleSpec::number_in_group("alt.test".parse().unwrap(), 5))
+            .await
+            .unwrap();
+        assert_eq!(pointer.message_id.unwrap(), "<five@x>");
+        // The group is only selected once
+        client
+            .stat(ArticleSpec::number_in_group("alt.test".parse().unwrap(), 6))
+            .await
+            .unwrap();
+
+        let result = client
+            .stat(ArticleSpec::number_in_group(
+                "no.such.group".parse().unwrap(),
+                1,
+            ))
+            .await;
+        assert!(matches!(result, Err(Error::Protocol { code: 411, .. })));
+        assert_eq!(
+            client.stream.get_ref().commands,
+            ["GROUP alt.test", "STAT 5", "STAT 6", "GROUP no.such.group"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_selects_groups_in_order() {
+        let mut client = scripted_client(&[
+            "223 0 <a@x>\r\n",
+            "211 10 1 10 alt.test\r\n",
+            "223 1 <one@x>\r\n",
+            "223 2 <two@x>\r\n",
+            "411 No such newsgroup\r\n",
+        ]);
+
+        let results = client
+            .stat_batch(vec![
+                ArticleSpec::MessageId("<a@x>".parse().unwrap()),
+                ArticleSpec::number_in_group("alt.test".parse().unwrap(), 1),
+                ArticleSpec::number_in_group("alt.test".parse().unwrap(), 2),
+                ArticleSpec::number_in_group("no.such.group".parse().unwrap(), 1),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[1].as_ref().unwrap().message_id.as_ref().unwrap(),
+            "<one@x>"
+        );
+        assert_eq!(
+            results[2].as_ref().unwrap().message_id.as_ref().unwrap(),
+            "<two@x>"
+        );
+        assert!(matches!(results[3], Err(Error::Protocol { code: 411, .. })));
+        assert_eq!(
+            client.stream.get_ref().commands,
+            [
+                "STAT <a@x>",
+                "GROUP alt.test",
+                "STAT 1",
+                "STAT 2",
+                "GROUP no.such.group"
+            ]
+        );
+    }
 }
@@ -0,0 +1,485 @@
+//! Read state in the `.newsrc` format.
+//!
+//! A `.newsrc` file lists one group per line, followed by `:` if the user is
+//! subscribed to it or `!` if not, and the article numbers already read:
+//!
+//! ```text
+//! comp.lang.rust: 1-100,105,110-200
+//! misc.test! 1-5
+//! ```
+//!
+//! [`Newsrc`] parses and writes this format, keeping the order of the groups
+//! so that a file can be read, updated and written back with minimal
+//! changes. Read numbers are stored in a [`RangeSet`].
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::newsrc::Newsrc;
+//! use nntp_rs::response::GroupStats;
+//!
+//! let mut newsrc: Newsrc = "comp.lang.rust: 1-100,105\nmisc.test! 1-5\n".parse().unwrap();
+//!
+//! let group = newsrc.get_mut("comp.lang.rust").unwrap();
+//! group.mark_read(101);
+//! let stats = GroupStats { count: 10, first: 95, last: 106 };
+//! assert_eq!(group.unread(&stats).to_string(), "102-104,106");
+//!
+//! assert_eq!(
+//!     newsrc.to_string(),
+//!     "comp.lang.rust: 1-101,105\nmisc.test! 1-5\n"
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::response::{GroupStats, NewsgroupList};
+use crate::{Error, GroupName, RangeSet, Result};
+
+/// The read state of one group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewsrcGroup {
+    /// Name of the group
+    pub name: GroupName,
+    /// Whether the user is subscribed to the group
+    pub subscribed: bool,
+    /// Article numbers already read
+    pub read: RangeSet,
+}
+
+impl NewsrcGroup {
+    /// Create the state of a group with nothing read.
+    pub fn new(name: GroupName, subscribed: bool) -> Self {
+        NewsrcGroup {
+            name,
+            subscribed,
+            read: RangeSet::new(),
+        }
+    }
+
+    /// Check whether an article was read.
+    pub fn is_read(&self, number: u64) -> bool {
+        self.read.contains(number)
+    }
+
+    /// Mark an article as read.
+    pub fn mark_read(&mut self, number: u64) {
+        self.read.insert(number);
+    }
+
+    /// Mark every article of a set as read.
+    pub fn mark_all_read(&mut self, numbers: &RangeSet) {
+        self.read = self.read.union(numbers);
+    }
+
+    /// Mark an article as unread.
+    pub fn mark_unread(&mut self, number: u64) {
+        self.read.remove(number);
+    }
+
+    /// Mark every article up to the end of the group as read.
+    pub fn catch_up(&mut self, stats: &GroupStats) {
+        self.read.insert_range(1..=stats.last);
+    }
+
+    /// Get the articles of the group that were not read yet.
+    ///
+    /// Numbers between the first and last article of the group may belong
+    /// to articles that have been cancelled; LISTGROUP gives the exact set.
+    pub fn unread(&self, stats: &GroupStats) -> RangeSet {
+        if stats.count == 0 {
+            return RangeSet::new();
+        }
+        RangeSet::from(stats.first..=stats.last).difference(&self.read)
+    }
+
+    /// Forget read numbers outside the articles the server still has.
+    ///
+    /// Everything below the first article is folded into a single `1-n`
+    /// range, which keeps the line short once old articles expire. An empty
+    /// group (`last < first` or `last == 0`) says nothing about which
+    /// articles still exist, so the read set is left alone.
+    fn trim(&mut self, first: u64, last: u64) {
+        if last < first || last == 0 {
+            return;
+        }
+        let mut read = self.read.intersection(&RangeSet::from(first..=last));
+        if first > 1 {
+            read.insert_range(1..=first - 1);
+        }
+        self.read = read;
+    }
+}
+
+impl fmt::Display for NewsrcGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = if self.subscribed { ':' } else { '!' };
+        write!(f, "{}{flag}", self.name)?;
+        if !self.read.is_empty() {
+            write!(f, " {}", self.read)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NewsrcGroup {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let line = s.trim();
+        let Some(flag) = line.find([':', '!']) else {
+            return Err(Error::Parse(format!(
+                "Missing subscription flag in newsrc line: {s}"
+            )));
+        };
+        Ok(NewsrcGroup {
+            name: line[..flag].trim().parse()?,
+            subscribed: line[flag..].starts_with(':'),
+            read: line[flag + 1..].parse()?,
+        })
+    }
+}
+
+/// The changes made by [`Newsrc::reconcile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// Groups found on the server that were added, unsubscribed
+    pub new_groups: Vec<GroupName>,
+    /// Groups in the newsrc that the server does not carry
+    pub missing_groups: Vec<GroupName>,
+}
+
+/// The read state of every group, in `.newsrc` order.
+///
+/// Displays as the contents of a `.newsrc` file. An `options` line at the
+/// top of the file, as written by some newsreaders, is kept as it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Newsrc {
+    /// The `options` line, if the file had one
+    options: Option<String>,
+    /// Groups in file order
+    groups: Vec<NewsrcGroup>,
+    /// Position of each group in `groups`
+    index: HashMap<GroupName, usize>,
+}
+
+impl Newsrc {
+    /// Create an empty newsrc.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of groups.
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Check whether there are no groups.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Iterate over the groups in file order.
+    pub fn groups(&self) -> impl Iterator<Item = &NewsrcGroup> {
+        self.groups.iter()
+    }
+
+    /// Iterate over the groups the user is subscribed to.
+    pub fn subscribed(&self) -> impl Iterator<Item = &NewsrcGroup> {
+        self.groups.iter().filter(|group| group.subscribed)
+    }
+
+    /// Get the state of a group.
+    pub fn get(&self, name: &str) -> Option<&NewsrcGroup> {
+        self.index.get(name).map(|&i| &self.groups[i])
+    }
+
+    /// Get the state of a group for updating.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut NewsrcGroup> {
+        self.index.get(name).map(|&i| &mut self.groups[i])
+    }
+
+    /// Get the state of a group, appending it unsubscribed if it is new.
+    pub fn entry(&mut self, name: GroupName) -> &mut NewsrcGroup {
+        let i = match self.index.get(&name) {
+            Some(&i) => i,
+            None => self.push(NewsrcGroup::new(name, false)),
+        };
+        &mut self.groups[i]
+    }
+
+    /// Subscribe to a group, appending it if it is new.
+    pub fn subscribe(&mut self, name: GroupName) {
+        self.entry(name).subscribed = true;
+    }
+
+    /// Unsubscribe from a group, keeping its read state.
+    pub fn unsubscribe(&mut self, name: &str) {
+        if let Some(group) = self.get_mut(name) {
+            group.subscribed = false;
+        }
+    }
+
+    /// Remove a group and its read state.
+    pub fn remove(&mut self, name: &str) -> Option<NewsrcGroup> {
+        let i = self.index.remove(name)?;
+        let group = self.groups.remove(i);
+        for position in self.index.values_mut() {
+            if *position > i {
+                *position -= 1;
+            }
+        }
+        Some(group)
+    }
+
+    /// Bring the newsrc up to date with the groups listed by LIST ACTIVE.
+    ///
+    /// Read numbers of known groups are trimmed to the articles the server
+    /// still has, and groups the newsrc does not know yet are appended
+    /// unsubscribed. Groups the server does not list are reported but kept,
+    /// since LIST ACTIVE may have been asked with a wildmat.
+    pub fn reconcile(&mut self, active: &NewsgroupList) -> Reconciliation {
+        let mut report = Reconciliation::default();
+        let mut listed = vec![false; self.groups.len()];
+
+        for group in active.iter() {
+            let Ok(name) = GroupName::new(group.name.as_str()) else {
+                continue;
+            };
+            match self.index.get(&name) {
+                Some(&i) => {
+                    listed[i] = true;
+                    self.groups[i].trim(group.first, group.last);
+                }
+                None => {
+                    let mut state = NewsrcGroup::new(name.clone(), false);
+                    state.trim(group.first, group.last);
+                    self.push(state);
+                    report.new_groups.push(name);
+                }
+            }
+        }
+
+        report.missing_groups = self
+            .groups
+            .iter()
+            .zip(listed)
+            .filter(|(_, listed)| !listed)
+            .map(|(group, _)| group.name.clone())
+            .collect();
+        report
+    }
+
+    fn push(&mut self, group: NewsrcGroup) -> usize {
+        self.index.insert(group.name.clone(), self.groups.len());
+        self.groups.push(group);
+        self.groups.len() - 1
+    }
+}
+
+impl fmt::Display for Newsrc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(options) = &self.options {
+            writeln!(f, "{options}")?;
+        }
+        for group in &self.groups {
+            writeln!(f, "{group}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Newsrc {
+    type Err = Error;
+
+    /// Parse the contents of a `.newsrc` file.
+    ///
+    /// Blank lines are skipped. If a group is listed twice, the lines are
+    /// merged.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut newsrc = Newsrc::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if newsrc.is_empty() && newsrc.options.is_none() && line.starts_with("options ") {
+                newsrc.options = Some(line.to_string());
+                continue;
+            }
+            let group: NewsrcGroup = line
+                .parse()
+                .map_err(|e| Error::Parse(format!("newsrc line {}: {e}", number + 1)))?;
+            match newsrc.index.get(&group.name) {
+                Some(&i) => {
+                    let existing = &mut newsrc.groups[i];
+                    existing.subscribed |= group.subscribed;
+                    existing.mark_all_read(&group.read);
+                }
+                None => {
+                    newsrc.push(group);
+                }
+            }
+        }
+        Ok(newsrc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::NewsGroup;
+
+    fn stats(first: u64, last: u64) -> GroupStats {
+        GroupStats {
+            count: last + 1 - first,
+            first,
+            last,
+        }
+    }
+
+    fn active(groups: &[(&str, u64, u64)]) -> NewsgroupList {
+        NewsgroupList(
+            groups
+                .iter()
+                .map(|&(name, first, last)| NewsGroup {
+                    name: name.to_string(),
+                    last,
+                    first,
+                    posting_status: 'y',
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_parse_and_write() {
+        let text = "options -n all !alt.*\n\
+                    comp.lang.rust: 1-100,105,110-200\n\
+                    \n\
+                    misc.test!1-5\n\
+                    alt.test:\n\
+                    de.test !\n";
+        let newsrc: Newsrc = text.parse().unwrap();
+        assert_eq!(newsrc.len(), 4);
+        let rust = newsrc.get("comp.lang.rust").unwrap();
+        assert!(rust.subscribed);
+        assert_eq!(rust.read.len(), 192);
+        assert!(!newsrc.get("misc.test").unwrap().subscribed);
+        assert!(newsrc.get("alt.test").unwrap().read.is_empty());
+
+        let names: Vec<_> = newsrc
+            .subscribed()
+            .map(|group| group.name.as_str())
+            .collect();
+        assert_eq!(names, ["comp.lang.rust", "alt.test"]);
+        assert_eq!(
+            newsrc.to_string(),
+            "options -n all !alt.*\n\
+             comp.lang.rust: 1-100,105,110-200\n\
+             misc.test! 1-5\n\
+             alt.test:\n\
+             de.test!\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("comp.lang.rust 1-5".parse::<Newsrc>().is_err());
+        assert!("comp.lang.rust: 1-x".parse::<Newsrc>().is_err());
+        assert!("bad..name: 1".parse::<Newsrc>().is_err());
+
+        let err = "misc.test: 1\nnoflag\n".parse::<Newsrc>().unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_duplicate_lines_merge() {
+        let newsrc: Newsrc = "misc.test! 1-5\nmisc.test: 7\n".parse().unwrap();
+        assert_eq!(newsrc.to_string(), "misc.test: 1-5,7\n");
+    }
+
+    #[test]
+    fn test_mark_and_unread() {
+        let mut group = NewsrcGroup::new("misc.test".parse().unwrap(), true);
+        group.mark_read(3);
+        group.mark_all_read(&"5-7".parse().unwrap());
+        assert!(group.is_read(6));
+        group.mark_unread(6);
+        assert!(!group.is_read(6));
+        assert_eq!(group.unread(&stats(1, 8)).to_string(), "1-2,4,6,8");
+
+        let empty = GroupStats {
+            count: 0,
+            first: 9,
+            last: 8,
+        };
+        assert!(group.unread(&empty).is_empty());
+
+        group.catch_up(&stats(1, 8));
+        assert!(group.unread(&stats(1, 8)).is_empty());
+        assert_eq!(group.unread(&stats(1, 10)).to_string(), "9-10");
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let mut newsrc = Newsrc::new();
+        newsrc.subscribe("misc.test".parse().unwrap());
+        newsrc.entry("alt.test".parse().unwrap()).mark_read(1);
+        newsrc.subscribe("comp.test".parse().unwrap());
+        newsrc.unsubscribe("misc.test");
+        assert_eq!(newsrc.to_string(), "misc.test!\nalt.test! 1\ncomp.test:\n");
+
+        let removed = newsrc.remove("alt.test").unwrap();
+        assert_eq!(removed.read.to_string(), "1");
+        assert!(newsrc.remove("alt.test").is_none());
+        assert!(newsrc.get_mut("comp.test").unwrap().subscribed);
+        assert_eq!(newsrc.to_string(), "misc.test!\ncomp.test:\n");
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let mut newsrc: Newsrc = "comp.lang.rust: 1-10,20-30,40,500\n\
+                                  gone.group: 1-5\n"
+            .parse()
+            .unwrap();
+        let report = newsrc.reconcile(&active(&[
+            ("comp.lang.rust", 25, 100),
+            ("misc.test", 3000, 3500),
+            ("bad..name", 1, 2),
+        ]));
+
+        assert_eq!(report.new_groups, ["misc.test"]);
+        assert_eq!(report.missing_groups, ["gone.group"]);
+        assert_eq!(
+            newsrc.to_string(),
+            "comp.lang.rust: 1-30,40\ngone.group: 1-5\nmisc.test! 1-2999\n"
+        );
+
+        let unread = newsrc.get("misc.test").unwrap().unread(&stats(3000, 3500));
+        assert_eq!(unread.len(), 501);
+    }
+
+    #[test]
+    fn test_reconcile_keeps_read_state_of_empty_groups() {
+        let mut newsrc: Newsrc = "alt.empty: 1-10,20
+                                  alt.drained: 1-500
+"
+        .parse()
+        .unwrap();
+        let report = newsrc.reconcile(&active(&[
+            ("alt.empty", 0, 0),
+            ("alt.drained", 1, 0),
+            ("alt.new", 0, 0),
+        ]));
+
+        assert_eq!(report.new_groups, ["alt.new"]);
+        assert!(report.missing_groups.is_empty());
+        assert_eq!(
+            newsrc.to_string(),
+            "alt.empty: 1-10,20\nalt.drained: 1-500\nalt.new!\n"
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Compact sets of article numbers.
+//!
+//! A [`RangeSet`] stores article numbers as sorted, disjoint ranges, so that
+//! the millions of numbers a LISTGROUP reply can hold for a big group take
+//! little memory as long as they are mostly contiguous. It supports the set
+//! operations needed to work out which articles still have to be fetched.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::RangeSet;
+//!
+//! let available: RangeSet = "1-1000,1500-2000".parse().unwrap();
+//! let fetched: RangeSet = (1..=800).into();
+//! let missing = available.difference(&fetched);
+//! assert_eq!(missing.to_string(), "801-1000,1500-2000");
+//! assert_eq!(missing.len(), 701);
+//! ```
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::command::ArticleRange;
+use crate::{Error, Result};
+
+/// A set of article numbers stored as sorted, disjoint ranges.
+///
+/// Displays and parses as a comma-separated list of numbers and `n-m`
+/// ranges, e.g. `1-5,7,9-12`, the form used by `.newsrc` files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    /// Inclusive ranges, sorted and neither overlapping nor adjacent
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether the set holds no numbers.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Get the number of article numbers in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|&(first, last)| last - first + 1)
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Get the lowest number in the set.
+    pub fn first(&self) -> Option<u64> {
+        self.ranges.first().map(|&(first, _)| first)
+    }
+
+    /// Get the highest number in the set.
+    pub fn last(&self) -> Option<u64> {
+        self.ranges.last().map(|&(_, last)| last)
+    }
+
+    /// Check whether a number is in the set.
+    pub fn contains(&self, number: u64) -> bool {
+        let i = self.ranges.partition_point(|&(_, last)| last < number);
+        self.ranges
+            .get(i)
+            .is_some_and(|&(first, _)| first <= number)
+    }
+
+    /// Add a number to the set.
+    pub fn insert(&mut self, number: u64) {
+        self.insert_range(number..=number);
+    }
+
+    /// Add all numbers of an inclusive range to the set.
+    pub fn insert_range(&mut self, range: RangeInclusive<u64>) {
+        let (first, last) = range.into_inner();
+        if first > last {
+            return;
+        }
+
+        // Appending in ascending order, as when decoding a listing, is the
+        // common case
+        match self.ranges.last_mut() {
+            None => {
+                self.ranges.push((first, last));
+                return;
+            }
+            Some(end) if first > end.1.saturating_add(1) => {
+                self.ranges.push((first, last));
+                return;
+            }
+            Some(end) if first >= end.0 => {
+                end.1 = end.1.max(last);
+                return;
+            }
+            Some(_) => {}
+        }
+
+        // Merge with every range that overlaps or touches the new one
+        let start = self
+            .ranges
+            .partition_point(|&(_, end)| end.saturating_add(1) < first);
+        let end = self
+            .ranges
+            .partition_point(|&(begin, _)| begin <= last.saturating_add(1));
+        let merged = if start < end {
+            (
+                first.min(self.ranges[start].0),
+                last.max(self.ranges[end - 1].1),
+            )
+        } else {
+            (first, last)
+        };
+        self.ranges.splice(start..end, [merged]);
+    }
+
+    /// Remove a number from the set.
+    pub fn remove(&mut self, number: u64) {
+        *self = self.difference(&RangeSet::from(number..=number));
+    }
+
+    /// Get the numbers that are in either set.
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut ranges: Vec<(u64, u64)> =
+            Vec::with_capacity(self.ranges.len() + other.ranges.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() || j < other.ranges.len() {
+            // Take whichever range starts first
+            let (first, last) = if j == other.ranges.len()
+                || (i < self.ranges.len() && self.ranges[i].0 <= other.ranges[j].0)
+            {
+                i += 1;
+                self.ranges[i - 1]
+            } else {
+                j += 1;
+                other.ranges[j - 1]
+            };
+            match ranges.last_mut() {
+                Some(end) if first <= end.1.saturating_add(1) => end.1 = end.1.max(last),
+                _ => ranges.push((first, last)),
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Get the numbers that are in both sets.
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_first, a_last) = self.ranges[i];
+            let (b_first, b_last) = other.ranges[j];
+            let first = a_first.max(b_first);
+            let last = a_last.min(b_last);
+            if first <= last {
+                ranges.push((first, last));
+            }
+            if a_last < b_last {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Get the numbers that are in this set but not in `other`.
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut ranges = Vec::with_capacity(self.ranges.len());
+        let mut j = 0;
+        for &(first, last) in &self.ranges {
+            let mut first = first;
+            // Ranges of `other` ending before this one cannot overlap later
+            // ranges either
+            while j < other.ranges.len() && other.ranges[j].1 < first {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].0 <= last {
+                let (cut_first, cut_last) = other.ranges[k];
+                if cut_first > first {
+                    ranges.push((first, cut_first - 1));
+                }
+                if cut_last >= last {
+                    break;
+                }
+                first = cut_last + 1;
+                k += 1;
+            }
+            if k == other.ranges.len() || other.ranges[k].0 > last {
+                ranges.push((first, last));
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Iterate over the numbers in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ranges.iter().flat_map(|&(first, last)| first..=last)
+    }
+
+    /// Iterate over the ranges of the set, in ascending order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+        self.ranges.iter().map(|&(first, last)| first..=last)
+    }
+
+    /// Iterate over the ranges of the set as [`ArticleRange`]s, to request
+    /// exactly these articles with OVER or HDR.
+    pub fn article_ranges(&self) -> impl Iterator<Item = ArticleRange> + '_ {
+        self.ranges.iter().map(|&(first, last)| {
+            if first == last {
+                ArticleRange::Number(first)
+            } else {
+                ArticleRange::Between(first, last)
+            }
+        })
+    }
+}
+
+impl From<RangeInclusive<u64>> for RangeSet {
+    fn from(range: RangeInclusive<u64>) -> Self {
+        let mut set = RangeSet::new();
+        set.insert_range(range);
+        set
+    }
+}
+
+impl FromIterator<u64> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u64> for RangeSet {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        for number in iter {
+            self.insert(number);
+        }
+    }
+}
+
+impl fmt::Display for RangeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, &(first, last)) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if first == last {
+                write!(f, "{first}")?;
+            } else {
+                write!(f, "{first}-{last}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RangeSet {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut set = RangeSet::new();
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let number = |n: &str| {
+                n.parse::<u64>()
+                    .map_err(|_| Error::Parse(format!("Invalid article number set: {s}")))
+            };
+            match part.split_once('-') {
+                Some((first, last)) => set.insert_range(number(first)?..=number(last)?),
+                None => set.insert(number(part)?),
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(s: &str) -> RangeSet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_insert_merges_ranges() {
+        let mut numbers = RangeSet::new();
+        for n in [5, 1, 2, 3, 9, 7, 8, 4] {
+            numbers.insert(n);
+        }
+        assert_eq!(numbers.to_string(), "1-5,7-9");
+        assert_eq!(numbers.len(), 8);
+
+        numbers.insert_range(6..=6);
+        assert_eq!(numbers.to_string(), "1-9");
+        numbers.insert_range(20..=30);
+        numbers.insert_range(11..=12);
+        numbers.insert_range(25..=40);
+        assert_eq!(numbers.to_string(), "1-9,11-12,20-40");
+        numbers.insert_range(0..=35);
+        assert_eq!(numbers.to_string(), "0-40");
+        assert_eq!((numbers.first(), numbers.last()), (Some(0), Some(40)));
+    }
+
+    #[test]
+    fn test_sorted_listing_is_compact() {
+        let numbers: RangeSet = (1..=1_000_000).filter(|n| n % 1000 != 0).collect();
+        assert_eq!(numbers.ranges().count(), 1000);
+        assert_eq!(numbers.len(), 999_000);
+        assert!(numbers.contains(999));
+        assert!(!numbers.contains(1000));
+        assert!(!numbers.contains(0));
+        assert!(!numbers.contains(1_000_001));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set("1-10,20-30,40");
+        let b = set("5-25,40-50");
+        assert_eq!(a.union(&b).to_string(), "1-30,40-50");
+        assert_eq!(a.intersection(&b).to_string(), "5-10,20-25,40");
+        assert_eq!(a.difference(&b).to_string(), "1-4,26-30");
+        assert_eq!(b.difference(&a).to_string(), "11-19,41-50");
+
+        assert_eq!(a.difference(&a), RangeSet::new());
+        assert_eq!(a.union(&RangeSet::new()), a);
+        assert!(a.intersection(&set("11-19,31-39")).is_empty());
+        assert_eq!(
+            set("1-100").difference(&set("2,4,6-98")).to_string(),
+            "1,3,5,99-100"
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut numbers = set("1-5");
+        numbers.remove(3);
+        numbers.remove(9);
+        assert_eq!(numbers.to_string(), "1-2,4-5");
+    }
+
+    #[test]
+    fn test_iteration() {
+        let numbers = set("3-5,8");
+        assert_eq!(numbers.iter().collect::<Vec<_>>(), [3, 4, 5, 8]);
+        assert_eq!(
+            numbers.article_ranges().collect::<Vec<_>>(),
+            [ArticleRange::Between(3, 5), ArticleRange::Number(8)]
+        );
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(set("1-3, 5,7-9").to_string(), "1-3,5,7-9");
+        assert_eq!(set("").to_string(), "");
+        assert_eq!(set("9-3"), RangeSet::new());
+        assert!("1-x".parse::<RangeSet>().is_err());
+        assert!("-5".parse::<RangeSet>().is_err());
+    }
+
+    #[test]
+    fn test_extremes() {
+        let mut numbers = RangeSet::from(u64::MAX - 1..=u64::MAX);
+        numbers.insert(0);
+        assert_eq!(numbers.len(), 3);
+        assert_eq!(
+            numbers.difference(&set("0")).to_string(),
+            format!("{}-{}", u64::MAX - 1, u64::MAX)
+        );
+    }
+}
@@ -35,6 +35,10 @@ impl Article {
     // === Identifier Access ===
 
     /// Get the article's Message-ID (globally unique identifier).
+    ///
+    /// This is the id from the status line as sent by the server; it has not
+    /// been validated. Parse it with [`MessageId::new`](crate::MessageId::new)
+    /// before reusing it in a command.
     pub fn article_id(&self) -> &str {
         &self.message_id
     }
@@ -45,6 +49,9 @@ impl Article {
     }
 
     /// Get raw article content bytes.
+    ///
+    /// These are the exact bytes sent by the server, after dot-unstuffing and
+    /// without any charset conversion.
     pub fn raw_content(&self) -> &[u8] {
         &self.content
     }
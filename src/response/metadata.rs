@@ -5,6 +5,12 @@
 //! - [`OverviewEntry`] - Article metadata from OVER command
 //! - [`HeaderEntry`] - Header field data from HDR command
 
+use std::sync::Arc;
+
+use super::overview::{DecodedOverview, OverviewSchema};
+use crate::ident::MessageId;
+use crate::utils::decode_encoded_words;
+
 /// Newsgroup information
 #[derive(Debug, Clone, PartialEq)]
 pub struct NewsGroup {
@@ -19,10 +25,18 @@ pub struct NewsGroup {
 }
 
 /// Overview entry for OVER command response
-#[derive(Debug, Clone, PartialEq)]
+///
+/// The named accessors look fields up in `schema`. Entries decoded by
+/// [`Client`] use the default RFC 3977 format, while `NntpClient::over`
+/// attaches the format announced by the server.
+///
+/// [`Client`]: crate::Client
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct OverviewEntry {
     /// Raw tab-separated fields from the OVER response
     pub fields: Vec<String>,
+    /// Layout of the fields after the article number
+    pub schema: Arc<OverviewSchema>,
 }
 
 impl OverviewEntry {
@@ -36,8 +50,17 @@ impl OverviewEntry {
         self.fields.get(index).map(|s| s.as_str())
     }
 
+    /// Get field by header name or metadata item, e.g. `Subject`, `Xref`
+    /// or `:bytes`, according to the schema
+    ///
+    /// See [`OverviewSchema::get`].
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.schema.get(self, name)
+    }
+
     /// Get field by name (requires field format knowledge)
-    /// This is a helper that assumes the default RFC 3977 format
+    /// This is a helper that assumes the default RFC 3977 format; prefer
+    /// [`get`](Self::get), which follows the schema
     pub fn get_default_field(&self, field_name: &str) -> Option<&str> {
         let index = match field_name.to_lowercase().as_str() {
             "subject" => 1,
@@ -52,51 +75,83 @@ impl OverviewEntry {
         self.get_field(index)
     }
 
-    /// Get subject field (index 1 in default format)
+    /// Get subject field
     pub fn subject(&self) -> Option<&str> {
-        self.get_field(1)
+        self.get("Subject")
     }
 
-    /// Get from field (index 2 in default format)
+    /// Get from field
     pub fn from(&self) -> Option<&str> {
-        self.get_field(2)
+        self.get("From")
     }
 
-    /// Get date field (index 3 in default format)
+    /// Get subject field with RFC 2047 encoded words decoded
+    pub fn decoded_subject(&self) -> Option<String> {
+        self.subject().map(decode_encoded_words)
+    }
+
+    /// Get from field with RFC 2047 encoded words decoded
+    pub fn decoded_from(&self) -> Option<String> {
+        self.from().map(decode_encoded_words)
+    }
+
+    /// Get date field
     pub fn date(&self) -> Option<&str> {
-        self.get_field(3)
+        self.get("Date")
     }
 
-    /// Get message-id field (index 4 in default format)
+    /// Get message-id field
     pub fn message_id(&self) -> Option<&str> {
-        self.get_field(4)
+        self.get("Message-ID")
     }
 
-    /// Get references field (index 5 in default format)
+    /// Get references field
     pub fn references(&self) -> Option<&str> {
-        self.get_field(5)
+        self.get("References")
     }
 
-    /// Get byte count field (index 6 in default format)
+    /// Get byte count field (`:bytes`)
     pub fn byte_count(&self) -> Option<u64> {
-        self.get_field(6)?.parse().ok()
+        self.get(":bytes")?.parse().ok()
     }
 
-    /// Get line count field (index 7 in default format)
+    /// Get line count field (`:lines`)
     pub fn line_count(&self) -> Option<u64> {
-        self.get_field(7)?.parse().ok()
+        self.get(":lines")?.parse().ok()
+    }
+
+    /// Decode the fields into typed values, following the schema
+    pub fn decode(&self) -> DecodedOverview {
+        DecodedOverview::from(self)
     }
 }
 
 /// Header entry for HDR command response
 #[derive(Debug, Clone, PartialEq)]
 pub struct HeaderEntry {
-    /// Article number or message ID
+    /// Article number or message ID, as sent by the server
     pub article: String,
     /// Header field value
     pub value: String,
 }
 
+impl HeaderEntry {
+    /// Get the article number, if the entry names the article by number
+    pub fn number(&self) -> Option<u64> {
+        self.article.parse().ok()
+    }
+
+    /// Get the message-id, if the entry names the article by a valid one
+    pub fn message_id(&self) -> Option<MessageId> {
+        self.article.parse().ok()
+    }
+
+    /// Get the header value with RFC 2047 encoded words decoded
+    pub fn decoded_value(&self) -> String {
+        decode_encoded_words(&self.value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +183,7 @@ mod tests {
                 "1234".to_string(),
                 "42".to_string(),
             ],
+            ..Default::default()
         };
 
         assert_eq!(entry.number(), Some(3000));
@@ -158,5 +214,37 @@ mod tests {
         };
         assert_eq!(entry.article, "3000");
         assert_eq!(entry.value, "Test Subject");
+        assert_eq!(entry.number(), Some(3000));
+        assert_eq!(entry.message_id(), None);
+
+        let entry = HeaderEntry {
+            article: "<a@example.com>".to_string(),
+            value: "Test Subject".to_string(),
+        };
+        assert_eq!(entry.number(), None);
+        assert_eq!(entry.message_id().unwrap(), "<a@example.com>");
+    }
+
+    #[test]
+    fn test_decoded_accessors() {
+        let entry = HeaderEntry {
+            article: "3000".to_string(),
+            value: "Re: =?iso-8859-1?Q?Gr=FC=DFe_?= =?utf-8?B?YXVzIEvDtmxu?=".to_string(),
+        };
+        assert_eq!(entry.decoded_value(), "Re: Grüße aus Köln");
+
+        let entry = OverviewEntry {
+            fields: vec![
+                "3000".to_string(),
+                "=?utf-8?Q?=E2=82=AC_100?=".to_string(),
+                "=?iso-8859-1?Q?J=F6rg?= <joerg@example.com>".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(entry.decoded_subject().as_deref(), Some("€ 100"));
+        assert_eq!(
+            entry.decoded_from().as_deref(),
+            Some("Jörg <joerg@example.com>")
+        );
     }
 }
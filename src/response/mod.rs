@@ -6,22 +6,36 @@
 //!
 //! - [`article`] - Article-related types ([`Article`], [`Attachment`])
 //! - [`metadata`] - Newsgroup metadata types ([`NewsGroup`], [`OverviewEntry`], [`HeaderEntry`])
+//! - `overview` - Overview field layout and decoding ([`OverviewSchema`], [`DecodedOverview`])
 //! - [`wrappers`] - Newtype wrappers for type-safe response extraction
 
 mod article;
 mod metadata;
+mod overview;
 pub mod wrappers;
 
 #[allow(deprecated)]
 pub use article::ParsedArticle;
 pub use article::{Article, Attachment};
 pub use metadata::{HeaderEntry, NewsGroup, OverviewEntry};
+pub use overview::{DecodedOverview, Mailbox, OverviewField, OverviewSchema, Xref};
 pub use wrappers::*;
 
+use crate::codec;
+use crate::command::{Command, ListVariant};
 use crate::error::{Error, Result};
+use crate::range::RangeSet;
+use crate::timestamp::Timestamp;
 use mail_parser::{Message, MessageParser};
 
 /// NNTP server responses
+///
+/// Message-ids reported by the server are kept as the raw strings it sent.
+/// Servers carry ids of older articles that [`MessageId`](crate::MessageId)
+/// rejects, and one such id must not fail a whole reply. The typed wrappers
+/// such as [`ArticlePointer`] and [`HeaderEntry::message_id`] validate them
+/// with [`MessageId::new`](crate::MessageId::new), so an id is only reused
+/// in a command once it passed.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Response {
     /// Server capabilities response (101)
@@ -39,6 +53,14 @@ pub enum Response {
     /// Authentication required (381)
     AuthRequired,
 
+    /// SASL authentication successful, with additional data from the
+    /// server (283, RFC 4643)
+    SaslSuccess(Vec<u8>),
+
+    /// SASL challenge from the server, already base64-decoded (383,
+    /// RFC 4643)
+    SaslChallenge(Vec<u8>),
+
     /// Group selected successfully (211)
     GroupSelected {
         /// Estimated number of articles
@@ -51,8 +73,19 @@ pub enum Response {
         name: String,
     },
 
-    /// Article listing (211)
-    ArticleListing(Vec<u64>),
+    /// Article listing from LISTGROUP (211)
+    ArticleListing {
+        /// Estimated number of articles in the group
+        count: u64,
+        /// First article number
+        first: u64,
+        /// Last article number
+        last: u64,
+        /// Group name
+        name: String,
+        /// Article numbers in the requested range
+        articles: RangeSet,
+    },
 
     /// Article retrieved (220/221/222)
     Article {
@@ -93,6 +126,39 @@ pub enum Response {
     /// Article transferred successfully (235)
     ArticleTransferred,
 
+    /// Streaming is permitted after MODE STREAM (203, RFC 4644)
+    StreamingPermitted,
+
+    /// Article wanted in reply to CHECK, send it with TAKETHIS (238)
+    CheckWanted {
+        /// Message-ID of the article
+        message_id: String,
+    },
+
+    /// Article not wanted in reply to CHECK now, offer it again later (431)
+    CheckDeferred {
+        /// Message-ID of the article
+        message_id: String,
+    },
+
+    /// Article not wanted in reply to CHECK (438)
+    CheckNotWanted {
+        /// Message-ID of the article
+        message_id: String,
+    },
+
+    /// Article sent with TAKETHIS transferred successfully (239)
+    TakethisAccepted {
+        /// Message-ID of the article
+        message_id: String,
+    },
+
+    /// Article sent with TAKETHIS rejected, do not send it again (439)
+    TakethisRejected {
+        /// Message-ID of the article
+        message_id: String,
+    },
+
     /// Article posted successfully (240)
     PostSuccess,
 
@@ -102,8 +168,9 @@ pub enum Response {
     /// Help information (100)
     Help(Vec<String>),
 
-    /// Server date and time (111)
-    Date(String),
+    /// Server date and time (111); a reply with a malformed date is decoded
+    /// as [`Response::Success`]
+    Date(Timestamp),
 
     /// Header field data (225)
     HeaderData(Vec<HeaderEntry>),
@@ -114,6 +181,12 @@ pub enum Response {
     /// Overview format data (215)
     OverviewFormat(Vec<String>),
 
+    /// Newsgroup descriptions from XGTITLE (282)
+    NewsgroupDescriptions(Vec<NewsgroupDesc>),
+
+    /// Newsgroup creation times from LIST ACTIVE.TIMES (215)
+    ActiveTimes(Vec<ActiveTimeEntry>),
+
     /// Generic successful response
     Success {
         /// Response code
@@ -152,6 +225,14 @@ pub enum Response {
     /// [`Command::StartTls`]: crate::command::Command::StartTls
     TlsReady,
 
+    /// Compression is active (206 response, RFC 8054).
+    ///
+    /// All data after this reply is compressed in both directions, see
+    /// [`Command::Compress`].
+    ///
+    /// [`Command::Compress`]: crate::command::Command::Compress
+    CompressionActive,
+
     /// TLS temporarily unavailable (483 response).
     ///
     /// The server supports STARTTLS but cannot currently perform TLS negotiation.
@@ -173,7 +254,7 @@ pub enum Response {
 /// 4. ISO-8859-2 (Central European)
 ///
 /// If all fail, it falls back to lossy UTF-8 conversion.
-fn decode_text_with_encoding(data: &[u8]) -> String {
+pub(crate) fn decode_text_with_encoding(data: &[u8]) -> String {
     // First try UTF-8 since it's the most common nowadays
     if let Ok(text) = std::str::from_utf8(data) {
         return text.to_string();
@@ -205,44 +286,109 @@ impl Response {
     /// This method automatically detects and converts various text encodings
     /// to UTF-8, including UTF-8, Windows-1252, ISO-8859-15, and others.
     /// This ensures compatibility with NNTP servers that send responses in
-    /// different character encodings.
+    /// different character encodings. Only the status line and textual
+    /// lists are decoded; article content (220/221/222) is kept as the exact
+    /// bytes received, after dot-unstuffing.
+    ///
+    /// Without knowing the command that produced the reply, status codes that
+    /// are shared by several commands (211, 215) are told apart by the text of
+    /// the status line. Prefer [`Response::parse_for_command`] when the command
+    /// is known.
     pub fn parse(data: &[u8]) -> Result<Self> {
-        let response_text = decode_text_with_encoding(data);
-        Self::parse_str(&response_text)
+        Self::parse_bytes(data, None)
+    }
+
+    /// Parse the reply to a specific command from server bytes
+    ///
+    /// The command decides how ambiguous status codes are interpreted, e.g. a
+    /// 211 reply is an article listing after LISTGROUP and a group selection
+    /// after GROUP, and a 215 reply is an overview format after
+    /// LIST OVERVIEW.FMT and a newsgroup list after LIST ACTIVE.
+    pub fn parse_for_command(data: &[u8], command: &Command) -> Result<Self> {
+        Self::parse_bytes(data, Some(command))
     }
 
     /// Parse response from string
     pub fn parse_str(response: &str) -> Result<Self> {
-        let lines: Vec<&str> = response.lines().collect();
-        if lines.is_empty() {
+        Self::parse_bytes(response.as_bytes(), None)
+    }
+
+    fn parse_bytes(data: &[u8], command: Option<&Command>) -> Result<Self> {
+        let status_len = data
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(data.len(), |pos| pos + 1);
+        let (status_bytes, block) = data.split_at(status_len);
+
+        let status_line = decode_text_with_encoding(status_bytes);
+        let status_line = status_line.trim_end_matches(&['\r', '\n'][..]);
+        if status_line.is_empty() {
             return Err(Error::Parse("Empty response".to_string()));
         }
-
-        let status_line = lines[0];
         let (code, message) = parse_status_line(status_line)?;
 
+        // XZVER and XZHDR data is yEnc-encoded deflate data, unless the
+        // server chose to answer with plain data
+        if matches!(code, 221 | 224)
+            && matches!(command, Some(Command::Xzver { .. } | Command::Xzhdr { .. }))
+        {
+            #[cfg(feature = "compression")]
+            if let Some(data) = crate::compressed::decode_xz_block(block)? {
+                let text = decode_text_with_encoding(&data);
+                let lines: Vec<&str> = text.lines().collect();
+                return Self::parse_lines(code, message, &lines, command);
+            }
+            #[cfg(not(feature = "compression"))]
+            if codec::dot_unstuff(block).starts_with(b"=ybegin ") {
+                return Err(Error::InvalidResponse(
+                    "Compressed XZVER/XZHDR data requires the compression feature".to_string(),
+                ));
+            }
+        }
+
+        match code {
+            // Article content is binary-safe: no charset decoding, no line splitting
+            220..=222
+                if !matches!(
+                    command,
+                    Some(Command::Xhdr { .. } | Command::Xzhdr { .. } | Command::Xpat { .. })
+                ) =>
+            {
+                parse_article_response(&message, codec::dot_unstuff(block))
+            }
+            _ => {
+                let text = decode_text_with_encoding(block);
+                let lines: Vec<&str> = text.lines().collect();
+                Self::parse_lines(code, message, &lines, command)
+            }
+        }
+    }
+
+    /// Parse a reply whose data block (if any) is text
+    fn parse_lines(
+        code: u16,
+        message: String,
+        lines: &[&str],
+        command: Option<&Command>,
+    ) -> Result<Self> {
         match code {
             100 => {
                 // Help information
-                let help_lines = lines[1..]
-                    .iter()
-                    .take_while(|line| **line != ".")
-                    .map(|line| line.to_string())
-                    .collect();
+                let help_lines = data_lines(lines).map(|line| line.to_string()).collect();
                 Ok(Response::Help(help_lines))
             }
             101 => {
                 // Capabilities list
-                let capabilities = lines[1..]
-                    .iter()
-                    .take_while(|line| **line != ".")
-                    .map(|line| line.to_string())
-                    .collect();
+                let capabilities = data_lines(lines).map(|line| line.to_string()).collect();
                 Ok(Response::Capabilities(capabilities))
             }
             111 => {
-                // Server date
-                Ok(Response::Date(message))
+                // Server date; a malformed one is left for the conversion to
+                // ServerDate to report, so that decoding continues
+                Ok(match first_word(&message).parse() {
+                    Ok(date) => Response::Date(date),
+                    Err(_) => Response::Success { code, message },
+                })
             }
             200 => Ok(Response::ModeReader {
                 posting_allowed: true,
@@ -250,45 +396,53 @@ impl Response {
             201 => Ok(Response::ModeReader {
                 posting_allowed: false,
             }),
+            203 => Ok(Response::StreamingPermitted),
             205 => Ok(Response::Quit),
+            206 => Ok(Response::CompressionActive),
             211 => {
                 // Could be group selection or article listing
-                if message.contains("list follows") {
+                let is_listing = match command {
+                    Some(command) => matches!(command, Command::ListGroup { .. }),
+                    None => message.contains("list follows"),
+                };
+                if is_listing {
                     // Article listing
-                    let articles = lines[1..]
-                        .iter()
-                        .take_while(|line| **line != ".")
+                    let articles = data_lines(lines)
                         .filter_map(|line| line.parse::<u64>().ok())
                         .collect();
-                    Ok(Response::ArticleListing(articles))
+                    Ok(parse_listgroup_response(&message, articles, command))
                 } else {
                     // Group selection
                     parse_group_response(&message)
                 }
             }
             215 => {
-                // Could be newsgroup list or overview format
-                if message.to_lowercase().contains("overview") {
-                    // Overview format list
-                    let format_fields = lines[1..]
-                        .iter()
-                        .take_while(|line| **line != ".")
-                        .map(|line| line.to_string())
-                        .collect();
+                // Could be newsgroup list or a plain list of lines
+                let is_line_list = match command {
+                    Some(Command::List(variant)) => matches!(
+                        variant,
+                        ListVariant::OverviewFmt | ListVariant::Headers | ListVariant::Motd
+                    ),
+                    Some(_) => false,
+                    None => message.to_lowercase().contains("overview"),
+                };
+                if matches!(command, Some(Command::List(ListVariant::ActiveTimes))) {
+                    let times = data_lines(lines).filter_map(parse_active_time).collect();
+                    Ok(Response::ActiveTimes(times))
+                } else if is_line_list {
+                    // Overview format list, LIST HEADERS or LIST MOTD
+                    let format_fields = data_lines(lines).map(|line| line.to_string()).collect();
                     Ok(Response::OverviewFormat(format_fields))
                 } else {
                     // Newsgroup list
-                    let groups = lines[1..]
-                        .iter()
-                        .take_while(|line| **line != ".")
-                        .filter_map(|line| parse_newsgroup_line(line))
-                        .collect();
+                    let groups = data_lines(lines).filter_map(parse_newsgroup_line).collect();
                     Ok(Response::NewsgroupList(groups))
                 }
             }
-            220..=222 => {
-                // Article content
-                parse_article_response(code, &message, &lines[1..])
+            221 => {
+                // RFC 2980 XHDR replies with 221 followed by header data
+                let headers = data_lines(lines).filter_map(parse_header_entry).collect();
+                Ok(Response::HeaderData(headers))
             }
             223 => {
                 // Article status
@@ -296,48 +450,66 @@ impl Response {
             }
             224 => {
                 // Overview data
-                let overview = lines[1..]
-                    .iter()
-                    .take_while(|line| **line != ".")
-                    .filter_map(|line| parse_overview_entry(line))
-                    .collect();
+                let overview = data_lines(lines).filter_map(parse_overview_entry).collect();
                 Ok(Response::OverviewData(overview))
             }
             225 => {
                 // Header data
-                let headers = lines[1..]
-                    .iter()
-                    .take_while(|line| **line != ".")
-                    .filter_map(|line| parse_header_entry(line))
-                    .collect();
+                let headers = data_lines(lines).filter_map(parse_header_entry).collect();
                 Ok(Response::HeaderData(headers))
             }
             230 => {
                 // New articles
-                let articles = lines[1..]
-                    .iter()
-                    .take_while(|line| **line != ".")
-                    .map(|line| line.to_string())
-                    .collect();
+                let articles = data_lines(lines).map(|line| line.to_string()).collect();
                 Ok(Response::NewArticles(articles))
             }
             231 => {
                 // New newsgroups
-                let groups = lines[1..]
-                    .iter()
-                    .take_while(|line| **line != ".")
-                    .filter_map(|line| parse_newsgroup_line(line))
-                    .collect();
+                let groups = data_lines(lines).filter_map(parse_newsgroup_line).collect();
                 Ok(Response::NewNewsgroups(groups))
             }
             235 => Ok(Response::ArticleTransferred),
+            238 => Ok(Response::CheckWanted {
+                message_id: first_word(&message),
+            }),
+            239 => Ok(Response::TakethisAccepted {
+                message_id: first_word(&message),
+            }),
             240 => Ok(Response::PostSuccess),
             281 => Ok(Response::AuthSuccess),
+            283 => {
+                // The mechanism checks the additional data, so a malformed
+                // value is passed on as empty rather than failing here
+                let data = message.split_whitespace().next().unwrap_or_default();
+                Ok(Response::SaslSuccess(
+                    crate::sasl::decode_data(data).unwrap_or_default(),
+                ))
+            }
+            282 if matches!(command, Some(Command::Xgtitle(_))) => {
+                // XGTITLE newsgroup descriptions
+                let descriptions = data_lines(lines).filter_map(parse_newsgroup_desc).collect();
+                Ok(Response::NewsgroupDescriptions(descriptions))
+            }
             335 => Ok(Response::ArticleWanted),
             340 => Ok(Response::PostAccepted),
             381 => Ok(Response::AuthRequired),
             382 => Ok(Response::TlsReady),
+            383 => {
+                let challenge = message.split_whitespace().next().unwrap_or_default();
+                Ok(Response::SaslChallenge(crate::sasl::decode_data(
+                    challenge,
+                )?))
+            }
+            431 => Ok(Response::CheckDeferred {
+                message_id: first_word(&message),
+            }),
             435 | 436 => Ok(Response::ArticleNotWanted),
+            438 => Ok(Response::CheckNotWanted {
+                message_id: first_word(&message),
+            }),
+            439 => Ok(Response::TakethisRejected {
+                message_id: first_word(&message),
+            }),
             483 => Ok(Response::TlsNotAvailable { message }),
             // All 4xx and 5xx error codes use the unified Error variant
             400..=599 => Ok(Response::Error { code, message }),
@@ -474,6 +646,14 @@ impl Response {
     }
 }
 
+/// Lines of a multi-line data block up to the terminator, with dot-stuffing removed
+fn data_lines<'a>(lines: &'a [&'a str]) -> impl Iterator<Item = &'a str> + 'a {
+    lines
+        .iter()
+        .take_while(|line| !codec::is_terminator(line.as_bytes()))
+        .map(|line| codec::unstuff_line(*line))
+}
+
 fn parse_status_line(line: &str) -> Result<(u16, String)> {
     let parts: Vec<&str> = line.splitn(2, ' ').collect();
     if parts.len() < 2 {
@@ -488,6 +668,15 @@ fn parse_status_line(line: &str) -> Result<(u16, String)> {
     Ok((code, message))
 }
 
+/// The message-id at the start of a streaming reply (RFC 4644)
+fn first_word(message: &str) -> String {
+    message
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
 fn parse_group_response(message: &str) -> Result<Response> {
     let parts: Vec<&str> = message.split_whitespace().collect();
     if parts.len() < 4 {
@@ -513,7 +702,47 @@ fn parse_group_response(message: &str) -> Result<Response> {
     })
 }
 
-fn parse_article_response(_code: u16, message: &str, content_lines: &[&str]) -> Result<Response> {
+/// Build a LISTGROUP reply from its status line and article numbers.
+///
+/// RFC 2980 servers send no group statistics, so they are derived from the
+/// listed articles and the group named in the command instead.
+fn parse_listgroup_response(
+    message: &str,
+    articles: RangeSet,
+    command: Option<&Command>,
+) -> Response {
+    if let Ok(Response::GroupSelected {
+        count,
+        first,
+        last,
+        name,
+    }) = parse_group_response(message)
+    {
+        return Response::ArticleListing {
+            count,
+            first,
+            last,
+            name,
+            articles,
+        };
+    }
+
+    let name = match command {
+        Some(Command::ListGroup {
+            group: Some(group), ..
+        }) => group.to_string(),
+        _ => String::new(),
+    };
+    Response::ArticleListing {
+        count: articles.len(),
+        first: articles.first().unwrap_or(0),
+        last: articles.last().unwrap_or(0),
+        name,
+        articles,
+    }
+}
+
+fn parse_article_response(message: &str, content: Vec<u8>) -> Result<Response> {
     let parts: Vec<&str> = message.split_whitespace().collect();
     if parts.len() < 2 {
         return Err(Error::Parse(format!("Invalid article response: {message}")));
@@ -530,16 +759,6 @@ fn parse_article_response(_code: u16, message: &str, content_lines: &[&str]) ->
     };
     let message_id = parts[1].to_string();
 
-    // Collect content until terminator dot
-    let mut content = Vec::new();
-    for line in content_lines {
-        if *line == "." {
-            break;
-        }
-        content.extend_from_slice(line.as_bytes());
-        content.extend_from_slice(b"\r\n");
-    }
-
     Ok(Response::Article {
         number,
         message_id,
@@ -611,6 +830,33 @@ fn parse_newsgroup_line(line: &str) -> Option<NewsGroup> {
     None
 }
 
+/// Parse a "name description" line, separated by a tab or spaces
+fn parse_newsgroup_desc(line: &str) -> Option<NewsgroupDesc> {
+    let line = line.trim_end();
+    let (name, description) = line
+        .split_once(|c: char| c.is_whitespace())
+        .unwrap_or((line, ""));
+    if name.is_empty() {
+        return None;
+    }
+    Some(NewsgroupDesc {
+        name: name.to_string(),
+        description: description.trim_start().to_string(),
+    })
+}
+
+/// Parse a LIST ACTIVE.TIMES line: `name time creator`
+fn parse_active_time(line: &str) -> Option<ActiveTimeEntry> {
+    let mut parts = line.split_whitespace();
+    let name = parts.next()?;
+    let time = parts.next()?.parse::<i64>().ok()?;
+    Some(ActiveTimeEntry {
+        name: name.to_string(),
+        timestamp: Timestamp::from_unix(time),
+        creator: parts.next().unwrap_or_default().to_string(),
+    })
+}
+
 fn parse_header_entry(line: &str) -> Option<HeaderEntry> {
     let parts: Vec<&str> = line.splitn(2, ' ').collect();
     if parts.len() < 2 {
@@ -629,12 +875,16 @@ fn parse_overview_entry(line: &str) -> Option<OverviewEntry> {
         return None;
     }
 
-    Some(OverviewEntry { fields: parts })
+    Some(OverviewEntry {
+        fields: parts,
+        schema: OverviewSchema::shared_default(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ArticleRange;
 
     #[test]
     fn test_parse_capabilities() {
@@ -706,10 +956,23 @@ mod tests {
         let parsed = Response::parse_str(response).unwrap();
 
         if let Response::Date(date) = parsed {
-            assert_eq!(date, "20231106123456");
+            assert_eq!(date.to_utc(), (2023, 11, 6, 12, 34, 56));
         } else {
             panic!("Expected Date response");
         }
+
+        let parsed = Response::parse_str("111 2023-11-06").unwrap();
+        assert_eq!(
+            parsed,
+            Response::Success {
+                code: 111,
+                message: "2023-11-06".to_string()
+            }
+        );
+        assert!(matches!(
+            ServerDate::try_from(parsed),
+            Err(Error::InvalidResponse(_))
+        ));
     }
 
     #[test]
@@ -1143,13 +1406,262 @@ mod tests {
         let response = "211 list follows\r\n3000\r\n3001\r\n3002\r\n.\r\n";
         let parsed = Response::parse_str(response).unwrap();
 
-        if let Response::ArticleListing(articles) = parsed {
-            assert_eq!(articles, vec![3000, 3001, 3002]);
+        if let Response::ArticleListing {
+            count,
+            first,
+            last,
+            name,
+            articles,
+        } = parsed
+        {
+            assert_eq!(articles, RangeSet::from(3000..=3002));
+            // Derived from the listing when the status line has no stats
+            assert_eq!((count, first, last), (3, 3000, 3002));
+            assert_eq!(name, "");
         } else {
             panic!("Expected ArticleListing response");
         }
     }
 
+    #[test]
+    fn test_parse_for_command_listgroup() {
+        // RFC 3977 LISTGROUP reply carries group stats rather than "list follows"
+        let response = b"211 3 3000 3002 misc.test\r\n3000\r\n3001\r\n3002\r\n.\r\n";
+        let command = Command::ListGroup {
+            group: None,
+            range: None,
+        };
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        assert_eq!(
+            parsed,
+            Response::ArticleListing {
+                count: 3,
+                first: 3000,
+                last: 3002,
+                name: "misc.test".to_string(),
+                articles: RangeSet::from(3000..=3002),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_for_command_listgroup_without_stats() {
+        // RFC 2980 servers only announce the list
+        let response = b"211 list of article numbers follow\r\n7\r\n9\r\n.\r\n";
+        let command = Command::ListGroup {
+            group: Some("misc.test".parse().unwrap()),
+            range: None,
+        };
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        assert_eq!(
+            parsed,
+            Response::ArticleListing {
+                count: 2,
+                first: 7,
+                last: 9,
+                name: "misc.test".to_string(),
+                articles: [7, 9].into_iter().collect(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_for_command_group() {
+        let response = b"211 1234 3000 4234 misc.test\r\n";
+        let command = Command::Group("misc.test".parse().unwrap());
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        assert!(matches!(
+            parsed,
+            Response::GroupSelected { count: 1234, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_for_command_list_overview_fmt() {
+        let response = b"215 Order of fields follows\r\nSubject:\r\nFrom:\r\n.\r\n";
+        let command = Command::List(ListVariant::OverviewFmt);
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        assert_eq!(
+            parsed,
+            Response::OverviewFormat(vec!["Subject:".to_string(), "From:".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_for_command_list_active() {
+        // Status text mentioning "overview" must not change the interpretation
+        let response = b"215 overview of active groups\r\nmisc.test 3002 3000 y\r\n.\r\n";
+        let command = Command::List(ListVariant::Active(None));
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        if let Response::NewsgroupList(groups) = parsed {
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].name, "misc.test");
+            assert_eq!(groups[0].last, 3002);
+        } else {
+            panic!("Expected NewsgroupList response");
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_parse_for_command_xzver_and_xzhdr() {
+        let mut response = b"224 Compressed overview follows\r\n".to_vec();
+        response.extend_from_slice(&crate::compressed::encode_xz_block(
+            b"3000\tFirst\tposter@x\tDate\t<a@x>\t\t100\t5\r\n",
+        ));
+        let command = Command::Xzver {
+            range: Some(ArticleRange::Number(3000)),
+        };
+        match Response::parse_for_command(&response, &command).unwrap() {
+            Response::OverviewData(overview) => {
+                assert_eq!(overview.len(), 1);
+                assert_eq!(overview[0].fields[1], "First");
+            }
+            other => panic!("Expected OverviewData response, got {other:?}"),
+        }
+
+        let mut response = b"221 Compressed headers follow\r\n".to_vec();
+        response.extend_from_slice(&crate::compressed::encode_xz_block(
+            b"3000 First subject\r\n3001 Second\r\n",
+        ));
+        let command = Command::Xzhdr {
+            field: "Subject".to_string(),
+            range: Some(ArticleRange::Between(3000, 3001)),
+        };
+        assert!(matches!(
+            Response::parse_for_command(&response, &command).unwrap(),
+            Response::HeaderData(headers) if headers.len() == 2 && headers[1].value == "Second"
+        ));
+
+        // Plain data is accepted as well
+        let response = b"221 Headers follow\r\n3000 First subject\r\n.\r\n";
+        assert!(matches!(
+            Response::parse_for_command(response, &command).unwrap(),
+            Response::HeaderData(headers) if headers.len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_parse_for_command_xhdr() {
+        let response = b"221 Header follows\r\n3000 First subject\r\n3001 Second\r\n.\r\n";
+        let command = Command::Xhdr {
+            field: "Subject".to_string(),
+            range: Some(ArticleRange::Between(3000, 3001)),
+        };
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        if let Response::HeaderData(headers) = parsed {
+            assert_eq!(headers.len(), 2);
+            assert_eq!(headers[0].article, "3000");
+            assert_eq!(headers[0].value, "First subject");
+        } else {
+            panic!("Expected HeaderData response");
+        }
+    }
+
+    #[test]
+    fn test_parse_for_command_xpat() {
+        let response = b"221 Header follows\r\n3000 Rust 1.75 released\r\n.\r\n";
+        let command = Command::Xpat {
+            field: "Subject".to_string(),
+            range: ArticleRange::Between(3000, 3100),
+            patterns: vec!["*Rust*".to_string()],
+        };
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        if let Response::HeaderData(headers) = parsed {
+            assert_eq!(headers.len(), 1);
+            assert_eq!(headers[0].article, "3000");
+            assert_eq!(headers[0].value, "Rust 1.75 released");
+        } else {
+            panic!("Expected HeaderData response");
+        }
+    }
+
+    #[test]
+    fn test_parse_for_command_xgtitle() {
+        let response = b"282 list of groups and descriptions follows\r\n\
+            comp.lang.rust\tThe Rust programming language\r\n\
+            misc.test  Testing postings\r\n\
+            alt.empty\r\n.\r\n";
+        let parsed =
+            Response::parse_for_command(response, &Command::Xgtitle(Some("*".to_string())))
+                .unwrap();
+        let Response::NewsgroupDescriptions(descriptions) = parsed else {
+            panic!("Expected NewsgroupDescriptions response");
+        };
+        assert_eq!(descriptions.len(), 3);
+        assert_eq!(descriptions[0].name, "comp.lang.rust");
+        assert_eq!(descriptions[0].description, "The Rust programming language");
+        assert_eq!(descriptions[1].description, "Testing postings");
+        assert_eq!(descriptions[2].name, "alt.empty");
+        assert_eq!(descriptions[2].description, "");
+    }
+
+    #[test]
+    fn test_parse_for_command_list_active_times() {
+        let response = b"215 information follows\r\ncomp.lang.rust 1609459200 admin@example.com\r\nalt.test 1609545600\r\nbroken.group yesterday x\r\n.\r\n";
+        let command = Command::List(ListVariant::ActiveTimes);
+        let parsed = Response::parse_for_command(response, &command).unwrap();
+        assert_eq!(
+            parsed,
+            Response::ActiveTimes(vec![
+                ActiveTimeEntry {
+                    name: "comp.lang.rust".to_string(),
+                    timestamp: Timestamp::from_utc(2021, 1, 1, 0, 0, 0).unwrap(),
+                    creator: "admin@example.com".to_string(),
+                },
+                ActiveTimeEntry {
+                    name: "alt.test".to_string(),
+                    timestamp: Timestamp::from_unix(1_609_545_600),
+                    creator: String::new(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_article_content_is_8bit_clean() {
+        // Windows-1252/Latin-1 bytes, a bare CR and a bare LF must survive untouched
+        let body: &[u8] = b"Subject: caf\xe9\r\n\r\n=ybegin \x00\xff\rraw\nline\r\n";
+        let mut data = b"220 1 <bin@example.com>\r\n".to_vec();
+        data.extend_from_slice(body);
+        data.extend_from_slice(b".\r\n");
+
+        let parsed = Response::parse(&data).unwrap();
+        if let Response::Article { content, .. } = parsed {
+            assert_eq!(content, body);
+        } else {
+            panic!("Expected Article response");
+        }
+    }
+
+    #[test]
+    fn test_parse_article_content_is_dot_unstuffed() {
+        let data = b"222 1 <dots@example.com>\r\n..leading\r\n\xe9\r\n.\r\n";
+        let command = Command::Body(crate::ArticleSpec::Current);
+        let parsed = Response::parse_for_command(data, &command).unwrap();
+        if let Response::Article { content, .. } = parsed {
+            assert_eq!(content, b".leading\r\n\xe9\r\n");
+        } else {
+            panic!("Expected Article response");
+        }
+    }
+
+    #[test]
+    fn test_parse_article_status_line_is_decoded() {
+        // Non-UTF-8 bytes in the status line are decoded, not the content
+        let data = b"221 5 <x@example.com> caf\xe9\r\nFrom: \xe9\r\n.\r\n";
+        let parsed = Response::parse(data).unwrap();
+        if let Response::Article {
+            number, content, ..
+        } = parsed
+        {
+            assert_eq!(number, Some(5));
+            assert_eq!(content, b"From: \xe9\r\n");
+        } else {
+            panic!("Expected Article response");
+        }
+    }
+
     #[test]
     fn test_parse_article_with_number_zero() {
         let response = "220 0 <test@example.com>\r\nSubject: Test\r\n\r\nBody\r\n.\r\n";
@@ -1218,6 +1730,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_article_status_accepts_odd_message_id() {
+        for id in [
+            "<a>b@example.com>",
+            "no-brackets@example.com",
+            "<ünïcode@example.com>",
+        ] {
+            let parsed = Response::parse_str(&format!("223 3000 {id}")).unwrap();
+            match parsed {
+                Response::ArticleStatus { number, message_id } => {
+                    assert_eq!(number, 3000);
+                    assert_eq!(message_id, id);
+                }
+                other => panic!("Expected ArticleStatus response, got {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn test_parse_new_articles_response() {
         let response =
@@ -1268,6 +1798,31 @@ mod tests {
         assert_eq!(parsed, Response::AuthSuccess);
     }
 
+    #[test]
+    fn test_parse_compression_active_response() {
+        let parsed = Response::parse_str("206 Compression active").unwrap();
+        assert_eq!(parsed, Response::CompressionActive);
+    }
+
+    #[test]
+    fn test_parse_sasl_responses() {
+        let parsed = Response::parse_str("383 cj1meWtvK2QybGJiRmdPTlJ2OXFreGRhd0w=").unwrap();
+        assert_eq!(
+            parsed,
+            Response::SaslChallenge(b"r=fyko+d2lbbFgONRv9qkxdawL".to_vec())
+        );
+        assert_eq!(
+            Response::parse_str("383 =").unwrap(),
+            Response::SaslChallenge(Vec::new())
+        );
+        assert!(Response::parse_str("383 not*base64").is_err());
+
+        assert_eq!(
+            Response::parse_str("283 YWJj").unwrap(),
+            Response::SaslSuccess(b"abc".to_vec())
+        );
+    }
+
     #[test]
     fn test_parse_article_wanted_response() {
         let response = "335 Send article";
@@ -1303,6 +1858,35 @@ mod tests {
         assert_eq!(parsed, Response::ArticleNotWanted);
     }
 
+    #[test]
+    fn test_parse_streaming_responses() {
+        let id = || "<a@example.com>".to_string();
+        assert_eq!(
+            Response::parse_str("203 Streaming permitted").unwrap(),
+            Response::StreamingPermitted
+        );
+        assert_eq!(
+            Response::parse_str("238 <a@example.com>").unwrap(),
+            Response::CheckWanted { message_id: id() }
+        );
+        assert_eq!(
+            Response::parse_str("431 <a@example.com> try later").unwrap(),
+            Response::CheckDeferred { message_id: id() }
+        );
+        assert_eq!(
+            Response::parse_str("438 <a@example.com>").unwrap(),
+            Response::CheckNotWanted { message_id: id() }
+        );
+        assert_eq!(
+            Response::parse_str("239 <a@example.com>").unwrap(),
+            Response::TakethisAccepted { message_id: id() }
+        );
+        assert_eq!(
+            Response::parse_str("439 <a@example.com> duplicate").unwrap(),
+            Response::TakethisRejected { message_id: id() }
+        );
+    }
+
     #[test]
     fn test_parse_success_generic() {
         let response = "282 Some custom success";
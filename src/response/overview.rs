@@ -0,0 +1,487 @@
+//! Overview field layout from LIST OVERVIEW.FMT.
+//!
+//! The fields of an OVER reply follow the order given by LIST OVERVIEW.FMT
+//! (RFC 3977 Section 8.4). An [`OverviewSchema`] maps field names to their
+//! position, so that entries can be read by name on servers that reorder
+//! the fields or append extra ones such as `Xref:full`.
+//!
+//! [`DecodedOverview`] goes one step further and turns the fields of an
+//! entry into typed values.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use mail_parser::MessageParser;
+
+use super::wrappers::OverviewFormat;
+use super::OverviewEntry;
+use crate::utils::parse_references;
+use crate::{GroupName, MessageId, Timestamp};
+
+/// One field of the overview format.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OverviewField {
+    /// Header name without the colon, e.g. `Subject`, or a metadata item
+    /// with its leading colon, `:bytes` or `:lines`
+    pub name: String,
+    /// Whether values include the header name, as in `Xref: host group:1`
+    pub full: bool,
+}
+
+impl OverviewField {
+    /// Parse a line of LIST OVERVIEW.FMT.
+    ///
+    /// The RFC 2980 spellings `Bytes:` and `Lines:` are read as the
+    /// `:bytes` and `:lines` metadata items they stand for.
+    pub fn parse(line: &str) -> Self {
+        let line = line.trim();
+        let (line, full) = match line.len().checked_sub(":full".len()) {
+            Some(end)
+                if line.is_char_boundary(end) && line[end..].eq_ignore_ascii_case(":full") =>
+            {
+                (&line[..end], true)
+            }
+            _ => (line, false),
+        };
+        let name = line.strip_suffix(':').unwrap_or(line);
+        let name = if name.eq_ignore_ascii_case("bytes") || name.eq_ignore_ascii_case(":bytes") {
+            ":bytes".to_string()
+        } else if name.eq_ignore_ascii_case("lines") || name.eq_ignore_ascii_case(":lines") {
+            ":lines".to_string()
+        } else {
+            name.to_string()
+        };
+        OverviewField { name, full }
+    }
+
+    /// Check whether this field is a metadata item rather than a header
+    pub fn is_metadata(&self) -> bool {
+        self.name.starts_with(':')
+    }
+
+    /// Check whether a name refers to this field, ignoring case and
+    /// colons, so that `Xref`, `xref:` and `bytes` for `:bytes` all match.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim_start_matches(':').trim_end_matches(':');
+        self.name.trim_start_matches(':').eq_ignore_ascii_case(name)
+    }
+
+    /// Get a value of this field with the header name of a full field
+    /// removed.
+    fn strip<'a>(&self, value: &'a str) -> &'a str {
+        if !self.full {
+            return value;
+        }
+        match value.split_once(':') {
+            Some((name, rest)) if self.matches(name) => rest.trim_start(),
+            _ => value,
+        }
+    }
+}
+
+/// The layout of overview entries announced by a server.
+///
+/// Built from an [`OverviewFormat`]; the default is the format mandated by
+/// RFC 3977 Section 8.4.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::response::OverviewFormat;
+/// use nntp_rs::{OverviewEntry, OverviewSchema};
+/// use std::sync::Arc;
+///
+/// let format = OverviewFormat(
+///     ["Subject:", "From:", "Date:", "Message-ID:", "References:", "Bytes:", "Lines:", "Xref:full"]
+///         .map(String::from)
+///         .to_vec(),
+/// );
+/// let entry = OverviewEntry {
+///     fields: "7\tHello\ta@b\td\t<m@x>\t\t120\t4\tXref: host misc.test:7"
+///         .split('\t')
+///         .map(String::from)
+///         .collect(),
+///     schema: Arc::new(OverviewSchema::from(&format)),
+/// };
+/// assert_eq!(entry.get("xref"), Some("host misc.test:7"));
+/// assert_eq!(entry.byte_count(), Some(120));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OverviewSchema {
+    fields: Vec<OverviewField>,
+}
+
+impl OverviewSchema {
+    /// Get the fields in the order they follow the article number
+    pub fn fields(&self) -> &[OverviewField] {
+        &self.fields
+    }
+
+    /// Get the position of a field in [`OverviewEntry::fields`], where the
+    /// article number comes first.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field| field.matches(name))
+            .map(|i| i + 1)
+    }
+
+    /// Get the value of a field of an entry by name.
+    ///
+    /// Full fields are returned without their header name. Fields the
+    /// server left empty are returned as empty strings, fields missing from
+    /// the schema or the entry as `None`.
+    pub fn get<'a>(&self, entry: &'a OverviewEntry, name: &str) -> Option<&'a str> {
+        let i = self.index_of(name)?;
+        let value = entry.fields.get(i)?;
+        Some(self.fields[i - 1].strip(value))
+    }
+
+    /// Iterate over the fields of an entry with their values, without the
+    /// article number.
+    pub fn values<'a>(
+        &'a self,
+        entry: &'a OverviewEntry,
+    ) -> impl Iterator<Item = (&'a OverviewField, &'a str)> {
+        self.fields
+            .iter()
+            .zip(entry.fields.iter().skip(1))
+            .map(|(field, value)| (field, field.strip(value)))
+    }
+
+    /// Get a shared copy of the default schema.
+    pub(crate) fn shared_default() -> Arc<OverviewSchema> {
+        static DEFAULT: OnceLock<Arc<OverviewSchema>> = OnceLock::new();
+        DEFAULT.get_or_init(Arc::default).clone()
+    }
+}
+
+impl Default for OverviewSchema {
+    fn default() -> Self {
+        OverviewSchema::from(&OverviewFormat::default())
+    }
+}
+
+impl From<&OverviewFormat> for OverviewSchema {
+    fn from(format: &OverviewFormat) -> Self {
+        OverviewSchema {
+            fields: format
+                .iter()
+                .map(|line| OverviewField::parse(line))
+                .collect(),
+        }
+    }
+}
+
+/// The fields of an overview entry decoded into typed values.
+///
+/// Created by [`OverviewEntry::decode`]. Every field is `None` or empty if
+/// the schema lacks it or its value cannot be decoded, so that one malformed
+/// header does not hide the rest of the entry.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::OverviewEntry;
+///
+/// let entry = OverviewEntry {
+///     fields: [
+///         "3000",
+///         "=?utf-8?Q?Caf=C3=A9?= opening",
+///         "=?iso-8859-1?Q?J=F6rg?= <joerg@example.com>",
+///         "6 Oct 1998 04:38:40 -0500",
+///         "<b@example.com>",
+///         "<a@example.com>",
+///         "1234",
+///         "42",
+///     ]
+///     .map(String::from)
+///     .to_vec(),
+///     ..Default::default()
+/// };
+/// let overview = entry.decode();
+/// assert_eq!(overview.subject.as_deref(), Some("Café opening"));
+/// let from = overview.from.unwrap();
+/// assert_eq!(from.name.as_deref(), Some("Jörg"));
+/// assert_eq!(from.address.as_deref(), Some("joerg@example.com"));
+/// assert_eq!(overview.date.unwrap().to_string(), "19981006093840");
+/// assert_eq!(overview.references, ["<a@example.com>"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedOverview {
+    /// Article number
+    pub number: Option<u64>,
+    /// Subject with RFC 2047 encoded words decoded
+    pub subject: Option<String>,
+    /// Author from the From header, if it is not empty
+    pub from: Option<Mailbox>,
+    /// Date the article was posted
+    pub date: Option<Timestamp>,
+    /// Message-ID of the article
+    pub message_id: Option<MessageId>,
+    /// Message-IDs of the ancestors of the article, oldest first
+    pub references: Vec<MessageId>,
+    /// Size of the article in octets
+    pub bytes: Option<u64>,
+    /// Number of lines in the article body
+    pub lines: Option<u64>,
+    /// Groups the article was filed in, if the schema has an Xref field
+    pub xref: Option<Xref>,
+}
+
+impl From<&OverviewEntry> for DecodedOverview {
+    fn from(entry: &OverviewEntry) -> Self {
+        DecodedOverview {
+            number: entry.number(),
+            subject: entry.decoded_subject(),
+            from: entry
+                .from()
+                .map(Mailbox::parse)
+                .filter(|mailbox| mailbox.name.is_some() || mailbox.address.is_some()),
+            date: entry
+                .date()
+                .and_then(|date| Timestamp::from_date_header(date).ok()),
+            message_id: entry.message_id().and_then(|id| id.trim().parse().ok()),
+            references: parse_references(entry.references()),
+            bytes: entry.byte_count(),
+            lines: entry.line_count(),
+            xref: entry.get("Xref").map(Xref::parse),
+        }
+    }
+}
+
+/// A mailbox from a From header: a display name and an address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mailbox {
+    /// Display name with RFC 2047 encoded words decoded
+    pub name: Option<String>,
+    /// Address such as `user@example.com`
+    pub address: Option<String>,
+}
+
+impl Mailbox {
+    /// Parse the first mailbox of a header value.
+    ///
+    /// Both `Name <user@example.com>` and the older
+    /// `user@example.com (Name)` form are understood. A value that is not
+    /// an address is kept as the name.
+    pub fn parse(value: &str) -> Self {
+        let header = format!("From: {value}\r\n\r\n");
+        let message = MessageParser::default().parse_headers(header.as_bytes());
+        let mailbox = message
+            .as_ref()
+            .and_then(|message| message.from()?.first())
+            .map(|addr| Mailbox {
+                name: addr.name().map(str::to_string),
+                address: addr.address().map(str::to_string),
+            });
+        match mailbox {
+            Some(mailbox) if mailbox.name.is_some() || mailbox.address.is_some() => mailbox,
+            _ => Mailbox {
+                name: Some(value.trim().to_string()).filter(|name| !name.is_empty()),
+                address: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, &self.address) {
+            (Some(name), Some(address)) => write!(f, "{name} <{address}>"),
+            (Some(name), None) => f.write_str(name),
+            (None, Some(address)) => f.write_str(address),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// The value of an Xref header: where a server filed an article.
+///
+/// Written as `host group:number ...`, e.g.
+/// `news.example.com misc.test:3000 alt.test:42`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Xref {
+    /// Name of the server that assigned the numbers
+    pub server: Option<String>,
+    /// Article number in each group
+    pub articles: BTreeMap<GroupName, u64>,
+}
+
+impl Xref {
+    /// Parse an Xref value, skipping malformed entries.
+    pub fn parse(value: &str) -> Self {
+        let mut xref = Xref::default();
+        for (i, token) in value.split_whitespace().enumerate() {
+            match token.rsplit_once(':') {
+                Some((group, number)) => {
+                    if let (Ok(group), Ok(number)) = (group.parse(), number.parse()) {
+                        xref.articles.insert(group, number);
+                    }
+                }
+                None if i == 0 => xref.server = Some(token.to_string()),
+                None => {}
+            }
+        }
+        xref
+    }
+
+    /// Get the article number in a group
+    pub fn get(&self, group: &str) -> Option<u64> {
+        self.articles.get(group).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: &str, schema: OverviewSchema) -> OverviewEntry {
+        OverviewEntry {
+            fields: line.split('\t').map(String::from).collect(),
+            schema: Arc::new(schema),
+        }
+    }
+
+    fn schema(lines: &[&str]) -> OverviewSchema {
+        OverviewSchema::from(&OverviewFormat(
+            lines.iter().map(|line| line.to_string()).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_parse_field_spellings() {
+        let field = |line| OverviewField::parse(line);
+        assert_eq!(field("Subject:").name, "Subject");
+        assert_eq!(field(":bytes").name, ":bytes");
+        assert_eq!(field("Bytes:").name, ":bytes");
+        assert_eq!(field("Lines:").name, ":lines");
+        assert!(field(":lines").is_metadata());
+        assert!(!field("Subject:").is_metadata());
+
+        let xref = field("Xref:full");
+        assert_eq!(xref.name, "Xref");
+        assert!(xref.full);
+        assert!(field("XREF:FULL").full);
+        assert!(!field("Xref:").full);
+    }
+
+    #[test]
+    fn test_default_schema() {
+        let schema = OverviewSchema::default();
+        assert_eq!(schema.fields().len(), 7);
+        assert_eq!(schema.index_of("subject"), Some(1));
+        assert_eq!(schema.index_of("Message-ID:"), Some(4));
+        assert_eq!(schema.index_of("Bytes"), Some(6));
+        assert_eq!(schema.index_of(":lines"), Some(7));
+        assert_eq!(schema.index_of("Xref"), None);
+    }
+
+    #[test]
+    fn test_reordered_format_with_full_fields() {
+        let entry = entry(
+            "42\tFrom: a@example.com\tHello\t77\tXref: host misc.test:42 alt.test:9\t",
+            schema(&["From:full", "Subject:", ":lines", "Xref:full", "Keywords:"]),
+        );
+        assert_eq!(entry.get("From"), Some("a@example.com"));
+        assert_eq!(entry.subject(), Some("Hello"));
+        assert_eq!(entry.line_count(), Some(77));
+        assert_eq!(entry.get("xref"), Some("host misc.test:42 alt.test:9"));
+        assert_eq!(entry.get("Keywords"), Some(""));
+        assert_eq!(entry.byte_count(), None);
+        assert_eq!(entry.get("Date"), None);
+
+        let names: Vec<_> = entry
+            .schema
+            .values(&entry)
+            .map(|(field, value)| (field.name.as_str(), value))
+            .collect();
+        assert_eq!(names[0], ("From", "a@example.com"));
+        assert_eq!(names.len(), 5);
+    }
+
+    #[test]
+    fn test_full_field_without_name() {
+        // Some servers omit the name of an empty full field
+        let empty = entry("1\t", schema(&["Xref:full"]));
+        assert_eq!(empty.get("Xref"), Some(""));
+
+        let other = entry("1\tOther: value", schema(&["Xref:full"]));
+        assert_eq!(other.get("Xref"), Some("Other: value"));
+    }
+
+    #[test]
+    fn test_decode_entry() {
+        let entry = entry(
+            "12\t=?utf-8?B?w6lsw6h2ZQ==?= =?iso-8859-1?Q?_caf=E9?=\tjoe@example.com (Joe Bloggs)\t\
+             Tuesday, 06-Oct-98 04:38:40 EST\t <m@x> \t<a@x> broken <b@x>\t2048\t40\t\
+             Xref: news.example.com misc.test:7 bad alt.test:x comp.lang.rust:1234",
+            schema(&[
+                "Subject:",
+                "From:",
+                "Date:",
+                "Message-ID:",
+                "References:",
+                ":bytes",
+                ":lines",
+                "Xref:full",
+            ]),
+        );
+        let overview = entry.decode();
+        assert_eq!(overview.number, Some(12));
+        assert_eq!(overview.subject.as_deref(), Some("élève café"));
+        assert_eq!(
+            overview.from,
+            Some(Mailbox {
+                name: Some("Joe Bloggs".to_string()),
+                address: Some("joe@example.com".to_string()),
+            })
+        );
+        assert_eq!(overview.date.unwrap().to_string(), "19981006093840");
+        assert_eq!(overview.message_id.unwrap(), "<m@x>");
+        assert_eq!(overview.references, ["<a@x>", "<b@x>"]);
+        assert_eq!((overview.bytes, overview.lines), (Some(2048), Some(40)));
+
+        let xref = overview.xref.unwrap();
+        assert_eq!(xref.server.as_deref(), Some("news.example.com"));
+        assert_eq!(xref.articles.len(), 2);
+        assert_eq!(xref.get("misc.test"), Some(7));
+        assert_eq!(xref.get("comp.lang.rust"), Some(1234));
+        assert_eq!(xref.get("alt.test"), None);
+    }
+
+    #[test]
+    fn test_decode_malformed_entry() {
+        let overview = entry(
+            "x\tPlain\t\tlast Tuesday\tnot-an-id",
+            OverviewSchema::default(),
+        )
+        .decode();
+        assert_eq!(overview.number, None);
+        assert_eq!(overview.subject.as_deref(), Some("Plain"));
+        assert_eq!(overview.from, None);
+        assert_eq!(overview.date, None);
+        assert_eq!(overview.message_id, None);
+        assert!(overview.references.is_empty());
+        assert_eq!(overview.xref, None);
+    }
+
+    #[test]
+    fn test_mailbox_forms() {
+        let mailbox = Mailbox::parse("\"Doe, Jane\" <jane@example.com>");
+        assert_eq!(mailbox.name.as_deref(), Some("Doe, Jane"));
+        assert_eq!(mailbox.to_string(), "Doe, Jane <jane@example.com>");
+
+        let mailbox = Mailbox::parse("jane@example.com");
+        assert_eq!(mailbox.name, None);
+        assert_eq!(mailbox.to_string(), "jane@example.com");
+    }
+
+    #[test]
+    fn test_short_entry() {
+        let entry = entry("5\tSubject only", OverviewSchema::default());
+        assert_eq!(entry.subject(), Some("Subject only"));
+        assert_eq!(entry.from(), None);
+    }
+}
@@ -7,32 +7,217 @@
 //! These types are used as return types for `TryFrom<Response>` conversions,
 //! allowing type-safe extraction of specific response data.
 
+use std::collections::HashMap;
 use std::ops::Deref;
 
 use super::{Article, HeaderEntry, NewsGroup, OverviewEntry, Response};
-use crate::Error;
+use crate::{Error, MessageId, RangeSet, Timestamp, Wildmat};
 
 /// Server capabilities list.
 ///
 /// Wraps the list of capability strings returned by the CAPABILITIES command (101 response).
 /// Each string represents a capability the server supports (e.g., "VERSION 2", "READER", "POST").
 ///
+/// Each line consists of a capability label followed by optional arguments
+/// (RFC 3977 Section 3.3.2). The lines are parsed once on construction; labels
+/// and keyword arguments are compared case-insensitively. The raw lines stay
+/// available through [`Deref`].
+///
 /// # Example
 ///
-/// ```ignore
-/// let caps: Capabilities = response.try_into()?;
-/// for capability in caps.iter() {
-///     println!("Server supports: {}", capability);
-/// }
+/// ```
+/// use nntp_rs::response::Capabilities;
+///
+/// let caps = Capabilities::new(vec![
+///     "VERSION 2".to_string(),
+///     "READER".to_string(),
+///     "OVER MSGID".to_string(),
+///     "SASL PLAIN SCRAM-SHA-256".to_string(),
+/// ]);
+/// assert_eq!(caps.versions(), [2]);
+/// assert!(caps.is_reader());
+/// assert!(caps.supports_over_msgid());
+/// assert_eq!(caps.sasl_mechanisms(), ["PLAIN", "SCRAM-SHA-256"]);
+/// assert_eq!(caps.get("XSECRET"), None);
 /// ```
 #[derive(Debug, Clone, PartialEq)]
-pub struct Capabilities(pub Vec<String>);
+pub struct Capabilities {
+    /// Capability lines as sent by the server
+    lines: Vec<String>,
+    /// Arguments by upper-cased label
+    arguments: HashMap<String, Vec<String>>,
+    /// Free-form text of the IMPLEMENTATION line
+    implementation: Option<String>,
+}
+
+impl Capabilities {
+    /// Parse the capability lines of a CAPABILITIES reply.
+    ///
+    /// When a label is listed more than once, the first line wins.
+    pub fn new(lines: Vec<String>) -> Self {
+        let mut arguments = HashMap::new();
+        let mut implementation = None;
+        for line in &lines {
+            let mut words = line.split_whitespace();
+            let Some(label) = words.next() else {
+                continue;
+            };
+            let label = label.to_ascii_uppercase();
+            if label == "IMPLEMENTATION" && implementation.is_none() {
+                implementation = line
+                    .split_once(char::is_whitespace)
+                    .map(|(_, rest)| rest.trim().to_string());
+            }
+            arguments
+                .entry(label)
+                .or_insert_with(|| words.map(str::to_string).collect());
+        }
+        Self {
+            lines,
+            arguments,
+            implementation,
+        }
+    }
+}
+
+impl From<Vec<String>> for Capabilities {
+    fn from(lines: Vec<String>) -> Self {
+        Self::new(lines)
+    }
+}
 
 impl Deref for Capabilities {
     type Target = Vec<String>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.lines
+    }
+}
+
+impl Capabilities {
+    /// Look up a capability by label.
+    ///
+    /// Returns the arguments of the capability, or `None` if the server does
+    /// not advertise it.
+    pub fn get(&self, label: &str) -> Option<&[String]> {
+        self.arguments
+            .get(&label.to_ascii_uppercase())
+            .map(Vec::as_slice)
+    }
+
+    /// Check if the server advertises a capability.
+    pub fn has(&self, label: &str) -> bool {
+        self.get(label).is_some()
+    }
+
+    /// Check if a capability is advertised with the given argument.
+    pub fn has_argument(&self, label: &str, argument: &str) -> bool {
+        self.get(label)
+            .is_some_and(|args| args.iter().any(|arg| arg.eq_ignore_ascii_case(argument)))
+    }
+
+    /// Arguments of a capability, or none if it is not advertised
+    fn arguments_of(&self, label: &str) -> &[String] {
+        self.get(label).unwrap_or_default()
+    }
+
+    /// Protocol versions supported by the server (VERSION)
+    pub fn versions(&self) -> Vec<u32> {
+        self.arguments_of("VERSION")
+            .iter()
+            .filter_map(|version| version.parse().ok())
+            .collect()
+    }
+
+    /// Server implementation description (IMPLEMENTATION)
+    pub fn implementation(&self) -> Option<&str> {
+        self.implementation.as_deref()
+    }
+
+    /// Check if the reader commands are available (READER)
+    pub fn is_reader(&self) -> bool {
+        self.has("READER")
+    }
+
+    /// Check if articles can be offered with IHAVE (IHAVE)
+    pub fn is_ihave(&self) -> bool {
+        self.has("IHAVE")
+    }
+
+    /// Check if articles can be posted (POST)
+    pub fn is_post(&self) -> bool {
+        self.has("POST")
+    }
+
+    /// Check if the streaming feed commands are available (STREAMING, RFC 4644)
+    pub fn is_streaming(&self) -> bool {
+        self.has("STREAMING")
+    }
+
+    /// Check if MODE READER is needed to switch to reader mode (MODE-READER)
+    pub fn has_mode_reader(&self) -> bool {
+        self.has("MODE-READER")
+    }
+
+    /// Check if the connection can be upgraded to TLS (STARTTLS, RFC 4642)
+    pub fn has_starttls(&self) -> bool {
+        self.has("STARTTLS")
+    }
+
+    /// LIST keywords supported by the server (LIST)
+    pub fn list_variants(&self) -> &[String] {
+        self.arguments_of("LIST")
+    }
+
+    /// Check if a LIST keyword such as "OVERVIEW.FMT" is supported
+    pub fn supports_list(&self, keyword: &str) -> bool {
+        self.has_argument("LIST", keyword)
+    }
+
+    /// Check if the HDR command is available (HDR)
+    pub fn supports_hdr(&self) -> bool {
+        self.has("HDR")
+    }
+
+    /// Check if the OVER command is available (OVER)
+    pub fn supports_over(&self) -> bool {
+        self.has("OVER")
+    }
+
+    /// Check if OVER accepts a message-id argument (OVER MSGID)
+    pub fn supports_over_msgid(&self) -> bool {
+        self.has_argument("OVER", "MSGID")
+    }
+
+    /// AUTHINFO variants offered by the server, e.g. "USER" and "SASL" (AUTHINFO, RFC 4643)
+    pub fn authinfo_methods(&self) -> &[String] {
+        self.arguments_of("AUTHINFO")
+    }
+
+    /// SASL mechanisms offered by the server (SASL, RFC 4643)
+    pub fn sasl_mechanisms(&self) -> &[String] {
+        self.arguments_of("SASL")
+    }
+
+    /// Check if compressed reply data blocks can be enabled with
+    /// XFEATURE COMPRESS GZIP (XFEATURE-COMPRESS GZIP)
+    pub fn supports_xfeature_gzip(&self) -> bool {
+        self.has_argument("XFEATURE-COMPRESS", "GZIP")
+    }
+
+    /// Check if the compressed overview command is available (XZVER)
+    pub fn supports_xzver(&self) -> bool {
+        self.has("XZVER")
+    }
+
+    /// Check if the compressed header command is available (XZHDR)
+    pub fn supports_xzhdr(&self) -> bool {
+        self.has("XZHDR")
+    }
+
+    /// Compression algorithms offered by the server (COMPRESS, RFC 8054)
+    pub fn compression_algorithms(&self) -> &[String] {
+        self.arguments_of("COMPRESS")
     }
 }
 
@@ -41,7 +226,7 @@ impl TryFrom<Response> for Capabilities {
 
     fn try_from(response: Response) -> Result<Self, Self::Error> {
         match response {
-            Response::Capabilities(caps) => Ok(Capabilities(caps)),
+            Response::Capabilities(caps) => Ok(Capabilities::new(caps)),
             _ => Err(Error::InvalidResponse(
                 "Expected capabilities response".to_string(),
             )),
@@ -100,6 +285,16 @@ impl TryFrom<Response> for HelpText {
 #[derive(Debug, Clone, PartialEq)]
 pub struct NewsgroupList(pub Vec<NewsGroup>);
 
+impl NewsgroupList {
+    /// Iterate over the groups whose names match a wildmat.
+    ///
+    /// Useful to filter a full LIST ACTIVE locally instead of asking the
+    /// server again with a different wildmat.
+    pub fn matching<'a>(&'a self, wildmat: &'a Wildmat) -> impl Iterator<Item = &'a NewsGroup> {
+        self.0.iter().filter(|group| wildmat.matches(&group.name))
+    }
+}
+
 impl Deref for NewsgroupList {
     type Target = Vec<NewsGroup>;
 
@@ -160,28 +355,51 @@ impl TryFrom<Response> for MessageIdList {
     }
 }
 
-/// List of article numbers.
+/// List of article numbers with the statistics of their group.
 ///
-/// Wraps a list of article numbers returned by the LISTGROUP command (211 response with listing).
-/// Each number is a valid article number within the currently selected group.
+/// Returned by the LISTGROUP command (211 response with listing). Each number
+/// is a valid article number within the listed group. Dereferences to the
+/// [`RangeSet`] of numbers.
 ///
 /// # Example
 ///
 /// ```ignore
 /// let articles: ArticleNumbers = response.try_into()?;
-/// println!("Group has {} articles", articles.len());
+/// println!("{} has about {} articles", articles.name, articles.count);
 /// for num in articles.iter() {
 ///     println!("Article: {}", num);
 /// }
 /// ```
 #[derive(Debug, Clone, PartialEq)]
-pub struct ArticleNumbers(pub Vec<u64>);
+pub struct ArticleNumbers {
+    /// Estimated number of articles in the group.
+    pub count: u64,
+    /// First (lowest) article number in the group.
+    pub first: u64,
+    /// Last (highest) article number in the group.
+    pub last: u64,
+    /// Name of the group.
+    pub name: String,
+    /// Article numbers in the requested range.
+    pub numbers: RangeSet,
+}
+
+impl ArticleNumbers {
+    /// Get the statistics of the listed group.
+    pub fn stats(&self) -> GroupStats {
+        GroupStats {
+            count: self.count,
+            first: self.first,
+            last: self.last,
+        }
+    }
+}
 
 impl Deref for ArticleNumbers {
-    type Target = Vec<u64>;
+    type Target = RangeSet;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.numbers
     }
 }
 
@@ -190,7 +408,19 @@ impl TryFrom<Response> for ArticleNumbers {
 
     fn try_from(response: Response) -> Result<Self, Self::Error> {
         match response {
-            Response::ArticleListing(numbers) => Ok(ArticleNumbers(numbers)),
+            Response::ArticleListing {
+                count,
+                first,
+                last,
+                name,
+                articles,
+            } => Ok(ArticleNumbers {
+                count,
+                first,
+                last,
+                name,
+                numbers: articles,
+            }),
             _ => Err(Error::InvalidResponse(
                 "Expected article listing response".to_string(),
             )),
@@ -277,22 +507,35 @@ impl TryFrom<Response> for PostingStatus {
     }
 }
 
-/// Server date/time string.
+/// Server date and time.
 ///
-/// Wraps the date/time string returned by the DATE command (111 response).
-/// The format is typically "YYYYMMDDhhmmss" in UTC.
+/// Wraps the UTC time returned by the DATE command (111 response).
+/// Dereferences to the [`Timestamp`].
 ///
 /// # Example
 ///
 /// ```ignore
 /// let date: ServerDate = response.try_into()?;
 /// println!("Server time: {}", *date);
+/// println!("Server clock is {}s ahead", date.clock_skew(Timestamp::now()));
 /// ```
-#[derive(Debug, Clone, PartialEq)]
-pub struct ServerDate(pub String);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerDate(pub Timestamp);
+
+impl ServerDate {
+    /// Get how many seconds the server clock is ahead of a local time,
+    /// negative if it is behind.
+    ///
+    /// Pass the local time at which the reply was received, or better the
+    /// middle of the round trip. Adding the skew to a local time gives the
+    /// matching server time, e.g. for the next NEWNEWS poll.
+    pub fn clock_skew(&self, local: Timestamp) -> i64 {
+        self.0.offset_from(local)
+    }
+}
 
 impl Deref for ServerDate {
-    type Target = String;
+    type Target = Timestamp;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -305,6 +548,9 @@ impl TryFrom<Response> for ServerDate {
     fn try_from(response: Response) -> Result<Self, Self::Error> {
         match response {
             Response::Date(date) => Ok(ServerDate(date)),
+            Response::Success { code: 111, message } => Err(Error::InvalidResponse(format!(
+                "Invalid server date: {message}"
+            ))),
             _ => Err(Error::InvalidResponse("Expected date response".to_string())),
         }
     }
@@ -318,20 +564,23 @@ impl TryFrom<Response> for ServerDate {
 /// # Fields
 ///
 /// * `number` - Article number within the current group
-/// * `message_id` - Globally unique message ID
+/// * `message_id` - Globally unique message ID, or `None` if the server
+///   reported one that is not valid
 ///
 /// # Example
 ///
 /// ```ignore
 /// let pointer: ArticlePointer = response.try_into()?;
-/// println!("Article {} has ID {}", pointer.number, pointer.message_id);
+/// if let Some(message_id) = &pointer.message_id {
+///     println!("Article {} has ID {}", pointer.number, message_id);
+/// }
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct ArticlePointer {
     /// Article number within the current group.
     pub number: u64,
-    /// Message ID (globally unique identifier).
-    pub message_id: String,
+    /// Message ID (globally unique identifier), if the server sent a valid one.
+    pub message_id: Option<MessageId>,
 }
 
 impl TryFrom<Response> for ArticlePointer {
@@ -339,9 +588,10 @@ impl TryFrom<Response> for ArticlePointer {
 
     fn try_from(response: Response) -> Result<Self, Self::Error> {
         match response {
-            Response::ArticleStatus { number, message_id } => {
-                Ok(ArticlePointer { number, message_id })
-            }
+            Response::ArticleStatus { number, message_id } => Ok(ArticlePointer {
+                number,
+                message_id: MessageId::new(message_id).ok(),
+            }),
             _ => Err(Error::InvalidResponse(
                 "Expected article status response".to_string(),
             )),
@@ -449,6 +699,27 @@ impl Deref for OverviewFormat {
     }
 }
 
+impl Default for OverviewFormat {
+    /// The overview format mandated by RFC 3977 Section 8.4, used by servers
+    /// that do not support LIST OVERVIEW.FMT.
+    fn default() -> Self {
+        OverviewFormat(
+            [
+                "Subject:",
+                "From:",
+                "Date:",
+                "Message-ID:",
+                "References:",
+                ":bytes",
+                ":lines",
+            ]
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+        )
+    }
+}
+
 impl TryFrom<Response> for OverviewFormat {
     type Error = Error;
 
@@ -471,7 +742,7 @@ impl TryFrom<Response> for OverviewFormat {
 ///
 /// The wire format is: `groupname timestamp creator`
 /// - `groupname` - The name of the newsgroup
-/// - `timestamp` - Unix timestamp (seconds since epoch) when the group was created
+/// - `timestamp` - Seconds since the Unix epoch when the group was created
 /// - `creator` - Email address or identifier of who created the group
 ///
 /// # Example
@@ -479,7 +750,7 @@ impl TryFrom<Response> for OverviewFormat {
 /// ```ignore
 /// let entry = ActiveTimeEntry {
 ///     name: "comp.lang.rust".to_string(),
-///     timestamp: 1609459200,
+///     timestamp: Timestamp::from_unix(1609459200),
 ///     creator: "admin@example.com".to_string(),
 /// };
 /// ```
@@ -487,8 +758,8 @@ impl TryFrom<Response> for OverviewFormat {
 pub struct ActiveTimeEntry {
     /// Newsgroup name.
     pub name: String,
-    /// Unix timestamp when the group was created.
-    pub timestamp: u64,
+    /// Time when the group was created.
+    pub timestamp: Timestamp,
     /// Email address or identifier of the creator.
     pub creator: String,
 }
@@ -517,6 +788,19 @@ impl Deref for ActiveTimesList {
     }
 }
 
+impl TryFrom<Response> for ActiveTimesList {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        match response {
+            Response::ActiveTimes(times) => Ok(ActiveTimesList(times)),
+            _ => Err(Error::InvalidResponse(
+                "Expected active times response".to_string(),
+            )),
+        }
+    }
+}
+
 /// Distribution pattern entry.
 ///
 /// Represents a single entry from LIST DISTRIB.PATS response (RFC 3977 Section 7.6.5).
@@ -534,7 +818,7 @@ impl Deref for ActiveTimesList {
 /// ```ignore
 /// let pat = DistribPat {
 ///     weight: 10,
-///     wildmat: "comp.*".to_string(),
+///     wildmat: "comp.*".parse()?,
 ///     distribution: "world".to_string(),
 /// };
 /// ```
@@ -543,7 +827,7 @@ pub struct DistribPat {
     /// Weight for pattern priority (higher = more specific).
     pub weight: u32,
     /// Wildcard pattern matching newsgroup names.
-    pub wildmat: String,
+    pub wildmat: Wildmat,
     /// Default distribution value.
     pub distribution: String,
 }
@@ -564,6 +848,29 @@ pub struct DistribPat {
 #[derive(Debug, Clone, PartialEq)]
 pub struct DistribPatsList(pub Vec<DistribPat>);
 
+impl DistribPat {
+    /// Check whether a newsgroup matches this entry's wildmat.
+    pub fn matches(&self, group: &str) -> bool {
+        self.wildmat.matches(group)
+    }
+}
+
+impl DistribPatsList {
+    /// Get the default distribution for a newsgroup.
+    ///
+    /// Per RFC 3977 Section 7.6.5, the matching entry with the highest weight
+    /// applies. Returns `None` if no entry matches.
+    pub fn distribution(&self, group: &str) -> Option<&str> {
+        // `max_by_key` keeps the last maximum, so reverse to prefer the first
+        self.0
+            .iter()
+            .rev()
+            .filter(|pat| pat.matches(group))
+            .max_by_key(|pat| pat.weight)
+            .map(|pat| pat.distribution.as_str())
+    }
+}
+
 impl Deref for DistribPatsList {
     type Target = Vec<DistribPat>;
 
@@ -673,14 +980,14 @@ impl Deref for CountsList {
 ///
 /// ```ignore
 /// let mod_entry = ModeratorEntry {
-///     wildmat: "comp.*".to_string(),
+///     wildmat: "comp.*".parse()?,
 ///     template: "%s@moderators.isc.org".to_string(),
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct ModeratorEntry {
     /// Wildcard pattern matching newsgroup names.
-    pub wildmat: String,
+    pub wildmat: Wildmat,
     /// Email template (%s is replaced with group name components).
     pub template: String,
 }
@@ -700,6 +1007,51 @@ pub struct ModeratorEntry {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ModeratorsList(pub Vec<ModeratorEntry>);
 
+impl ModeratorEntry {
+    /// Check whether a newsgroup matches this entry's wildmat.
+    pub fn matches(&self, group: &str) -> bool {
+        self.wildmat.matches(group)
+    }
+
+    /// Build the submission address for a newsgroup from the template.
+    ///
+    /// `%s` is replaced by the group name with periods turned into dashes,
+    /// and `%%` by a single `%`.
+    pub fn address(&self, group: &str) -> String {
+        let mut address = String::with_capacity(self.template.len() + group.len());
+        let mut chars = self.template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                address.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('s') => address.push_str(&group.replace('.', "-")),
+                Some('%') => address.push('%'),
+                Some(other) => {
+                    address.push('%');
+                    address.push(other);
+                }
+                None => address.push('%'),
+            }
+        }
+        address
+    }
+}
+
+impl ModeratorsList {
+    /// Get the submission address of a moderated newsgroup.
+    ///
+    /// The first entry whose wildmat matches the group applies, as in the
+    /// moderators file the list is served from.
+    pub fn moderator(&self, group: &str) -> Option<String> {
+        self.0
+            .iter()
+            .find(|entry| entry.matches(group))
+            .map(|entry| entry.address(group))
+    }
+}
+
 impl Deref for ModeratorsList {
     type Target = Vec<ModeratorEntry>;
 
@@ -787,7 +1139,8 @@ pub struct NewsgroupDesc {
 
 /// List of newsgroup descriptions.
 ///
-/// Wraps a list of [`NewsgroupDesc`] values returned by LIST NEWSGROUPS (215 response).
+/// Wraps a list of [`NewsgroupDesc`] values returned by LIST NEWSGROUPS (215 response)
+/// or XGTITLE (282 response).
 /// Each entry contains the group name and a human-readable description.
 ///
 /// # Example
@@ -809,6 +1162,59 @@ impl Deref for NewsgroupDescList {
     }
 }
 
+impl TryFrom<Response> for NewsgroupDescList {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        match response {
+            Response::NewsgroupDescriptions(descriptions) => Ok(NewsgroupDescList(descriptions)),
+            _ => Err(Error::InvalidResponse(
+                "Expected newsgroup descriptions response".to_string(),
+            )),
+        }
+    }
+}
+
+/// Outcome of offering an article in a streaming feed (RFC 4644).
+///
+/// Converted from the final reply to CHECK or TAKETHIS. A 238 reply to
+/// CHECK is not an outcome, since the article still has to be sent.
+///
+/// # Example
+///
+/// ```ignore
+/// let outcome: FeedOutcome = response.try_into()?;
+/// if outcome == FeedOutcome::Deferred {
+///     retry_later.push(message_id);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedOutcome {
+    /// The server accepted the article (239)
+    Accepted,
+    /// The server cannot take the article now; offer it again later (431)
+    Deferred,
+    /// The server does not want the article; do not offer it again (438/439)
+    Rejected,
+}
+
+impl TryFrom<Response> for FeedOutcome {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        match response {
+            Response::TakethisAccepted { .. } => Ok(FeedOutcome::Accepted),
+            Response::CheckDeferred { .. } => Ok(FeedOutcome::Deferred),
+            Response::CheckNotWanted { .. } | Response::TakethisRejected { .. } => {
+                Ok(FeedOutcome::Rejected)
+            }
+            _ => Err(Error::InvalidResponse(
+                "Expected streaming feed response".to_string(),
+            )),
+        }
+    }
+}
+
 // TryFrom<Response> for Article
 impl TryFrom<Response> for Article {
     type Error = Error;
@@ -833,12 +1239,105 @@ mod tests {
 
     #[test]
     fn test_capabilities_deref() {
-        let caps = Capabilities(vec!["VERSION 2".to_string(), "READER".to_string()]);
+        let caps = Capabilities::new(vec!["VERSION 2".to_string(), "READER".to_string()]);
         assert_eq!(caps.len(), 2);
         assert_eq!(caps[0], "VERSION 2");
         assert!(caps.contains(&"READER".to_string()));
     }
 
+    fn sample_capabilities() -> Capabilities {
+        Capabilities::new(
+            [
+                "VERSION 2 3",
+                "READER",
+                "IHAVE",
+                "POST",
+                "LIST ACTIVE NEWSGROUPS OVERVIEW.FMT",
+                "HDR",
+                "OVER MSGID",
+                "AUTHINFO USER SASL",
+                "SASL PLAIN SCRAM-SHA-256",
+                "COMPRESS DEFLATE",
+                "IMPLEMENTATION INN 2.7.1",
+                "XFEATURE-COMPRESS GZIP TERMINATOR",
+            ]
+            .iter()
+            .map(|line| line.to_string())
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn test_capabilities_accessors() {
+        let caps = sample_capabilities();
+        assert_eq!(caps.versions(), [2, 3]);
+        assert!(caps.is_reader());
+        assert!(caps.is_ihave());
+        assert!(caps.is_post());
+        assert!(!caps.is_streaming());
+        assert!(!caps.has_mode_reader());
+        assert!(!caps.has_starttls());
+        assert_eq!(
+            caps.list_variants(),
+            ["ACTIVE", "NEWSGROUPS", "OVERVIEW.FMT"]
+        );
+        assert!(caps.supports_list("overview.fmt"));
+        assert!(!caps.supports_list("COUNTS"));
+        assert!(caps.supports_hdr());
+        assert!(caps.supports_over());
+        assert!(caps.supports_over_msgid());
+        assert_eq!(caps.authinfo_methods(), ["USER", "SASL"]);
+        assert_eq!(caps.sasl_mechanisms(), ["PLAIN", "SCRAM-SHA-256"]);
+        assert_eq!(caps.compression_algorithms(), ["DEFLATE"]);
+        assert!(caps.supports_xfeature_gzip());
+        assert!(!caps.supports_xzver());
+        assert!(!caps.supports_xzhdr());
+        assert_eq!(caps.implementation(), Some("INN 2.7.1"));
+    }
+
+    #[test]
+    fn test_capabilities_generic_lookup() {
+        let caps = sample_capabilities();
+        assert_eq!(
+            caps.get("xfeature-compress").unwrap(),
+            ["GZIP", "TERMINATOR"]
+        );
+        assert_eq!(caps.get("READER"), Some(&[][..]));
+        assert!(caps.has_argument("XFEATURE-COMPRESS", "terminator"));
+        assert!(!caps.has("XZVER"));
+    }
+
+    #[test]
+    fn test_capabilities_missing() {
+        let caps = Capabilities::new(vec!["VERSION 2".to_string(), "OVER".to_string()]);
+        assert!(caps.supports_over());
+        assert!(!caps.supports_over_msgid());
+        assert!(caps.sasl_mechanisms().is_empty());
+        assert_eq!(caps.implementation(), None);
+    }
+
+    #[test]
+    fn test_capabilities_first_line_wins() {
+        let caps = Capabilities::from(vec![
+            "over".to_string(),
+            "".to_string(),
+            "OVER MSGID".to_string(),
+            "Implementation  Example  Server ".to_string(),
+        ]);
+        assert_eq!(caps.len(), 4);
+        assert!(caps.supports_over());
+        assert!(!caps.supports_over_msgid());
+        assert_eq!(caps.implementation(), Some("Example  Server"));
+    }
+
+    #[test]
+    fn test_overview_format_default() {
+        let format = OverviewFormat::default();
+        assert_eq!(format.len(), 7);
+        assert_eq!(format[0], "Subject:");
+        assert_eq!(format[6], ":lines");
+    }
+
     #[test]
     fn test_help_text_deref() {
         let help = HelpText(vec!["HELP".to_string(), "GROUP".to_string()]);
@@ -858,6 +1357,28 @@ mod tests {
         assert_eq!(groups[0].name, "misc.test");
     }
 
+    #[test]
+    fn test_newsgroup_list_matching() {
+        let group = |name: &str| NewsGroup {
+            name: name.to_string(),
+            first: 1,
+            last: 10,
+            posting_status: 'y',
+        };
+        let groups = NewsgroupList(vec![
+            group("comp.lang.rust"),
+            group("comp.sys.mac"),
+            group("de.comp.lang.übersicht"),
+            group("misc.test"),
+        ]);
+        let wildmat = Wildmat::new("*comp.*,!comp.sys.*").unwrap();
+        let names: Vec<&str> = groups
+            .matching(&wildmat)
+            .map(|group| group.name.as_str())
+            .collect();
+        assert_eq!(names, ["comp.lang.rust", "de.comp.lang.übersicht"]);
+    }
+
     #[test]
     fn test_message_id_list_deref() {
         let ids = MessageIdList(vec!["<abc@example.com>".to_string()]);
@@ -867,7 +1388,13 @@ mod tests {
 
     #[test]
     fn test_article_numbers_deref() {
-        let nums = ArticleNumbers(vec![1, 2, 3, 5, 8]);
+        let nums = ArticleNumbers {
+            count: 5,
+            first: 1,
+            last: 8,
+            name: "misc.test".to_string(),
+            numbers: [1, 2, 3, 5, 8].into_iter().collect(),
+        };
         assert_eq!(nums.len(), 5);
         assert_eq!(nums.iter().sum::<u64>(), 19);
     }
@@ -894,19 +1421,26 @@ mod tests {
 
     #[test]
     fn test_server_date_deref() {
-        let date = ServerDate("20231106123456".to_string());
-        assert!(date.starts_with("2023"));
-        assert_eq!(date.len(), 14);
+        let date = ServerDate(Timestamp::from_utc(2023, 11, 6, 12, 34, 56).unwrap());
+        assert_eq!(date.to_utc().0, 2023);
+        assert_eq!(date.to_string(), "20231106123456");
+    }
+
+    #[test]
+    fn test_server_date_clock_skew() {
+        let date = ServerDate(Timestamp::from_unix(1_700_000_100));
+        assert_eq!(date.clock_skew(Timestamp::from_unix(1_700_000_000)), 100);
+        assert_eq!(date.clock_skew(Timestamp::from_unix(1_700_000_130)), -30);
     }
 
     #[test]
     fn test_article_pointer() {
         let pointer = ArticlePointer {
             number: 12345,
-            message_id: "<abc@example.com>".to_string(),
+            message_id: Some("<abc@example.com>".parse().unwrap()),
         };
         assert_eq!(pointer.number, 12345);
-        assert!(pointer.message_id.contains('@'));
+        assert!(pointer.message_id.unwrap().contains('@'));
     }
 
     #[test]
@@ -923,6 +1457,7 @@ mod tests {
     fn test_overview_data_deref() {
         let overview = OverviewData(vec![OverviewEntry {
             fields: vec!["1234".to_string(), "Test Subject".to_string()],
+            ..Default::default()
         }]);
         assert_eq!(overview.len(), 1);
         assert_eq!(overview[0].subject(), Some("Test Subject"));
@@ -1026,9 +1561,24 @@ mod tests {
 
     #[test]
     fn test_article_numbers_try_from_success() {
-        let response = Response::ArticleListing(vec![1, 2, 3, 5, 8]);
+        let response = Response::ArticleListing {
+            count: 6,
+            first: 1,
+            last: 9,
+            name: "misc.test".to_string(),
+            articles: [1, 2, 3, 5, 8].into_iter().collect(),
+        };
         let nums: ArticleNumbers = response.try_into().unwrap();
         assert_eq!(nums.len(), 5);
+        assert_eq!(nums.name, "misc.test");
+        assert_eq!(
+            nums.stats(),
+            GroupStats {
+                count: 6,
+                first: 1,
+                last: 9
+            }
+        );
     }
 
     #[test]
@@ -1083,9 +1633,9 @@ mod tests {
 
     #[test]
     fn test_server_date_try_from_success() {
-        let response = Response::Date("20231106123456".to_string());
+        let response = Response::Date(Timestamp::from_unix(1_699_274_096));
         let date: ServerDate = response.try_into().unwrap();
-        assert_eq!(*date, "20231106123456");
+        assert_eq!(date.to_string(), "20231106123456");
     }
 
     #[test]
@@ -1103,7 +1653,7 @@ mod tests {
         };
         let pointer: ArticlePointer = response.try_into().unwrap();
         assert_eq!(pointer.number, 12345);
-        assert_eq!(pointer.message_id, "<abc@example.com>");
+        assert_eq!(pointer.message_id.unwrap(), "<abc@example.com>");
     }
 
     #[test]
@@ -1135,6 +1685,7 @@ mod tests {
     fn test_overview_data_try_from_success() {
         let response = Response::OverviewData(vec![OverviewEntry {
             fields: vec!["1234".to_string(), "Test Subject".to_string()],
+            ..Default::default()
         }]);
         let overview: OverviewData = response.try_into().unwrap();
         assert_eq!(overview.len(), 1);
@@ -1192,11 +1743,11 @@ mod tests {
     fn test_active_time_entry() {
         let entry = ActiveTimeEntry {
             name: "comp.lang.rust".to_string(),
-            timestamp: 1609459200,
+            timestamp: Timestamp::from_unix(1609459200),
             creator: "admin@example.com".to_string(),
         };
         assert_eq!(entry.name, "comp.lang.rust");
-        assert_eq!(entry.timestamp, 1609459200);
+        assert_eq!(entry.timestamp.to_string(), "20210101000000");
         assert_eq!(entry.creator, "admin@example.com");
     }
 
@@ -1205,12 +1756,12 @@ mod tests {
         let times = ActiveTimesList(vec![
             ActiveTimeEntry {
                 name: "comp.lang.rust".to_string(),
-                timestamp: 1609459200,
+                timestamp: Timestamp::from_unix(1609459200),
                 creator: "admin@example.com".to_string(),
             },
             ActiveTimeEntry {
                 name: "alt.test".to_string(),
-                timestamp: 1609545600,
+                timestamp: Timestamp::from_unix(1609545600),
                 creator: "other@example.com".to_string(),
             },
         ]);
@@ -1223,11 +1774,11 @@ mod tests {
     fn test_distrib_pat() {
         let pat = DistribPat {
             weight: 10,
-            wildmat: "comp.*".to_string(),
+            wildmat: "comp.*".parse().unwrap(),
             distribution: "world".to_string(),
         };
         assert_eq!(pat.weight, 10);
-        assert_eq!(pat.wildmat, "comp.*");
+        assert_eq!(pat.wildmat.as_str(), "comp.*");
         assert_eq!(pat.distribution, "world");
     }
 
@@ -1236,20 +1787,45 @@ mod tests {
         let pats = DistribPatsList(vec![
             DistribPat {
                 weight: 10,
-                wildmat: "comp.*".to_string(),
+                wildmat: "comp.*".parse().unwrap(),
                 distribution: "world".to_string(),
             },
             DistribPat {
                 weight: 5,
-                wildmat: "local.*".to_string(),
+                wildmat: "local.*".parse().unwrap(),
                 distribution: "local".to_string(),
             },
         ]);
         assert_eq!(pats.len(), 2);
-        assert_eq!(pats[0].wildmat, "comp.*");
+        assert_eq!(pats[0].wildmat.as_str(), "comp.*");
         assert_eq!(pats[1].distribution, "local");
     }
 
+    #[test]
+    fn test_distrib_pats_lookup() {
+        let pats = DistribPatsList(vec![
+            DistribPat {
+                weight: 3,
+                wildmat: "*".parse().unwrap(),
+                distribution: "world".to_string(),
+            },
+            DistribPat {
+                weight: 10,
+                wildmat: "local.*".parse().unwrap(),
+                distribution: "local".to_string(),
+            },
+            DistribPat {
+                weight: 10,
+                wildmat: "local.test".parse().unwrap(),
+                distribution: "test".to_string(),
+            },
+        ]);
+        assert_eq!(pats.distribution("comp.lang.rust"), Some("world"));
+        // The first of the highest weights wins
+        assert_eq!(pats.distribution("local.test"), Some("local"));
+        assert_eq!(DistribPatsList(Vec::new()).distribution("misc.test"), None);
+    }
+
     #[test]
     fn test_headers_list_deref() {
         let headers = HeadersList(vec![
@@ -1295,10 +1871,10 @@ mod tests {
     #[test]
     fn test_moderator_entry() {
         let entry = ModeratorEntry {
-            wildmat: "comp.lang.*".to_string(),
+            wildmat: "comp.lang.*".parse().unwrap(),
             template: "%s@moderators.example.org".to_string(),
         };
-        assert_eq!(entry.wildmat, "comp.lang.*");
+        assert_eq!(entry.wildmat.as_str(), "comp.lang.*");
         assert_eq!(entry.template, "%s@moderators.example.org");
     }
 
@@ -1306,16 +1882,39 @@ mod tests {
     fn test_moderators_list_deref() {
         let mods = ModeratorsList(vec![
             ModeratorEntry {
-                wildmat: "comp.*".to_string(),
+                wildmat: "comp.*".parse().unwrap(),
                 template: "%s@comp-moderators.example.org".to_string(),
             },
             ModeratorEntry {
-                wildmat: "*".to_string(),
+                wildmat: "*".parse().unwrap(),
                 template: "%s@default-moderators.example.org".to_string(),
             },
         ]);
         assert_eq!(mods.len(), 2);
-        assert_eq!(mods[0].wildmat, "comp.*");
+        assert_eq!(mods[0].wildmat.as_str(), "comp.*");
+    }
+
+    #[test]
+    fn test_moderators_list_lookup() {
+        let mods = ModeratorsList(vec![
+            ModeratorEntry {
+                wildmat: "comp.lang.*,!comp.lang.c".parse().unwrap(),
+                template: "%s@lang.example.org".to_string(),
+            },
+            ModeratorEntry {
+                wildmat: "*".parse().unwrap(),
+                template: "%s%%news@moderators.example.org".to_string(),
+            },
+        ]);
+        assert_eq!(
+            mods.moderator("comp.lang.rust").as_deref(),
+            Some("comp-lang-rust@lang.example.org")
+        );
+        assert_eq!(
+            mods.moderator("comp.lang.c").as_deref(),
+            Some("comp-lang-c%news@moderators.example.org")
+        );
+        assert_eq!(ModeratorsList(Vec::new()).moderator("misc.test"), None);
     }
 
     #[test]
@@ -1371,4 +1970,27 @@ mod tests {
         assert_eq!(descs[0].name, "comp.lang.rust");
         assert_eq!(descs[1].name, "comp.lang.c");
     }
+
+    #[test]
+    fn test_feed_outcome_from_response() {
+        let id = || "<a@b>".to_string();
+        let outcome = |response| FeedOutcome::try_from(response).unwrap();
+        assert_eq!(
+            outcome(Response::TakethisAccepted { message_id: id() }),
+            FeedOutcome::Accepted
+        );
+        assert_eq!(
+            outcome(Response::CheckDeferred { message_id: id() }),
+            FeedOutcome::Deferred
+        );
+        assert_eq!(
+            outcome(Response::CheckNotWanted { message_id: id() }),
+            FeedOutcome::Rejected
+        );
+        assert_eq!(
+            outcome(Response::TakethisRejected { message_id: id() }),
+            FeedOutcome::Rejected
+        );
+        assert!(FeedOutcome::try_from(Response::CheckWanted { message_id: id() }).is_err());
+    }
 }
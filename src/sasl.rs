@@ -0,0 +1,582 @@
+//! SASL mechanisms for AUTHINFO SASL (RFC 4643).
+//!
+//! A [`SaslMechanism`] produces the client side of a SASL exchange. The
+//! sans-io [`Client`](crate::Client) drives the exchange with
+//! [`Client::start_sasl`](crate::Client::start_sasl) and
+//! [`Client::continue_sasl`](crate::Client::continue_sasl), and
+//! `NntpClient::authenticate_sasl` picks a mechanism offered by the server.
+//!
+//! Built-in mechanisms:
+//!
+//! - [`Plain`] - PLAIN (RFC 4616), username and password in clear text
+//! - [`Scram`] - SCRAM-SHA-1 and SCRAM-SHA-256 (RFC 5802, RFC 7677), without
+//!   channel binding
+//! - [`External`] - EXTERNAL (RFC 4422), e.g. for TLS client certificates
+//!
+//! Custom mechanisms can be added by implementing [`SaslMechanism`].
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::sasl::{password_mechanisms, select_mechanism};
+//!
+//! let offered = ["PLAIN", "SCRAM-SHA-1"];
+//! let mechanism = select_mechanism(password_mechanisms("user", "secret"), &offered).unwrap();
+//! assert_eq!(mechanism.name(), "SCRAM-SHA-1");
+//! ```
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Result};
+
+/// Client side of a SASL authentication mechanism.
+pub trait SaslMechanism: Send {
+    /// The registered mechanism name, e.g. "PLAIN".
+    fn name(&self) -> &str;
+
+    /// The data the client sends first, or `None` if the server speaks first.
+    fn initial_response(&mut self) -> Result<Option<Vec<u8>>>;
+
+    /// Compute the response to a server challenge.
+    fn step(&mut self, challenge: &[u8]) -> Result<Vec<u8>>;
+
+    /// Check the outcome of a successful exchange.
+    ///
+    /// `data` holds the additional data of a 283 reply and is empty after a
+    /// 281 reply. Mechanisms that authenticate the server fail here if it
+    /// has not proven its identity.
+    fn finish(&mut self, data: &[u8]) -> Result<()> {
+        let _ = data;
+        Ok(())
+    }
+}
+
+/// Pick the first of the `preferred` mechanisms that the server offers.
+///
+/// `offered` is the server's mechanism list, as returned by
+/// [`Capabilities::sasl_mechanisms`](crate::response::Capabilities::sasl_mechanisms).
+pub fn select_mechanism(
+    preferred: Vec<Box<dyn SaslMechanism>>,
+    offered: &[impl AsRef<str>],
+) -> Option<Box<dyn SaslMechanism>> {
+    preferred.into_iter().find(|mechanism| {
+        offered
+            .iter()
+            .any(|name| name.as_ref().eq_ignore_ascii_case(mechanism.name()))
+    })
+}
+
+/// The built-in password mechanisms, strongest first: SCRAM-SHA-256,
+/// SCRAM-SHA-1 and PLAIN.
+pub fn password_mechanisms(username: &str, password: &str) -> Vec<Box<dyn SaslMechanism>> {
+    vec![
+        Box::new(Scram::sha256(username, password)),
+        Box::new(Scram::sha1(username, password)),
+        Box::new(Plain::new(username, password)),
+    ]
+}
+
+/// The PLAIN mechanism (RFC 4616).
+///
+/// Sends the password in clear text, so it should only be used over TLS.
+pub struct Plain {
+    username: String,
+    password: String,
+}
+
+impl Plain {
+    /// Create a PLAIN mechanism for the given credentials.
+    pub fn new(username: &str, password: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+}
+
+impl SaslMechanism for Plain {
+    fn name(&self) -> &str {
+        "PLAIN"
+    }
+
+    fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+        // authzid NUL authcid NUL passwd, with an empty authzid
+        let message = format!("\0{}\0{}", self.username, self.password);
+        Ok(Some(message.into_bytes()))
+    }
+
+    fn step(&mut self, _challenge: &[u8]) -> Result<Vec<u8>> {
+        Err(Error::InvalidResponse(
+            "Unexpected SASL challenge for PLAIN".to_string(),
+        ))
+    }
+}
+
+/// The EXTERNAL mechanism (RFC 4422 Appendix A).
+///
+/// Authenticates with credentials established outside of SASL, typically
+/// the client certificate of the TLS connection.
+pub struct External {
+    authzid: String,
+}
+
+impl External {
+    /// Create an EXTERNAL mechanism.
+    ///
+    /// `authzid` is the identity to act as, or `None` to use the one
+    /// derived from the external credentials.
+    pub fn new(authzid: Option<&str>) -> Self {
+        Self {
+            authzid: authzid.unwrap_or_default().to_string(),
+        }
+    }
+}
+
+impl SaslMechanism for External {
+    fn name(&self) -> &str {
+        "EXTERNAL"
+    }
+
+    fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+        Ok(Some(self.authzid.clone().into_bytes()))
+    }
+
+    fn step(&mut self, _challenge: &[u8]) -> Result<Vec<u8>> {
+        Err(Error::InvalidResponse(
+            "Unexpected SASL challenge for EXTERNAL".to_string(),
+        ))
+    }
+}
+
+/// Highest SCRAM iteration count accepted from a server, so that a hostile
+/// server cannot make the client spend unbounded time in PBKDF2
+const MAX_SCRAM_ITERATIONS: u32 = 100_000;
+
+/// The SCRAM-SHA-1 and SCRAM-SHA-256 mechanisms (RFC 5802, RFC 7677).
+///
+/// SCRAM never sends the password and also verifies that the server knows
+/// it. Channel binding is not used. The password is used as UTF-8 without
+/// SASLprep normalization, which only matters for non-ASCII passwords.
+/// Servers asking for more than 100 000 iterations are refused.
+pub struct Scram {
+    hash: ScramHash,
+    username: String,
+    password: String,
+    client_nonce: Option<String>,
+    state: ScramState,
+}
+
+/// Hash function of a SCRAM variant
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScramHash {
+    Sha1,
+    Sha256,
+}
+
+/// Progress of a SCRAM exchange
+enum ScramState {
+    /// Nothing sent yet
+    Initial,
+    /// client-first-message sent
+    ClientFirst { client_first_bare: String },
+    /// client-final-message sent, the server must prove its signature
+    ClientFinal {
+        server_key: Vec<u8>,
+        auth_message: String,
+    },
+    /// The server has been verified
+    Verified,
+}
+
+impl Scram {
+    /// Create a SCRAM-SHA-1 mechanism for the given credentials.
+    pub fn sha1(username: &str, password: &str) -> Self {
+        Self::new(ScramHash::Sha1, username, password)
+    }
+
+    /// Create a SCRAM-SHA-256 mechanism for the given credentials.
+    pub fn sha256(username: &str, password: &str) -> Self {
+        Self::new(ScramHash::Sha256, username, password)
+    }
+
+    fn new(hash: ScramHash, username: &str, password: &str) -> Self {
+        Self {
+            hash,
+            username: username.to_string(),
+            password: password.to_string(),
+            client_nonce: None,
+            state: ScramState::Initial,
+        }
+    }
+
+    /// Answer the server-first-message with the client-final-message.
+    fn client_final(&mut self, client_first_bare: &str, server_first: &str) -> Result<Vec<u8>> {
+        let client_nonce = self.client_nonce.as_deref().unwrap_or_default();
+        let nonce = scram_attribute(server_first, 'r')?;
+        if !nonce.starts_with(client_nonce) || nonce.len() == client_nonce.len() {
+            return Err(Error::InvalidResponse(
+                "SCRAM server nonce does not extend the client nonce".to_string(),
+            ));
+        }
+        let salt = decode_data(scram_attribute(server_first, 's')?)?;
+        let iterations: u32 = scram_attribute(server_first, 'i')?
+            .parse()
+            .ok()
+            .filter(|&i| i > 0)
+            .ok_or_else(|| Error::InvalidResponse("Invalid SCRAM iteration count".to_string()))?;
+        if iterations > MAX_SCRAM_ITERATIONS {
+            return Err(Error::InvalidResponse(format!(
+                "SCRAM iteration count {iterations} exceeds the limit of {MAX_SCRAM_ITERATIONS}"
+            )));
+        }
+
+        // The GS2 header "n,," says that channel binding is not supported
+        let client_final_bare = format!("c=biws,r={nonce}");
+        let auth_message = format!("{client_first_bare},{server_first},{client_final_bare}");
+
+        let salted_password = self.salted_password(&salt, iterations);
+        let client_key = self.hash.hmac(&salted_password, b"Client Key");
+        let stored_key = self.hash.digest(&client_key);
+        let client_signature = self.hash.hmac(&stored_key, auth_message.as_bytes());
+        let proof: Vec<u8> = client_key
+            .iter()
+            .zip(&client_signature)
+            .map(|(key, signature)| key ^ signature)
+            .collect();
+
+        let proof = BASE64.encode(proof);
+        self.state = ScramState::ClientFinal {
+            server_key: self.hash.hmac(&salted_password, b"Server Key"),
+            auth_message,
+        };
+        Ok(format!("{client_final_bare},p={proof}").into_bytes())
+    }
+
+    /// Hi() of RFC 5802 Section 2.2, i.e. PBKDF2 with HMAC.
+    fn salted_password(&self, salt: &[u8], iterations: u32) -> Vec<u8> {
+        let password = self.password.as_bytes();
+        let mut block = salt.to_vec();
+        block.extend_from_slice(&1u32.to_be_bytes());
+
+        let mut u = self.hash.hmac(password, &block);
+        let mut result = u.clone();
+        for _ in 1..iterations {
+            u = self.hash.hmac(password, &u);
+            for (r, x) in result.iter_mut().zip(&u) {
+                *r ^= x;
+            }
+        }
+        result
+    }
+
+    /// Check the server-final-message.
+    fn verify(&mut self, server_final: &[u8]) -> Result<()> {
+        let ScramState::ClientFinal {
+            server_key,
+            auth_message,
+        } = &self.state
+        else {
+            return Err(Error::InvalidResponse(
+                "SCRAM exchange is not complete".to_string(),
+            ));
+        };
+        let server_final = std::str::from_utf8(server_final)
+            .map_err(|_| Error::InvalidResponse("Invalid SCRAM server message".to_string()))?;
+        if let Ok(error) = scram_attribute(server_final, 'e') {
+            return Err(Error::InvalidResponse(format!(
+                "SCRAM authentication failed: {error}"
+            )));
+        }
+        let verifier = decode_data(scram_attribute(server_final, 'v')?)?;
+        if !self
+            .hash
+            .verify_hmac(server_key, auth_message.as_bytes(), &verifier)
+        {
+            return Err(Error::InvalidResponse(
+                "SCRAM server signature does not match".to_string(),
+            ));
+        }
+        self.state = ScramState::Verified;
+        Ok(())
+    }
+}
+
+impl SaslMechanism for Scram {
+    fn name(&self) -> &str {
+        match self.hash {
+            ScramHash::Sha1 => "SCRAM-SHA-1",
+            ScramHash::Sha256 => "SCRAM-SHA-256",
+        }
+    }
+
+    fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+        let nonce = match &self.client_nonce {
+            Some(nonce) => nonce.clone(),
+            None => {
+                let mut random = [0u8; 18];
+                getrandom::getrandom(&mut random).map_err(|e| {
+                    Error::InvalidCommand(format!("Failed to generate SCRAM nonce: {e}"))
+                })?;
+                let nonce = BASE64.encode(random);
+                self.client_nonce = Some(nonce.clone());
+                nonce
+            }
+        };
+
+        let username = self.username.replace('=', "=3D").replace(',', "=2C");
+        let client_first_bare = format!("n={username},r={nonce}");
+        let message = format!("n,,{client_first_bare}");
+        self.state = ScramState::ClientFirst { client_first_bare };
+        Ok(Some(message.into_bytes()))
+    }
+
+    fn step(&mut self, challenge: &[u8]) -> Result<Vec<u8>> {
+        match &self.state {
+            ScramState::ClientFirst { client_first_bare } => {
+                let client_first_bare = client_first_bare.clone();
+                let server_first = std::str::from_utf8(challenge).map_err(|_| {
+                    Error::InvalidResponse("Invalid SCRAM server message".to_string())
+                })?;
+                self.client_final(&client_first_bare, server_first)
+            }
+            // The server-final-message may come as a challenge, which is
+            // answered with an empty response
+            ScramState::ClientFinal { .. } => {
+                self.verify(challenge)?;
+                Ok(Vec::new())
+            }
+            ScramState::Initial | ScramState::Verified => Err(Error::InvalidResponse(
+                "Unexpected SASL challenge for SCRAM".to_string(),
+            )),
+        }
+    }
+
+    fn finish(&mut self, data: &[u8]) -> Result<()> {
+        match self.state {
+            ScramState::Verified => Ok(()),
+            _ => self.verify(data),
+        }
+    }
+}
+
+impl ScramHash {
+    fn hmac(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            ScramHash::Sha1 => {
+                let mut mac =
+                    Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+            ScramHash::Sha256 => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+
+    /// Check an HMAC in constant time.
+    fn verify_hmac(self, key: &[u8], data: &[u8], tag: &[u8]) -> bool {
+        match self {
+            ScramHash::Sha1 => {
+                let mut mac =
+                    Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                mac.update(data);
+                mac.verify_slice(tag).is_ok()
+            }
+            ScramHash::Sha256 => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                mac.update(data);
+                mac.verify_slice(tag).is_ok()
+            }
+        }
+    }
+
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            ScramHash::Sha1 => Sha1::digest(data).to_vec(),
+            ScramHash::Sha256 => Sha256::digest(data).to_vec(),
+        }
+    }
+}
+
+/// Get the value of a `name=value` attribute of a SCRAM message.
+fn scram_attribute(message: &str, name: char) -> Result<&str> {
+    message
+        .split(',')
+        .find_map(|attribute| {
+            attribute
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+        .ok_or_else(|| Error::InvalidResponse(format!("SCRAM message lacks the {name} attribute")))
+}
+
+/// Encode SASL data for the wire; empty data is sent as "=" (RFC 4643
+/// Section 2.4.1).
+pub(crate) fn encode_data(data: &[u8]) -> String {
+    if data.is_empty() {
+        "=".to_string()
+    } else {
+        BASE64.encode(data)
+    }
+}
+
+/// Decode SASL data received from the server.
+pub(crate) fn decode_data(data: &str) -> Result<Vec<u8>> {
+    if data == "=" {
+        return Ok(Vec::new());
+    }
+    BASE64
+        .decode(data)
+        .map_err(|e| Error::Parse(format!("Invalid base64 in SASL data: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a SCRAM exchange against the messages of an RFC test vector.
+    fn run_scram(
+        mut scram: Scram,
+        client_nonce: &str,
+        server_first: &str,
+        server_final: &str,
+    ) -> (String, String) {
+        scram.client_nonce = Some(client_nonce.to_string());
+        let client_first = scram.initial_response().unwrap().unwrap();
+        let client_final = scram.step(server_first.as_bytes()).unwrap();
+        scram.finish(server_final.as_bytes()).unwrap();
+        (
+            String::from_utf8(client_first).unwrap(),
+            String::from_utf8(client_final).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_scram_sha1_rfc5802_example() {
+        let (client_first, client_final) = run_scram(
+            Scram::sha1("user", "pencil"),
+            "fyko+d2lbbFgONRv9qkxdawL",
+            "r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096",
+            "v=rmF9pqV8S7suAoZWja4dJRkFsKQ=",
+        );
+        assert_eq!(client_first, "n,,n=user,r=fyko+d2lbbFgONRv9qkxdawL");
+        assert_eq!(
+            client_final,
+            "c=biws,r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,p=v0X8v3Bz2T0CJGbJQyF0X+HI4Ts="
+        );
+    }
+
+    #[test]
+    fn test_scram_sha256_rfc7677_example() {
+        let (client_first, client_final) = run_scram(
+            Scram::sha256("user", "pencil"),
+            "rOprNGfwEbeRWgbNEkqO",
+            "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+            "v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=",
+        );
+        assert_eq!(client_first, "n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+        assert_eq!(
+            client_final,
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ="
+        );
+    }
+
+    #[test]
+    fn test_scram_rejects_wrong_server_signature() {
+        let mut scram = Scram::sha1("user", "pencil");
+        scram.client_nonce = Some("fyko+d2lbbFgONRv9qkxdawL".to_string());
+        scram.initial_response().unwrap();
+        scram
+            .step(b"r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096")
+            .unwrap();
+        assert!(scram.finish(b"v=AAAAAAAAAAAAAAAAAAAAAAAAAAA=").is_err());
+        assert!(scram.finish(b"").is_err());
+    }
+
+    #[test]
+    fn test_scram_rejects_excessive_iteration_count() {
+        let mut scram = Scram::sha256("user", "pencil");
+        scram.client_nonce = Some("abc".to_string());
+        scram.initial_response().unwrap();
+        for iterations in ["100001", "4294967295"] {
+            let server_first = format!("r=abcdef,s=QSXCR+Q6sek8bf92,i={iterations}");
+            assert!(scram.step(server_first.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_scram_rejects_foreign_nonce() {
+        let mut scram = Scram::sha256("user", "pencil");
+        scram.client_nonce = Some("abc".to_string());
+        scram.initial_response().unwrap();
+        assert!(scram.step(b"r=xyz123,s=QSXCR+Q6sek8bf92,i=4096").is_err());
+    }
+
+    #[test]
+    fn test_scram_escapes_username() {
+        let mut scram = Scram::sha256("a=b,c", "pencil");
+        scram.client_nonce = Some("abc".to_string());
+        let client_first = scram.initial_response().unwrap().unwrap();
+        assert_eq!(client_first, b"n,,n=a=3Db=2Cc,r=abc");
+    }
+
+    #[test]
+    fn test_scram_generates_nonce() {
+        let mut scram = Scram::sha256("user", "pencil");
+        let client_first = scram.initial_response().unwrap().unwrap();
+        assert!(client_first.starts_with(b"n,,n=user,r="));
+        assert_eq!(client_first.len(), "n,,n=user,r=".len() + 24);
+    }
+
+    #[test]
+    fn test_plain_initial_response() {
+        let mut plain = Plain::new("tim", "tanstaaftanstaaf");
+        assert_eq!(
+            plain.initial_response().unwrap().unwrap(),
+            b"\0tim\0tanstaaftanstaaf"
+        );
+        assert!(plain.step(b"").is_err());
+    }
+
+    #[test]
+    fn test_external_initial_response() {
+        assert_eq!(
+            External::new(None).initial_response().unwrap(),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            External::new(Some("admin")).initial_response().unwrap(),
+            Some(b"admin".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_select_mechanism_prefers_strongest_offered() {
+        let mechanism = select_mechanism(password_mechanisms("u", "p"), &["PLAIN"]).unwrap();
+        assert_eq!(mechanism.name(), "PLAIN");
+
+        let offered = ["plain", "scram-sha-256", "SCRAM-SHA-1"];
+        let mechanism = select_mechanism(password_mechanisms("u", "p"), &offered).unwrap();
+        assert_eq!(mechanism.name(), "SCRAM-SHA-256");
+
+        assert!(select_mechanism(password_mechanisms("u", "p"), &["GSSAPI"]).is_none());
+    }
+
+    #[test]
+    fn test_sasl_data_encoding() {
+        assert_eq!(encode_data(b""), "=");
+        assert_eq!(encode_data(b"abc"), "YWJj");
+        assert_eq!(decode_data("=").unwrap(), b"");
+        assert_eq!(decode_data("YWJj").unwrap(), b"abc");
+        assert!(decode_data("not base64!").is_err());
+    }
+}
@@ -0,0 +1,736 @@
+//! Incremental synchronization of group overviews.
+//!
+//! A [`Synchronizer`] runs the loop every newsreader and indexer needs: select
+//! a group, compare its high-water mark with the last article fetched, fetch
+//! the overview of the new articles in bounded chunks, and remember how far
+//! it got. Marks are kept in a [`SyncStore`], so that the state can live in
+//! memory, a file or a database.
+//!
+//! The mark is saved after every chunk, so an interrupted sync resumes where
+//! it stopped. If the server renumbered a group, which shows as a high or low
+//! water mark lower than before, the new articles are found with NEWNEWS when
+//! the server offers it together with OVER by message-id; otherwise the group
+//! is fetched again as on the first sync. A group reported as empty keeps
+//! its mark, since it says nothing about renumbering.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "tokio-runtime")]
+//! # {
+//! use nntp_rs::net_client::NntpClient;
+//! use nntp_rs::runtime::TokioStream;
+//! use nntp_rs::sync::{MemoryStore, Synchronizer};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = NntpClient::<TokioStream>::connect("news.example.com:119").await?;
+//! let mut sync = Synchronizer::new(MemoryStore::new());
+//! sync.set_initial_limit(Some(1000));
+//!
+//! let groups = ["comp.lang.rust".parse()?, "misc.test".parse()?];
+//! let reports = sync
+//!     .sync(&mut client, &groups, |progress, entries| {
+//!         println!("{}: {} new, {} to go", progress.group, entries.len(), progress.remaining);
+//!         Ok(())
+//!     })
+//!     .await?;
+//! for report in reports {
+//!     println!("{}: {:?}, {} entries", report.group, report.mode, report.fetched);
+//! }
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::net_client::NntpClient;
+use crate::response::{GroupStats, OverviewData, OverviewEntry};
+use crate::runtime::AsyncStream;
+use crate::{ArticleRange, Command, Error, GroupName, MessageId, Response, Result, Timestamp};
+
+/// Default number of articles asked for in one OVER command.
+pub const DEFAULT_CHUNK_SIZE: u64 = 1000;
+
+/// How far a group has been synchronized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SyncMark {
+    /// Lowest article number of the group at the last sync
+    pub first: u64,
+    /// Highest article number fetched
+    pub last: u64,
+    /// Server time when the last complete sync started, if known
+    pub since: Option<Timestamp>,
+}
+
+/// Storage for the marks of synchronized groups.
+#[async_trait]
+pub trait SyncStore: Send {
+    /// Get the mark of a group, or `None` if it was never synchronized.
+    async fn load(&mut self, group: &GroupName) -> Result<Option<SyncMark>>;
+
+    /// Remember the mark of a group.
+    async fn save(&mut self, group: &GroupName, mark: SyncMark) -> Result<()>;
+}
+
+/// A [`SyncStore`] that keeps marks in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    marks: HashMap<GroupName, SyncMark>,
+}
+
+impl MemoryStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the mark of a group.
+    pub fn get(&self, group: &str) -> Option<SyncMark> {
+        self.marks.get(group).copied()
+    }
+}
+
+#[async_trait]
+impl SyncStore for MemoryStore {
+    async fn load(&mut self, group: &GroupName) -> Result<Option<SyncMark>> {
+        Ok(self.marks.get(group).copied())
+    }
+
+    async fn save(&mut self, group: &GroupName, mark: SyncMark) -> Result<()> {
+        self.marks.insert(group.clone(), mark);
+        Ok(())
+    }
+}
+
+/// How a group was synchronized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncMode {
+    /// The group had no mark; its articles were fetched from the start or
+    /// from the initial limit
+    Initial,
+    /// Articles after the mark were fetched; an empty group leaves the mark
+    /// unchanged
+    Incremental,
+    /// The server renumbered the group; the new articles were found with
+    /// NEWNEWS and fetched by message-id
+    NewNews,
+    /// The server renumbered the group and NEWNEWS could not be used; the
+    /// group was fetched again as on the first sync
+    Reset,
+    /// The server does not carry the group
+    Missing,
+}
+
+/// Progress of the group being synchronized, passed with every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncProgress<'a> {
+    /// Group being synchronized
+    pub group: &'a GroupName,
+    /// How the group is synchronized
+    pub mode: SyncMode,
+    /// Overview entries fetched so far, including this chunk
+    pub fetched: u64,
+    /// Articles still to be asked for; cancelled articles make this an
+    /// upper bound of the entries to come
+    pub remaining: u64,
+}
+
+/// The outcome of synchronizing one group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSyncReport {
+    /// Group that was synchronized
+    pub group: GroupName,
+    /// How the group was synchronized
+    pub mode: SyncMode,
+    /// Number of overview entries fetched
+    pub fetched: u64,
+    /// Mark saved at the end, `None` if the group is missing
+    pub mark: Option<SyncMark>,
+}
+
+/// Fetches the overview of new articles, group by group.
+///
+/// Marks are read from and written to the store `T`. Entries are handed to
+/// a callback one chunk at a time, together with a [`SyncProgress`]; an
+/// error returned by the callback stops the sync before the chunk's mark is
+/// saved, so the chunk is fetched again next time.
+#[derive(Debug, Clone)]
+pub struct Synchronizer<T: SyncStore> {
+    store: T,
+    /// Articles asked for in one OVER command, or message-ids in one
+    /// pipelined batch
+    chunk_size: u64,
+    /// Most articles fetched for a group without a mark
+    initial_limit: Option<u64>,
+}
+
+impl<T: SyncStore> Synchronizer<T> {
+    /// Create a synchronizer using a store for the marks.
+    pub fn new(store: T) -> Self {
+        Synchronizer {
+            store,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            initial_limit: None,
+        }
+    }
+
+    /// Get the store of the marks.
+    pub fn store(&self) -> &T {
+        &self.store
+    }
+
+    /// Get the store of the marks for updating.
+    pub fn store_mut(&mut self) -> &mut T {
+        &mut self.store
+    }
+
+    /// Take back the store of the marks.
+    pub fn into_store(self) -> T {
+        self.store
+    }
+
+    /// Get the number of articles asked for at once.
+    ///
+    /// Defaults to [`DEFAULT_CHUNK_SIZE`].
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Set the number of articles asked for at once; 0 is treated as 1.
+    pub fn set_chunk_size(&mut self, size: u64) {
+        self.chunk_size = size.max(1);
+    }
+
+    /// Limit how many of the newest articles are fetched for a group
+    /// without a mark, or after a reset. `None`, the default, fetches all.
+    pub fn set_initial_limit(&mut self, limit: Option<u64>) {
+        self.initial_limit = limit;
+    }
+
+    /// Synchronize several groups.
+    ///
+    /// The server time is asked once with DATE and recorded in the marks,
+    /// so that NEWNEWS can be used if a group is renumbered later. Groups
+    /// the server does not carry are reported as [`SyncMode::Missing`]
+    /// without stopping the others.
+    pub async fn sync<S, F>(
+        &mut self,
+        client: &mut NntpClient<S>,
+        groups: &[GroupName],
+        mut on_chunk: F,
+    ) -> Result<Vec<GroupSyncReport>>
+    where
+        S: AsyncStream,
+        F: FnMut(&SyncProgress<'_>, Vec<OverviewEntry>) -> Result<()>,
+    {
+        let now = server_time(client).await?;
+        let mut reports = Vec::with_capacity(groups.len());
+        for group in groups {
+            reports.push(self.sync_at(client, group, now, &mut on_chunk).await?);
+        }
+        Ok(reports)
+    }
+
+    /// Synchronize one group.
+    ///
+    /// See [`sync`](Self::sync).
+    pub async fn sync_group<S, F>(
+        &mut self,
+        client: &mut NntpClient<S>,
+        group: &GroupName,
+        mut on_chunk: F,
+    ) -> Result<GroupSyncReport>
+    where
+        S: AsyncStream,
+        F: FnMut(&SyncProgress<'_>, Vec<OverviewEntry>) -> Result<()>,
+    {
+        let now = server_time(client).await?;
+        self.sync_at(client, group, now, &mut on_chunk).await
+    }
+
+    async fn sync_at<S, F>(
+        &mut self,
+        client: &mut NntpClient<S>,
+        group: &GroupName,
+        now: Option<Timestamp>,
+        on_chunk: &mut F,
+    ) -> Result<GroupSyncReport>
+    where
+        S: AsyncStream,
+        F: FnMut(&SyncProgress<'_>, Vec<OverviewEntry>) -> Result<()>,
+    {
+        let stats = match client.group(group).await {
+            Ok(stats) => stats,
+            Err(Error::Protocol { code: 411, .. }) => {
+                return Ok(GroupSyncReport {
+                    group: group.clone(),
+                    mode: SyncMode::Missing,
+                    fetched: 0,
+                    mark: None,
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        let previous = self.store.load(group).await?;
+        if let Some(mark) = previous {
+            // An empty group says nothing about renumbering, so keep the mark
+            // until articles show up again
+            if stats.count == 0 || stats.last < stats.first || stats.last == 0 {
+                return Ok(GroupSyncReport {
+                    group: group.clone(),
+                    mode: SyncMode::Incremental,
+                    fetched: 0,
+                    mark: Some(mark),
+                });
+            }
+        }
+        let mut mode = match previous {
+            None => SyncMode::Initial,
+            Some(mark) if stats.last < mark.last || stats.first < mark.first => SyncMode::Reset,
+            Some(_) => SyncMode::Incremental,
+        };
+
+        let mut fetched = 0;
+        let mut since = previous.and_then(|mark| mark.since);
+        if mode == SyncMode::Reset {
+            if let Some(ids) = self.new_message_ids(client, group, since).await? {
+                mode = SyncMode::NewNews;
+                fetched = self.fetch_by_id(client, group, ids, on_chunk).await?;
+            } else {
+                since = None;
+            }
+        }
+
+        if mode != SyncMode::NewNews && stats.count > 0 {
+            let start = match (mode, previous) {
+                (SyncMode::Incremental, Some(mark)) => mark.last.saturating_add(1),
+                _ => match self.initial_limit {
+                    Some(limit) => stats.last.saturating_add(1).saturating_sub(limit),
+                    None => stats.first,
+                },
+            };
+            fetched = self
+                .fetch_range(
+                    client,
+                    group,
+                    mode,
+                    &stats,
+                    start.max(stats.first),
+                    since,
+                    on_chunk,
+                )
+                .await?;
+        }
+
+        let mark = SyncMark {
+            first: stats.first,
+            last: stats.last,
+            since: now.or(since),
+        };
+        self.store.save(group, mark).await?;
+        Ok(GroupSyncReport {
+            group: group.clone(),
+            mode,
+            fetched,
+            mark: Some(mark),
+        })
+    }
+
+    /// Fetch the overview of a range of articles in chunks.
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_range<S, F>(
+        &mut self,
+        client: &mut NntpClient<S>,
+        group: &GroupName,
+        mode: SyncMode,
+        stats: &GroupStats,
+        start: u64,
+        since: Option<Timestamp>,
+        on_chunk: &mut F,
+    ) -> Result<u64>
+    where
+        S: AsyncStream,
+        F: FnMut(&SyncProgress<'_>, Vec<OverviewEntry>) -> Result<()>,
+    {
+        let mut fetched = 0;
+        let mut next = start;
+        while next <= stats.last {
+            let end = next.saturating_add(self.chunk_size - 1).min(stats.last);
+            let entries = match client.over(Some(ArticleRange::Between(next, end))).await {
+                Ok(overview) => overview.0,
+                // No articles left in the chunk
+                Err(Error::Protocol { code: 423, .. }) => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            fetched += entries.len() as u64;
+            let progress = SyncProgress {
+                group,
+                mode,
+                fetched,
+                remaining: stats.last - end,
+            };
+            on_chunk(&progress, entries)?;
+
+            let mark = SyncMark {
+                first: stats.first,
+                last: end,
+                since,
+            };
+            self.store.save(group, mark).await?;
+            next = match end.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(fetched)
+    }
+
+    /// Find the articles that arrived in a group since the last sync with
+    /// NEWNEWS, if the server allows it.
+    async fn new_message_ids<S: AsyncStream>(
+        &mut self,
+        client: &mut NntpClient<S>,
+        group: &GroupName,
+        since: Option<Timestamp>,
+    ) -> Result<Option<Vec<MessageId>>> {
+        let Some(since) = since else {
+            return Ok(None);
+        };
+        let capabilities = match client.capabilities().await {
+            Ok(capabilities) => capabilities,
+            Err(Error::Protocol { .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if !capabilities.has("NEWNEWS") || !capabilities.supports_over_msgid() {
+            return Ok(None);
+        }
+        match client.newnews(group.to_string(), since).await {
+            Ok(ids) => Ok(Some(
+                ids.iter()
+                    .filter_map(|id| MessageId::new(id.as_str()).ok())
+                    .collect(),
+            )),
+            // NEWNEWS may be disabled even if advertised
+            Err(Error::Protocol { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetch the overview of articles by message-id, pipelining the OVER
+    /// commands of each chunk.
+    ///
+    /// No mark is saved until all chunks are done, since the numbers before
+    /// the renumbering mean nothing any more.
+    async fn fetch_by_id<S, F>(
+        &mut self,
+        client: &mut NntpClient<S>,
+        group: &GroupName,
+        ids: Vec<MessageId>,
+        on_chunk: &mut F,
+    ) -> Result<u64>
+    where
+        S: AsyncStream,
+        F: FnMut(&SyncProgress<'_>, Vec<OverviewEntry>) -> Result<()>,
+    {
+        let schema = client.overview_schema().await?;
+        let chunk_size = usize::try_from(self.chunk_size).unwrap_or(usize::MAX);
+        let mut fetched = 0;
+        let mut remaining = ids.len() as u64;
+
+        for chunk in ids.chunks(chunk_size) {
+            let commands = chunk
+                .iter()
+                .map(|id| Command::Over {
+                    range: Some(ArticleRange::MessageId(id.clone())),
+                })
+                .collect();
+            let mut entries = Vec::with_capacity(chunk.len());
+            for response in client.pipeline(commands).await? {
+                // Articles cancelled since NEWNEWS are skipped
+                if let Response::Error { .. } = response {
+                    continue;
+                }
+                let overview: OverviewData = response.try_into()?;
+                entries.extend(overview.0.into_iter().map(|mut entry| {
+                    entry.schema = schema.clone();
+                    entry
+                }));
+            }
+
+            fetched += entries.len() as u64;
+            remaining -= chunk.len() as u64;
+            let progress = SyncProgress {
+                group,
+                mode: SyncMode::NewNews,
+                fetched,
+                remaining,
+            };
+            on_chunk(&progress, entries)?;
+        }
+
+        Ok(fetched)
+    }
+}
+
+/// Get the server time, or `None` if the server does not support DATE.
+async fn server_time<S: AsyncStream>(client: &mut NntpClient<S>) -> Result<Option<Timestamp>> {
+    match client.date().await {
+        Ok(date) => Ok(Some(date.0)),
+        Err(Error::Protocol { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net_client::scripted::{scripted_client, sent_commands};
+
+    const FORMAT: &str = "215 Order of fields\r\nSubject:\r\nMessage-ID:\r\n.\r\n";
+
+    fn group() -> GroupName {
+        "misc.test".parse().unwrap()
+    }
+
+    fn timestamp(day: u32) -> Timestamp {
+        Timestamp::from_utc(2024, 1, day, 12, 0, 0).unwrap()
+    }
+
+    /// Reply to an OVER request listing some article numbers.
+    fn over(numbers: &[u64]) -> String {
+        let mut reply = "224 Overview follows\r\n".to_string();
+        for n in numbers {
+            reply.push_str(&format!("{n}\tSubject {n}\t<{n}@x>\r\n"));
+        }
+        reply + ".\r\n"
+    }
+
+    #[tokio::test]
+    async fn test_initial_and_incremental_sync() {
+        let replies = [
+            "111 20240101120000\r\n".to_string(),
+            "211 5 1 5 misc.test\r\n".to_string(),
+            FORMAT.to_string(),
+            over(&[1, 2]),
+            over(&[4]),
+            over(&[5]),
+            "111 20240102120000\r\n".to_string(),
+            "211 6 1 7 misc.test\r\n".to_string(),
+            over(&[6, 7]),
+        ];
+        let replies: Vec<&str> = replies.iter().map(String::as_str).collect();
+        let mut client = scripted_client(&replies);
+        let mut sync = Synchronizer::new(MemoryStore::new());
+        sync.set_chunk_size(2);
+
+        let mut chunks = Vec::new();
+        let reports = sync
+            .sync(&mut client, &[group()], |progress, entries| {
+                let numbers: Vec<_> = entries.iter().filter_map(|e| e.number()).collect();
+                chunks.push((progress.mode, progress.fetched, progress.remaining, numbers));
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            chunks,
+            [
+                (SyncMode::Initial, 2, 3, vec![1, 2]),
+                (SyncMode::Initial, 3, 1, vec![4]),
+                (SyncMode::Initial, 4, 0, vec![5]),
+            ]
+        );
+        assert_eq!(reports[0].mode, SyncMode::Initial);
+        assert_eq!(reports[0].fetched, 4);
+        let mark = SyncMark {
+            first: 1,
+            last: 5,
+            since: Some(timestamp(1)),
+        };
+        assert_eq!(sync.store().get("misc.test"), Some(mark));
+
+        let report = sync
+            .sync_group(&mut client, &group(), |progress, entries| {
+                assert_eq!(entries[0].subject(), Some("Subject 6"));
+                assert_eq!(progress.remaining, 0);
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.mode, SyncMode::Incremental);
+        assert_eq!(report.fetched, 2);
+        assert_eq!(report.mark.unwrap().last, 7);
+        assert_eq!(
+            sent_commands(&client),
+            [
+                "DATE",
+                "GROUP misc.test",
+                "LIST OVERVIEW.FMT",
+                "OVER 1-2",
+                "OVER 3-4",
+                "OVER 5-5",
+                "DATE",
+                "GROUP misc.test",
+                "OVER 6-7",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_sync_resumes() {
+        let replies = [
+            "500 Unknown command\r\n".to_string(),
+            "411 No such newsgroup\r\n".to_string(),
+            "211 4 1 4 misc.test\r\n".to_string(),
+            FORMAT.to_string(),
+            over(&[1, 2]),
+            over(&[3, 4]),
+            "500 Unknown command\r\n".to_string(),
+            "211 4 1 4 misc.test\r\n".to_string(),
+            over(&[3, 4]),
+        ];
+        let replies: Vec<&str> = replies.iter().map(String::as_str).collect();
+        let mut client = scripted_client(&replies);
+        let mut sync = Synchronizer::new(MemoryStore::new());
+        sync.set_chunk_size(2);
+
+        let groups = ["gone.test".parse().unwrap(), group()];
+        let result = sync
+            .sync(&mut client, &groups, |progress, _| {
+                if progress.fetched > 2 {
+                    return Err(Error::Io("disk full".to_string()));
+                }
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert_eq!(sync.store().get("gone.test"), None);
+        let mark = sync.store().get("misc.test").unwrap();
+        assert_eq!((mark.last, mark.since), (2, None));
+
+        let report = sync
+            .sync_group(&mut client, &group(), |_, _| Ok(()))
+            .await
+            .unwrap();
+        assert_eq!((report.mode, report.fetched), (SyncMode::Incremental, 2));
+        assert_eq!(sent_commands(&client).last().unwrap(), "OVER 3-4");
+
+        let mut client = scripted_client(&["111 20240101120000\r\n", "411 No such newsgroup\r\n"]);
+        let report = sync
+            .sync_group(&mut client, &"gone.test".parse().unwrap(), |_, _| Ok(()))
+            .await
+            .unwrap();
+        assert_eq!((report.mode, report.mark), (SyncMode::Missing, None));
+    }
+
+    #[tokio::test]
+    async fn test_renumbered_group_uses_newnews() {
+        let mut client = scripted_client(&[
+            "111 20240102120000\r\n",
+            "211 3 1 3 misc.test\r\n",
+            "101 Capability list:\r\nVERSION 2\r\nREADER\r\nNEWNEWS\r\nOVER MSGID\r\nLIST ACTIVE OVERVIEW.FMT\r\n.\r\n",
+            "230 New articles follow\r\n<a@x>\r\n<b@x>\r\n.\r\n",
+            FORMAT,
+            "224 Overview follows\r\n0\tHello\t<a@x>\r\n.\r\n",
+            "430 No such article\r\n",
+        ]);
+        let mut sync = Synchronizer::new(MemoryStore::new());
+        let old = SyncMark {
+            first: 50,
+            last: 100,
+            since: Some(timestamp(1)),
+        };
+        sync.store_mut().save(&group(), old).await.unwrap();
+
+        let mut ids = Vec::new();
+        let report = sync
+            .sync_group(&mut client, &group(), |progress, entries| {
+                assert_eq!(progress.mode, SyncMode::NewNews);
+                ids.extend(entries.iter().map(|e| e.message_id().unwrap().to_string()));
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids, ["<a@x>"]);
+        assert_eq!((report.mode, report.fetched), (SyncMode::NewNews, 1));
+        let mark = SyncMark {
+            first: 1,
+            last: 3,
+            since: Some(timestamp(2)),
+        };
+        assert_eq!(report.mark, Some(mark));
+        assert_eq!(sync.store().get("misc.test"), Some(mark));
+        assert_eq!(
+            sent_commands(&client)[3..],
+            [
+                "NEWNEWS misc.test 20240101 120000 GMT",
+                "LIST OVERVIEW.FMT",
+                "OVER <a@x>",
+                "OVER <b@x>",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_renumbered_group_without_newnews_resets() {
+        let mut client = scripted_client(&[
+            "111 20240102120000\r\n",
+            "211 30 1 30 misc.test\r\n",
+            "101 Capability list:\r\nVERSION 2\r\nREADER\r\nOVER\r\nLIST ACTIVE OVERVIEW.FMT\r\n.\r\n",
+            FORMAT,
+            &over(&[29, 30]),
+        ]);
+        let mut sync = Synchronizer::new(MemoryStore::new());
+        sync.set_initial_limit(Some(2));
+        let old = SyncMark {
+            first: 1,
+            last: 100,
+            since: Some(timestamp(1)),
+        };
+        sync.store_mut().save(&group(), old).await.unwrap();
+
+        let report = sync
+            .sync_group(&mut client, &group(), |_, _| Ok(()))
+            .await
+            .unwrap();
+        assert_eq!((report.mode, report.fetched), (SyncMode::Reset, 2));
+        assert_eq!(report.mark.unwrap().last, 30);
+        assert_eq!(sent_commands(&client).last().unwrap(), "OVER 29-30");
+    }
+
+    #[tokio::test]
+    async fn test_empty_group_keeps_mark() {
+        let mut client = scripted_client(&[
+            "111 20240102120000\r\n",
+            "211 0 0 0 misc.test\r\n",
+            "111 20240103120000\r\n",
+            "211 0 101 100 misc.test\r\n",
+        ]);
+        let mut sync = Synchronizer::new(MemoryStore::new());
+        let old = SyncMark {
+            first: 50,
+            last: 100,
+            since: Some(timestamp(1)),
+        };
+        sync.store_mut().save(&group(), old).await.unwrap();
+
+        for _ in 0..2 {
+            let report = sync
+                .sync_group(&mut client, &group(), |_, _| panic!("nothing to fetch"))
+                .await
+                .unwrap();
+            assert_eq!((report.mode, report.fetched), (SyncMode::Incremental, 0));
+            assert_eq!(report.mark, Some(old));
+        }
+        assert_eq!(sync.store().get("misc.test"), Some(old));
+        assert_eq!(
+            sent_commands(&client),
+            ["DATE", "GROUP misc.test", "DATE", "GROUP misc.test"]
+        );
+    }
+}
@@ -0,0 +1,807 @@
+//! Conversation threading using the JWZ algorithm.
+//!
+//! [`Threader`] arranges articles into threads the way most newsreaders do,
+//! following Jamie Zawinski's algorithm
+//! (<https://www.jwz.org/doc/threading.html>): replies hang below the
+//! articles named in their References header, ancestors that were never
+//! seen become placeholders, and placeholders that hold nothing are pruned
+//! away. Threads whose roots share a subject can optionally be gathered
+//! together, and siblings are sorted by date or article number.
+//!
+//! Articles can be added as they are fetched; [`Threader::threads`] builds
+//! the forest from everything seen so far.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::threading::Threader;
+//! use nntp_rs::OverviewEntry;
+//!
+//! fn entry(number: &str, subject: &str, id: &str, references: &str) -> OverviewEntry {
+//!     OverviewEntry {
+//!         fields: [number, subject, "", "", id, references, "", ""]
+//!             .map(String::from)
+//!             .to_vec(),
+//!         ..Default::default()
+//!     }
+//! }
+//!
+//! let mut threader = Threader::new();
+//! threader.add(entry("1", "Hello", "<a@x>", ""));
+//! threader.add(entry("3", "Re: Hello", "<c@x>", "<a@x> <b@x>"));
+//! threader.add(entry("2", "Re: Hello", "<b@x>", "<a@x>"));
+//!
+//! let threads = threader.threads();
+//! assert_eq!(threads.len(), 1);
+//! assert_eq!(threads[0].message.unwrap().number(), Some(1));
+//! let reply = &threads[0].children[0];
+//! assert_eq!(reply.message.unwrap().number(), Some(2));
+//! assert_eq!(reply.children[0].message.unwrap().number(), Some(3));
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use crate::response::{Article, OverviewEntry};
+use crate::utils::{normalize_subject, parse_references};
+use crate::{MessageId, Timestamp};
+
+/// An article that can be placed in a thread.
+///
+/// Implemented for [`OverviewEntry`] and [`Article`], and for references to
+/// either so that entries can be threaded without being moved.
+pub trait Threadable {
+    /// Get the Message-ID of the article
+    fn message_id(&self) -> Option<MessageId>;
+
+    /// Get the Message-IDs of the ancestors of the article, oldest first
+    fn references(&self) -> Vec<MessageId>;
+
+    /// Get the subject, decoded for display
+    fn subject(&self) -> Option<String>;
+
+    /// Get the date the article was posted
+    fn date(&self) -> Option<Timestamp>;
+
+    /// Get the article number within the group
+    fn number(&self) -> Option<u64>;
+}
+
+impl Threadable for OverviewEntry {
+    fn message_id(&self) -> Option<MessageId> {
+        self.message_id()?.trim().parse().ok()
+    }
+
+    fn references(&self) -> Vec<MessageId> {
+        parse_references(self.references())
+    }
+
+    fn subject(&self) -> Option<String> {
+        self.decoded_subject()
+    }
+
+    fn date(&self) -> Option<Timestamp> {
+        Timestamp::from_date_header(self.date()?).ok()
+    }
+
+    fn number(&self) -> Option<u64> {
+        self.number()
+    }
+}
+
+impl Threadable for Article {
+    fn message_id(&self) -> Option<MessageId> {
+        self.article_id()
+            .parse()
+            .ok()
+            .or_else(|| raw_header(self, "Message-ID")?.trim().parse().ok())
+    }
+
+    /// Falls back to the first Message-ID of In-Reply-To when the article
+    /// has no References header, as mail clients often do.
+    fn references(&self) -> Vec<MessageId> {
+        let references = parse_references(raw_header(self, "References").as_deref());
+        if !references.is_empty() {
+            return references;
+        }
+        parse_references(raw_header(self, "In-Reply-To").as_deref())
+            .into_iter()
+            .take(1)
+            .collect()
+    }
+
+    fn subject(&self) -> Option<String> {
+        self.subject()
+    }
+
+    fn date(&self) -> Option<Timestamp> {
+        Timestamp::from_date_header(&raw_header(self, "Date")?).ok()
+    }
+
+    fn number(&self) -> Option<u64> {
+        self.number()
+    }
+}
+
+impl<T: Threadable> Threadable for &T {
+    fn message_id(&self) -> Option<MessageId> {
+        T::message_id(self)
+    }
+
+    fn references(&self) -> Vec<MessageId> {
+        T::references(self)
+    }
+
+    fn subject(&self) -> Option<String> {
+        T::subject(self)
+    }
+
+    fn date(&self) -> Option<Timestamp> {
+        T::date(self)
+    }
+
+    fn number(&self) -> Option<u64> {
+        T::number(self)
+    }
+}
+
+/// Get a header of an article exactly as written, without interpretation.
+fn raw_header(article: &Article, name: &str) -> Option<String> {
+    article
+        .message()?
+        .header_raw(name)
+        .map(|value| value.trim().to_string())
+}
+
+/// How siblings are ordered in the threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ThreadOrder {
+    /// Oldest first by Date header, then by article number
+    #[default]
+    Date,
+    /// Lowest article number first, then by Date header
+    Number,
+}
+
+/// Builds a thread forest from articles, one article at a time.
+///
+/// Placeholders for missing ancestors are kept between calls, so a reply
+/// fetched before its parent is moved below the parent once it arrives.
+/// Subject grouping is off by default, since it can join unrelated threads
+/// that happen to share a subject.
+#[derive(Debug, Clone)]
+pub struct Threader<T> {
+    /// Every article and placeholder seen so far
+    containers: Vec<Container<T>>,
+    /// Container of each Message-ID
+    by_id: HashMap<MessageId, usize>,
+    /// Number of articles added
+    count: usize,
+    /// Whether roots with the same subject are gathered
+    group_by_subject: bool,
+    /// How siblings are ordered
+    order: ThreadOrder,
+}
+
+/// An article, or a placeholder for one that was only referenced.
+#[derive(Debug, Clone)]
+struct Container<T> {
+    item: Option<T>,
+    subject: Option<String>,
+    date: Option<Timestamp>,
+    number: Option<u64>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl<T> Container<T> {
+    fn empty() -> Self {
+        Container {
+            item: None,
+            subject: None,
+            date: None,
+            number: None,
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// A thread of articles, or a subtree of one.
+#[derive(Debug)]
+pub struct Thread<'a, T> {
+    /// The article, or `None` for a placeholder that holds its replies
+    /// together because their common ancestor was never seen
+    pub message: Option<&'a T>,
+    /// Replies to the article, in the order of the [`Threader`]
+    pub children: Vec<Thread<'a, T>>,
+}
+
+impl<'a, T> Thread<'a, T> {
+    /// Check whether this node is a placeholder without an article.
+    pub fn is_placeholder(&self) -> bool {
+        self.message.is_none()
+    }
+
+    /// Get the number of articles in this subtree.
+    pub fn len(&self) -> usize {
+        usize::from(self.message.is_some()) + self.children.iter().map(Thread::len).sum::<usize>()
+    }
+
+    /// Check whether this subtree holds no articles.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the articles of this subtree, parents before replies.
+    pub fn messages(&self) -> impl Iterator<Item = &'a T> + '_ {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || loop {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            if let Some(message) = node.message {
+                return Some(message);
+            }
+        })
+    }
+}
+
+/// A node of the forest while it is being built.
+struct Node {
+    /// Container holding the article, `None` for placeholders
+    index: Option<usize>,
+    children: Vec<Node>,
+}
+
+impl<T> Default for Threader<T> {
+    fn default() -> Self {
+        Threader {
+            containers: Vec::new(),
+            by_id: HashMap::new(),
+            count: 0,
+            group_by_subject: false,
+            order: ThreadOrder::default(),
+        }
+    }
+}
+
+impl<T: Threadable> Threader<T> {
+    /// Create an empty threader that orders threads by date.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gather threads whose roots have the same subject, ignoring `Re:`
+    /// and similar prefixes.
+    pub fn set_group_by_subject(&mut self, enabled: bool) {
+        self.group_by_subject = enabled;
+    }
+
+    /// Choose how siblings are ordered.
+    pub fn set_order(&mut self, order: ThreadOrder) {
+        self.order = order;
+    }
+
+    /// Get the number of articles added.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Check whether no articles have been added.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Add an article.
+    ///
+    /// An article whose Message-ID was already added is threaded on its own
+    /// rather than replacing the first one.
+    pub fn add(&mut self, item: T) {
+        let id = item.message_id();
+        let references = item.references();
+
+        let index = match &id {
+            Some(id) => match self.by_id.get(id) {
+                Some(&index) if self.containers[index].item.is_none() => index,
+                Some(_) => self.push_container(),
+                None => {
+                    let index = self.push_container();
+                    self.by_id.insert(id.clone(), index);
+                    index
+                }
+            },
+            None => self.push_container(),
+        };
+
+        // Link the references together, keeping links made earlier since
+        // they may come from an article that was more complete
+        let mut parent = None;
+        for reference in references {
+            if Some(&reference) == id.as_ref() {
+                continue;
+            }
+            let container = self.container_for(reference);
+            if let Some(parent) = parent {
+                if self.containers[container].parent.is_none()
+                    && !self.would_loop(parent, container)
+                {
+                    self.link(parent, container);
+                }
+            }
+            parent = Some(container);
+        }
+
+        // The article's own References are authoritative for its parent
+        self.unlink(index);
+        if let Some(parent) = parent {
+            if !self.would_loop(parent, index) {
+                self.link(parent, index);
+            }
+        }
+
+        let container = &mut self.containers[index];
+        container.subject = item.subject();
+        container.date = item.date();
+        container.number = item.number();
+        container.item = Some(item);
+        self.count += 1;
+    }
+
+    /// Build the thread forest from the articles added so far.
+    pub fn threads(&self) -> Vec<Thread<'_, T>> {
+        let mut roots = Vec::new();
+        for (index, container) in self.containers.iter().enumerate() {
+            if container.parent.is_some() {
+                continue;
+            }
+            let mut nodes = self.prune(index);
+            // Keep a placeholder at the root if it holds several replies
+            if container.item.is_none() && nodes.len() > 1 {
+                roots.push(Node {
+                    index: None,
+                    children: nodes,
+                });
+            } else {
+                roots.append(&mut nodes);
+            }
+        }
+
+        if self.group_by_subject {
+            roots = self.gather_subjects(roots);
+        }
+        self.sort(&mut roots);
+        roots.into_iter().map(|node| self.to_thread(node)).collect()
+    }
+
+    fn push_container(&mut self) -> usize {
+        self.containers.push(Container::empty());
+        self.containers.len() - 1
+    }
+
+    /// Get the container of a Message-ID, creating a placeholder if needed.
+    fn container_for(&mut self, id: MessageId) -> usize {
+        if let Some(&index) = self.by_id.get(&id) {
+            return index;
+        }
+        let index = self.push_container();
+        self.by_id.insert(id, index);
+        index
+    }
+
+    /// Check whether making `child` a reply to `parent` would create a loop.
+    fn would_loop(&self, parent: usize, child: usize) -> bool {
+        let mut current = Some(parent);
+        while let Some(index) = current {
+            if index == child {
+                return true;
+            }
+            current = self.containers[index].parent;
+        }
+        false
+    }
+
+    fn link(&mut self, parent: usize, child: usize) {
+        self.containers[child].parent = Some(parent);
+        self.containers[parent].children.push(child);
+    }
+
+    fn unlink(&mut self, child: usize) {
+        if let Some(parent) = self.containers[child].parent.take() {
+            self.containers[parent].children.retain(|&c| c != child);
+        }
+    }
+
+    /// Build the subtree of a container, dropping empty placeholders and
+    /// replacing the others by their replies.
+    fn prune(&self, index: usize) -> Vec<Node> {
+        let container = &self.containers[index];
+        let children = container
+            .children
+            .iter()
+            .flat_map(|&child| self.prune(child))
+            .collect();
+        match container.item {
+            Some(_) => vec![Node {
+                index: Some(index),
+                children,
+            }],
+            None => children,
+        }
+    }
+
+    /// Get the normalized subject of a root and whether it was a reply.
+    fn root_subject(&self, node: &Node) -> Option<(String, bool)> {
+        let subject = match node.index {
+            Some(index) => self.containers[index].subject.as_deref()?,
+            None => return self.root_subject(node.children.first()?),
+        };
+        let normalized = normalize_subject(subject);
+        if normalized.is_empty() {
+            return None;
+        }
+        let reply = normalized != subject.trim();
+        Some((normalized.to_lowercase(), reply))
+    }
+
+    /// Merge roots that share a subject.
+    fn gather_subjects(&self, roots: Vec<Node>) -> Vec<Node> {
+        // The most interesting root for each subject: a placeholder, else
+        // an original post rather than a reply
+        let mut table: HashMap<String, usize> = HashMap::new();
+        let subjects: Vec<_> = roots.iter().map(|root| self.root_subject(root)).collect();
+        for (position, subject) in subjects.iter().enumerate() {
+            let Some((subject, reply)) = subject else {
+                continue;
+            };
+            match table.entry(subject.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(position);
+                }
+                Entry::Occupied(mut entry) => {
+                    let current = *entry.get();
+                    let current_reply = subjects[current].as_ref().is_some_and(|(_, r)| *r);
+                    let placeholder = roots[position].index.is_none();
+                    let current_placeholder = roots[current].index.is_none();
+                    if (placeholder && !current_placeholder)
+                        || (placeholder == current_placeholder && current_reply && !reply)
+                    {
+                        entry.insert(position);
+                    }
+                }
+            }
+        }
+
+        let mut merged: Vec<Option<Node>> = roots.into_iter().map(Some).collect();
+        for (position, subject) in subjects.iter().enumerate() {
+            let Some((subject, reply)) = subject else {
+                continue;
+            };
+            let target = table[subject];
+            if target == position {
+                continue;
+            }
+            let Some(node) = merged[position].take() else {
+                continue;
+            };
+            let target_reply = subjects[target].as_ref().is_some_and(|(_, r)| *r);
+            let Some(root) = merged[target].as_mut() else {
+                continue;
+            };
+
+            match (root.index, node.index) {
+                (None, None) => root.children.extend(node.children),
+                (None, Some(_)) => root.children.push(node),
+                (Some(_), Some(_)) if !target_reply && *reply => root.children.push(node),
+                _ => {
+                    let previous = std::mem::replace(
+                        root,
+                        Node {
+                            index: None,
+                            children: Vec::new(),
+                        },
+                    );
+                    root.children = vec![previous, node];
+                }
+            }
+        }
+        merged.into_iter().flatten().collect()
+    }
+
+    /// Sort siblings at every level.
+    fn sort(&self, nodes: &mut [Node]) {
+        for node in nodes.iter_mut() {
+            self.sort(&mut node.children);
+        }
+        nodes.sort_by(|a, b| self.compare(a, b));
+    }
+
+    /// Compare two sorted nodes; a placeholder sorts as its first reply.
+    fn compare(&self, a: &Node, b: &Node) -> Ordering {
+        let (a_date, a_number) = self.sort_key(a);
+        let (b_date, b_number) = self.sort_key(b);
+        match self.order {
+            ThreadOrder::Date => {
+                missing_last(a_date, b_date).then_with(|| missing_last(a_number, b_number))
+            }
+            ThreadOrder::Number => {
+                missing_last(a_number, b_number).then_with(|| missing_last(a_date, b_date))
+            }
+        }
+    }
+
+    fn sort_key(&self, node: &Node) -> (Option<Timestamp>, Option<u64>) {
+        match node.index {
+            Some(index) => (self.containers[index].date, self.containers[index].number),
+            None => node
+                .children
+                .first()
+                .map_or((None, None), |child| self.sort_key(child)),
+        }
+    }
+
+    fn to_thread(&self, node: Node) -> Thread<'_, T> {
+        Thread {
+            message: node
+                .index
+                .and_then(|index| self.containers[index].item.as_ref()),
+            children: node
+                .children
+                .into_iter()
+                .map(|child| self.to_thread(child))
+                .collect(),
+        }
+    }
+}
+
+/// Compare optional keys, putting missing ones after present ones.
+fn missing_last<K: Ord>(a: Option<K>, b: Option<K>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl<T: Threadable> Extend<T> for Threader<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T: Threadable> FromIterator<T> for Threader<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut threader = Threader::new();
+        threader.extend(iter);
+        threader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use super::*;
+
+    /// A minimal article for tests: number, subject, id and references.
+    #[derive(Debug, PartialEq)]
+    struct Post {
+        number: u64,
+        subject: &'static str,
+        id: &'static str,
+        references: &'static str,
+        date: Option<Timestamp>,
+    }
+
+    impl Threadable for Post {
+        fn message_id(&self) -> Option<MessageId> {
+            self.id.parse().ok()
+        }
+
+        fn references(&self) -> Vec<MessageId> {
+            parse_references(Some(self.references))
+        }
+
+        fn subject(&self) -> Option<String> {
+            Some(self.subject.to_string())
+        }
+
+        fn date(&self) -> Option<Timestamp> {
+            self.date
+        }
+
+        fn number(&self) -> Option<u64> {
+            Some(self.number)
+        }
+    }
+
+    fn post(
+        number: u64,
+        subject: &'static str,
+        id: &'static str,
+        references: &'static str,
+    ) -> Post {
+        Post {
+            number,
+            subject,
+            id,
+            references,
+            date: None,
+        }
+    }
+
+    /// Render a forest as `number(children)`, with `*` for placeholders.
+    fn shape<P: Borrow<Post>>(threads: &[Thread<'_, P>]) -> String {
+        threads
+            .iter()
+            .map(|thread| {
+                let label = thread
+                    .message
+                    .map_or("*".to_string(), |post| post.borrow().number.to_string());
+                if thread.children.is_empty() {
+                    label
+                } else {
+                    format!("{label}({})", shape(&thread.children))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_missing_parent_is_promoted() {
+        let threader: Threader<_> = [
+            post(1, "A", "<a@x>", ""),
+            post(3, "Re: A", "<c@x>", "<a@x> <b@x>"),
+            post(4, "Re: A", "<d@x>", "<a@x> <b@x> <c@x>"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(shape(&threader.threads()), "1(3(4))");
+        assert_eq!(threader.len(), 3);
+    }
+
+    #[test]
+    fn test_placeholder_roots() {
+        // Siblings whose root is missing stay together under a placeholder
+        let threader: Threader<_> = [
+            post(2, "Re: A", "<b@x>", "<a@x>"),
+            post(3, "Re: A", "<c@x>", "<a@x>"),
+            post(5, "Re: E", "<f@x>", "<e@x>"),
+        ]
+        .into_iter()
+        .collect();
+        let threads = threader.threads();
+        assert_eq!(shape(&threads), "*(2 3) 5");
+        assert!(threads[0].is_placeholder());
+        assert_eq!(threads[0].len(), 2);
+        let numbers: Vec<_> = threads[0].messages().map(|post| post.number).collect();
+        assert_eq!(numbers, [2, 3]);
+    }
+
+    #[test]
+    fn test_incremental_adds() {
+        let mut threader = Threader::new();
+        threader.add(post(3, "Re: A", "<c@x>", "<a@x> <b@x>"));
+        assert_eq!(shape(&threader.threads()), "3");
+
+        threader.add(post(1, "A", "<a@x>", ""));
+        assert_eq!(shape(&threader.threads()), "1(3)");
+
+        // The parent guessed from the reply's References is replaced by
+        // what the article itself says
+        threader.add(post(2, "B", "<b@x>", ""));
+        assert_eq!(shape(&threader.threads()), "1 2(3)");
+    }
+
+    #[test]
+    fn test_loops_and_duplicates() {
+        let threader: Threader<_> = [
+            post(1, "A", "<a@x>", "<b@x>"),
+            post(2, "B", "<b@x>", "<a@x>"),
+            post(3, "C", "<c@x>", "<c@x>"),
+            post(4, "C", "<c@x>", ""),
+        ]
+        .into_iter()
+        .collect();
+        let threads = threader.threads();
+        assert_eq!(shape(&threads), "2(1) 3 4");
+        assert_eq!(threads.iter().map(Thread::len).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_subject_grouping() {
+        let posts = [
+            post(1, "Re: Hello", "<b@x>", "<missing@x>"),
+            post(2, "Hello", "<a@x>", ""),
+            post(3, "Re: hello", "<c@x>", ""),
+            post(4, "Other", "<d@x>", ""),
+            post(5, "Other", "<e@x>", ""),
+            post(6, "Re: Hi", "<g@x>", "<f@x>"),
+            post(7, "Re: Hi", "<h@x>", "<f@x>"),
+            post(8, "Hi", "<i@x>", ""),
+        ];
+        let mut threader: Threader<_> = posts.iter().collect();
+        assert_eq!(shape(&threader.threads()), "1 2 3 4 5 *(6 7) 8");
+
+        threader.set_group_by_subject(true);
+        assert_eq!(shape(&threader.threads()), "2(1 3) *(4 5) *(6 7 8)");
+    }
+
+    #[test]
+    fn test_sort_orders() {
+        let date = |day| Timestamp::from_utc(2024, 1, day, 0, 0, 0).ok();
+        let mut threader = Threader::new();
+        for (number, id, day) in [
+            (1, "<a@x>", Some(3)),
+            (2, "<b@x>", Some(1)),
+            (3, "<c@x>", None),
+            (4, "<d@x>", Some(2)),
+        ] {
+            threader.add(Post {
+                date: day.and_then(date),
+                ..post(number, "", id, "")
+            });
+        }
+        assert_eq!(shape(&threader.threads()), "2 4 1 3");
+        threader.set_order(ThreadOrder::Number);
+        assert_eq!(shape(&threader.threads()), "1 2 3 4");
+    }
+
+    #[test]
+    fn test_overview_and_article_sources() {
+        let entry = OverviewEntry {
+            fields: [
+                "7",
+                "=?utf-8?Q?Re:_Caf=C3=A9?=",
+                "",
+                "Mon, 1 Jan 2024 10:00:00 +0100",
+                "<r@x>",
+                "<p@x>",
+                "",
+                "",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(Threadable::message_id(&entry).unwrap(), "<r@x>");
+        assert_eq!(Threadable::references(&entry), ["<p@x>"]);
+        assert_eq!(Threadable::subject(&entry).as_deref(), Some("Re: Café"));
+        assert_eq!(
+            Threadable::date(&entry),
+            Timestamp::from_utc(2024, 1, 1, 9, 0, 0).ok()
+        );
+
+        let article = Article::new(
+            Some(8),
+            "<q@x>".to_string(),
+            b"Message-ID: <q@x>\r\nReferences: <o@x>\r\n <p@x>\r\nSubject: Re: Cafe\r\n\
+              Date: 6 Oct 98 04:38 EST\r\n\r\nBody\r\n"
+                .to_vec(),
+        );
+        assert_eq!(Threadable::references(&article), ["<o@x>", "<p@x>"]);
+        assert_eq!(
+            Threadable::date(&article).unwrap().to_string(),
+            "19981006093800"
+        );
+
+        let reply = Article::new(
+            None,
+            "0".to_string(),
+            b"Message-ID: <s@x>\r\nIn-Reply-To: <q@x> (Jane)\r\n\r\nBody\r\n".to_vec(),
+        );
+        assert_eq!(Threadable::message_id(&reply).unwrap(), "<s@x>");
+        assert_eq!(Threadable::references(&reply), ["<q@x>"]);
+
+        let mut threader = Threader::new();
+        threader.add(reply);
+        threader.add(article);
+        let threads = threader.threads();
+        let ids: Vec<_> = threads[0].messages().map(Article::article_id).collect();
+        assert_eq!(ids, ["<q@x>", "0"]);
+    }
+}
@@ -0,0 +1,483 @@
+//! UTC timestamps for DATE, NEWGROUPS, NEWNEWS and LIST ACTIVE.TIMES.
+//!
+//! NNTP exchanges times in UTC with one second resolution: the DATE reply
+//! and NEWGROUPS/NEWNEWS arguments as `yyyymmddhhmmss` digits (RFC 3977
+//! Section 7.1 and 7.3), and LIST ACTIVE.TIMES as seconds since the Unix
+//! epoch. [`Timestamp`] converts between these forms without needing a
+//! date library, and also reads the Date header of articles.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::Timestamp;
+//! use std::time::Duration;
+//!
+//! let since = Timestamp::from_utc(2024, 2, 29, 23, 59, 30).unwrap();
+//! assert_eq!(since.to_string(), "20240229235930");
+//! assert_eq!((since + Duration::from_secs(60)).to_string(), "20240301000030");
+//! assert_eq!("20240229235930".parse::<Timestamp>().unwrap(), since);
+//! ```
+
+use std::fmt;
+use std::ops::{Add, Range, Sub};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Error, Result};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A point in time in UTC, with one second resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    /// Seconds since the Unix epoch
+    secs: i64,
+}
+
+impl Timestamp {
+    /// Create a timestamp from seconds since the Unix epoch.
+    pub fn from_unix(secs: i64) -> Self {
+        Timestamp { secs }
+    }
+
+    /// Get the seconds since the Unix epoch.
+    pub fn unix(&self) -> i64 {
+        self.secs
+    }
+
+    /// Get the current time of the local clock.
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Create a timestamp from a UTC date and time.
+    ///
+    /// Fails unless the year is within 0..=9999 and the other fields form a
+    /// valid date and time.
+    pub fn from_utc(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Result<Self> {
+        let days = days_from_civil(year.into(), month, day);
+        let valid = (0..=9999).contains(&year)
+            && (1..=12).contains(&month)
+            && civil_from_days(days) == (year.into(), month, day)
+            && hour < 24
+            && minute < 60
+            && second < 60;
+        if !valid {
+            return Err(Error::Parse(format!(
+                "Invalid date: {year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02}"
+            )));
+        }
+        let secs = days * SECONDS_PER_DAY + i64::from(hour * 3600 + minute * 60 + second);
+        Ok(Timestamp { secs })
+    }
+
+    /// Split the timestamp into UTC year, month, day, hour, minute and second.
+    pub fn to_utc(&self) -> (i32, u32, u32, u32, u32, u32) {
+        let (year, month, day) = civil_from_days(self.secs.div_euclid(SECONDS_PER_DAY));
+        let secs = self.secs.rem_euclid(SECONDS_PER_DAY) as u32;
+        (
+            year as i32,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+        )
+    }
+
+    /// Get the number of seconds from `earlier` to this timestamp, negative
+    /// if `earlier` is actually later.
+    pub fn offset_from(&self, earlier: Timestamp) -> i64 {
+        self.secs - earlier.secs
+    }
+
+    /// Format as the `yyyymmdd hhmmss` arguments of NEWGROUPS and NEWNEWS.
+    pub(crate) fn to_command_arguments(self) -> String {
+        let (year, month, day, hour, minute, second) = self.to_utc();
+        format!("{year:04}{month:02}{day:02} {hour:02}{minute:02}{second:02}")
+    }
+
+    /// Parse the value of a Date header, as found in articles and overview
+    /// data.
+    ///
+    /// Besides the RFC 5322 form `Tue, 6 Oct 1998 04:38:40 -0500`, this
+    /// accepts the variants of old Usenet articles: the RFC 850 form
+    /// `Tuesday, 06-Oct-98 04:38:40 GMT`, the asctime form
+    /// `Tue Oct  6 04:38:40 1998`, two-digit years, missing seconds, US and
+    /// common European zone names, and comments. Unknown zone names are
+    /// taken as UTC, as RFC 5322 suggests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::Timestamp;
+    ///
+    /// let date = Timestamp::from_date_header("Tuesday, 06-Oct-98 04:38:40 EST").unwrap();
+    /// assert_eq!(date.to_string(), "19981006093840");
+    /// ```
+    pub fn from_date_header(value: &str) -> Result<Self> {
+        let error = || Error::Parse(format!("Invalid date: {value}"));
+
+        // Drop comments such as "(EST)" and treat commas as spaces
+        let mut text = String::with_capacity(value.len());
+        let mut depth = 0u32;
+        for c in value.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ if depth > 0 => {}
+                ',' => text.push(' '),
+                c => text.push(c),
+            }
+        }
+
+        let mut numbers = Vec::new();
+        let mut month = None;
+        let mut time = None;
+        let mut zone = None;
+        for token in text.split_whitespace() {
+            if token.contains(':') {
+                time = Some(parse_time(token).ok_or_else(error)?);
+            } else if let Some(offset) = parse_numeric_zone(token) {
+                zone = Some(offset);
+            } else {
+                // RFC 850 and ISO dates separate their parts with dashes
+                for part in token.split('-').filter(|part| !part.is_empty()) {
+                    if part.bytes().all(|b| b.is_ascii_digit()) {
+                        numbers.push(part);
+                    } else if let Some(m) = month_from_name(part) {
+                        month = Some(m);
+                    } else if zone.is_none() {
+                        // Weekdays and unknown words are skipped
+                        zone = zone_from_name(part);
+                    }
+                }
+            }
+        }
+
+        let number = |s: &str| s.parse::<u32>().map_err(|_| error());
+        let (year, month, day) = match (month, numbers.as_slice()) {
+            (Some(month), [day, year]) => (*year, month, number(day)?),
+            (None, [year, month, day]) if year.len() == 4 => (*year, number(month)?, number(day)?),
+            _ => return Err(error()),
+        };
+        let year = match (year.len(), number(year)?) {
+            (2, year) if year < 50 => year + 2000,
+            (2 | 3, year) => year + 1900,
+            (_, year) => year,
+        };
+        let (hour, minute, second) = time.unwrap_or_default();
+        // A leap second is folded into the second before it
+        let timestamp = Timestamp::from_utc(year as i32, month, day, hour, minute, second.min(59))
+            .map_err(|_| error())?;
+        Ok(Timestamp {
+            secs: timestamp.secs - zone.unwrap_or(0),
+        })
+    }
+}
+
+/// Parse a `hh:mm` or `hh:mm:ss` time of day.
+fn parse_time(token: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = token.split(':').map(|part| {
+        if (1..=2).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit()) {
+            part.parse().ok()
+        } else {
+            None
+        }
+    });
+    let hour = parts.next()??;
+    let minute = parts.next()??;
+    let second = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((hour, minute, second))
+}
+
+/// Parse a `+hhmm` or `-hhmm` zone into its offset from UTC in seconds.
+fn parse_numeric_zone(token: &str) -> Option<i64> {
+    let (sign, digits) = match token.as_bytes().first()? {
+        b'+' => (1, &token[1..]),
+        b'-' => (-1, &token[1..]),
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Get the offset from UTC in seconds of a zone name.
+fn zone_from_name(name: &str) -> Option<i64> {
+    let hours = match name.to_ascii_uppercase().as_str() {
+        "UT" | "UTC" | "GMT" | "Z" => 0,
+        "EST" => -5,
+        "EDT" => -4,
+        "CST" => -6,
+        "CDT" => -5,
+        "MST" => -7,
+        "MDT" => -6,
+        "PST" => -8,
+        "PDT" => -7,
+        "BST" | "CET" | "MET" => 1,
+        "CEST" | "MEST" | "EET" => 2,
+        "EEST" => 3,
+        "JST" => 9,
+        _ => return None,
+    };
+    Some(hours * 3600)
+}
+
+/// Get the number of a month from its English name or abbreviation.
+fn month_from_name(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    if name.len() < 3 || !name.is_char_boundary(3) {
+        return None;
+    }
+    let prefix = name[..3].to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|month| *month == prefix)
+        .map(|i| i as u32 + 1)
+}
+
+/// Count the days from 1970-01-01 to a date of the proleptic Gregorian
+/// calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Count years from March, so that the leap day ends the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Get the date of the day a number of days after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl fmt::Display for Timestamp {
+    /// Formats as `yyyymmddhhmmss`, the form of the DATE reply.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day, hour, minute, second) = self.to_utc();
+        write!(
+            f,
+            "{year:04}{month:02}{day:02}{hour:02}{minute:02}{second:02}"
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    /// Parses the `yyyymmddhhmmss` form of the DATE reply.
+    fn from_str(s: &str) -> Result<Self> {
+        if s.len() != 14 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::Parse(format!("Invalid timestamp: {s}")));
+        }
+        let field = |range: Range<usize>| s[range].parse::<u32>().unwrap_or_default();
+        Timestamp::from_utc(
+            field(0..4) as i32,
+            field(4..6),
+            field(6..8),
+            field(8..10),
+            field(10..12),
+            field(12..14),
+        )
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            // Round down, so that a time before the epoch does not move later
+            Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+        };
+        Timestamp { secs }
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        let secs = Duration::from_secs(timestamp.secs.unsigned_abs());
+        if timestamp.secs >= 0 {
+            UNIX_EPOCH + secs
+        } else {
+            UNIX_EPOCH - secs
+        }
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: Duration) -> Timestamp {
+        let secs = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+        Timestamp {
+            secs: self.secs.saturating_add(secs),
+        }
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, duration: Duration) -> Timestamp {
+        let secs = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+        Timestamp {
+            secs: self.secs.saturating_sub(secs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utc_conversion() {
+        assert_eq!(Timestamp::from_unix(0).to_string(), "19700101000000");
+        assert_eq!(
+            Timestamp::from_utc(2021, 1, 1, 0, 0, 0).unwrap().unix(),
+            1_609_459_200
+        );
+        assert_eq!(
+            Timestamp::from_unix(951_825_600).to_utc(),
+            (2000, 2, 29, 12, 0, 0)
+        );
+        assert_eq!(
+            Timestamp::from_unix(-1).to_utc(),
+            (1969, 12, 31, 23, 59, 59)
+        );
+
+        // Every day of a few years around leap years survives a round trip
+        let start = Timestamp::from_utc(1899, 12, 25, 0, 0, 0).unwrap();
+        for day in 0..(6 * 366) {
+            let timestamp = start + Duration::from_secs(day * 86_400 + 3_723);
+            let (y, mo, d, h, mi, s) = timestamp.to_utc();
+            assert_eq!((h, mi, s), (1, 2, 3));
+            assert_eq!(Timestamp::from_utc(y, mo, d, h, mi, s).unwrap(), timestamp);
+        }
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        assert!(Timestamp::from_utc(2023, 2, 29, 0, 0, 0).is_err());
+        assert!(Timestamp::from_utc(1900, 2, 29, 0, 0, 0).is_err());
+        assert!(Timestamp::from_utc(2000, 2, 29, 0, 0, 0).is_ok());
+        assert!(Timestamp::from_utc(2024, 4, 31, 0, 0, 0).is_err());
+        assert!(Timestamp::from_utc(2024, 13, 1, 0, 0, 0).is_err());
+        assert!(Timestamp::from_utc(2024, 1, 0, 0, 0, 0).is_err());
+        assert!(Timestamp::from_utc(2024, 1, 1, 24, 0, 0).is_err());
+        assert!(Timestamp::from_utc(2024, 1, 1, 0, 60, 0).is_err());
+        assert!(Timestamp::from_utc(10_000, 1, 1, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_reply() {
+        let timestamp: Timestamp = "20231106123456".parse().unwrap();
+        assert_eq!(timestamp.to_utc(), (2023, 11, 6, 12, 34, 56));
+        assert_eq!(timestamp.to_string(), "20231106123456");
+        assert_eq!(timestamp.to_command_arguments(), "20231106 123456");
+
+        for invalid in [
+            "2023110612345",
+            "202311061234567",
+            "2023-11-06 1234",
+            "20231306123456",
+        ] {
+            assert!(invalid.parse::<Timestamp>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_date_header_forms() {
+        let expected = Timestamp::from_utc(1998, 10, 6, 9, 38, 40).unwrap();
+        for value in [
+            "Tue, 6 Oct 1998 04:38:40 -0500",
+            "6 Oct 1998 04:38:40 -0500",
+            "Tue, 06 Oct 1998 09:38:40 GMT",
+            "Tue, 6 Oct 1998 04:38:40 -0500 (EST)",
+            "Tuesday, 06-Oct-98 04:38:40 EST",
+            "Tue Oct  6 09:38:40 1998",
+            "Tue Oct  6 09:38:40 UTC 1998",
+            "6 OCT 98 11:38:40 CEST",
+            "1998-10-06 09:38:40",
+            "Tue, 6 Oct 1998 09:38:40 +0000 (GMT)",
+            "Tue, 6 Oct 1998 09:38:40 X",
+        ] {
+            assert_eq!(
+                Timestamp::from_date_header(value).unwrap(),
+                expected,
+                "{value}"
+            );
+        }
+
+        // Missing seconds, a leap second, and a year after 2049
+        let date = Timestamp::from_date_header("1 Jan 2001 0:5 +0100").unwrap();
+        assert_eq!(date.to_utc(), (2000, 12, 31, 23, 5, 0));
+        let date = Timestamp::from_date_header("31 Dec 1998 23:59:60 GMT").unwrap();
+        assert_eq!(date.to_utc(), (1998, 12, 31, 23, 59, 59));
+        let date = Timestamp::from_date_header("1 Feb 03 12:00:00 GMT").unwrap();
+        assert_eq!(date.to_utc().0, 2003);
+    }
+
+    #[test]
+    fn test_invalid_date_headers() {
+        for value in [
+            "",
+            "yesterday",
+            "Tue, 6 1998 04:38:40 GMT",
+            "31 Feb 1998 04:38:40 GMT",
+            "6 Oct 1998 4:38:x GMT",
+            "6 Oct 1998 25:00:00 GMT",
+        ] {
+            assert!(Timestamp::from_date_header(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_system_time_and_offsets() {
+        let now = SystemTime::now();
+        let timestamp = Timestamp::from(now);
+        let back = SystemTime::from(timestamp);
+        assert!(now.duration_since(back).unwrap() < Duration::from_secs(1));
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(Timestamp::from(before_epoch).unix(), -2);
+
+        let later = timestamp + Duration::from_secs(90);
+        assert_eq!(later.offset_from(timestamp), 90);
+        assert_eq!(timestamp.offset_from(later), -90);
+        assert_eq!(later - Duration::from_secs(90), timestamp);
+    }
+}
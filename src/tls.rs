@@ -0,0 +1,225 @@
+//! TLS support using rustls.
+//!
+//! NNTP connections can be secured in two ways:
+//!
+//! - Implicit TLS (NNTPS, usually port 563), where the TLS handshake happens
+//!   right after connecting: [`NntpClient::connect_tls`]
+//! - The STARTTLS command (RFC 4642), which upgrades an established plain
+//!   connection: [`NntpClient::starttls`]
+//!
+//! Both are configured with a [`TlsConfig`]. TLS runs over any
+//! [`AsyncStream`], so it works with every supported runtime.
+//!
+//! The [`rustls`] crate is re-exported for building root certificate stores
+//! and loading certificates.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "tokio-runtime")]
+//! # {
+//! use nntp_rs::net_client::NntpClient;
+//! use nntp_rs::runtime::TokioStream;
+//! use nntp_rs::tls::TlsConfig;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = TlsConfig::new();
+//! let mut client = NntpClient::<TokioStream>::connect_tls("news.example.com:563", &config).await?;
+//! client.quit().await?;
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+//!
+//! [`NntpClient::connect_tls`]: crate::net_client::NntpClient::connect_tls
+//! [`NntpClient::starttls`]: crate::net_client::NntpClient::starttls
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+pub use rustls;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore};
+
+use crate::runtime::AsyncStream;
+use crate::{Error, Result};
+
+/// Size of the buffer used for reading TLS records from the stream
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// TLS settings for a connection.
+///
+/// All fields are public, so a configuration can be written as a struct
+/// literal based on [`TlsConfig::new`]:
+///
+/// ```
+/// use nntp_rs::tls::TlsConfig;
+///
+/// let config = TlsConfig {
+///     server_name: Some("news.example.com".to_string()),
+///     ..TlsConfig::new()
+/// };
+/// ```
+#[derive(Debug)]
+pub struct TlsConfig {
+    /// Certificates trusted to sign the server certificate.
+    ///
+    /// Defaults to the Mozilla root program bundled by `webpki-roots`.
+    pub root_certificates: RootCertStore,
+    /// Certificate chain and private key presented for TLS client
+    /// authentication.
+    pub client_certificate: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+    /// Name sent with SNI and checked against the server certificate.
+    ///
+    /// Defaults to the host part of the address the client connected to.
+    pub server_name: Option<String>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self::with_root_certificates(RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        })
+    }
+}
+
+impl TlsConfig {
+    /// Create a configuration trusting the bundled web PKI roots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration trusting only the given root certificates,
+    /// e.g. a private CA or a self-signed server certificate.
+    pub fn with_root_certificates(root_certificates: RootCertStore) -> Self {
+        Self {
+            root_certificates,
+            client_certificate: None,
+            server_name: None,
+        }
+    }
+
+    /// Start a client session with the server at `host`.
+    pub(crate) fn session(&self, host: &str) -> Result<TlsSession> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| Error::Connection(format!("Invalid TLS configuration: {e}")))?
+            .with_root_certificates(self.root_certificates.clone());
+        let config = match &self.client_certificate {
+            Some((chain, key)) => builder
+                .with_client_auth_cert(chain.clone(), key.clone_key())
+                .map_err(|e| Error::Connection(format!("Invalid client certificate: {e}")))?,
+            None => builder.with_no_client_auth(),
+        };
+
+        let name = self.server_name.as_deref().unwrap_or(host);
+        let server_name = ServerName::try_from(name.to_string())
+            .map_err(|e| Error::Connection(format!("Invalid TLS server name {name:?}: {e}")))?;
+        let connection = ClientConnection::new(Arc::new(config), server_name)
+            .map_err(|e| Error::Connection(format!("Failed to start TLS: {e}")))?;
+
+        Ok(TlsSession {
+            connection,
+            received: Vec::new(),
+            read_buffer: vec![0; READ_BUFFER_SIZE].into_boxed_slice(),
+        })
+    }
+}
+
+/// A rustls client connection driven over an [`AsyncStream`].
+pub(crate) struct TlsSession {
+    connection: ClientConnection,
+    /// TLS records read from the stream but not yet accepted by rustls
+    received: Vec<u8>,
+    read_buffer: Box<[u8]>,
+}
+
+impl TlsSession {
+    /// Perform the TLS handshake.
+    pub(crate) async fn handshake<S: AsyncStream>(&mut self, stream: &mut S) -> io::Result<()> {
+        while self.connection.is_handshaking() {
+            self.flush(stream).await?;
+            if self.connection.wants_read() && self.receive(stream).await? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed during TLS handshake",
+                ));
+            }
+        }
+        self.flush(stream).await
+    }
+
+    /// Read decrypted data, returning 0 at the end of the stream.
+    pub(crate) async fn read<S: AsyncStream>(
+        &mut self,
+        stream: &mut S,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        loop {
+            match self.connection.reader().read(buf) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            if self.receive(stream).await? == 0 {
+                return Ok(0);
+            }
+        }
+    }
+
+    /// Encrypt and send data.
+    pub(crate) async fn write_all<S: AsyncStream>(
+        &mut self,
+        stream: &mut S,
+        buf: &[u8],
+    ) -> io::Result<()> {
+        self.connection.writer().write_all(buf)?;
+        self.flush(stream).await
+    }
+
+    /// Send a close_notify alert.
+    pub(crate) async fn close<S: AsyncStream>(&mut self, stream: &mut S) -> io::Result<()> {
+        self.connection.send_close_notify();
+        self.flush(stream).await
+    }
+
+    /// Write all pending TLS records to the stream.
+    async fn flush<S: AsyncStream>(&mut self, stream: &mut S) -> io::Result<()> {
+        let mut records = Vec::new();
+        while self.connection.wants_write() {
+            self.connection.write_tls(&mut records)?;
+        }
+        if !records.is_empty() {
+            stream.write_all(&records).await?;
+        }
+        Ok(())
+    }
+
+    /// Feed TLS records to rustls, reading from the stream when none are
+    /// buffered. Returns the number of bytes accepted, 0 at the end of the
+    /// stream.
+    async fn receive<S: AsyncStream>(&mut self, stream: &mut S) -> io::Result<usize> {
+        if self.received.is_empty() {
+            let n = stream.read(&mut self.read_buffer).await?;
+            if n == 0 {
+                return Ok(0);
+            }
+            self.received.extend_from_slice(&self.read_buffer[..n]);
+        }
+
+        let mut records = &self.received[..];
+        let accepted = self.connection.read_tls(&mut records)?;
+        self.received.drain(..accepted);
+
+        if let Err(e) = self.connection.process_new_packets() {
+            // Let the server know why the connection is being dropped
+            let _ = self.flush(stream).await;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        // Handshake messages and key updates may need an answer
+        self.flush(stream).await?;
+        Ok(accepted)
+    }
+}
@@ -0,0 +1,136 @@
+//! Byte transport of [`NntpClient`](crate::net_client::NntpClient).
+//!
+//! The transport owns the connected [`AsyncStream`] and applies the layers
+//! that can be negotiated on an NNTP connection, such as TLS and
+//! compression, to all data read and written.
+
+use std::io;
+
+#[cfg(feature = "compression")]
+use crate::compression::{CompressionStats, DeflateSession};
+use crate::runtime::AsyncStream;
+#[cfg(feature = "rustls")]
+use crate::tls::TlsSession;
+
+/// Size of the buffer for compressed data read from the link
+#[cfg(feature = "compression")]
+const COMPRESSED_READ_SIZE: usize = 8 * 1024;
+
+/// A connected stream together with its negotiated layers.
+pub(crate) struct Transport<S: AsyncStream> {
+    link: Link<S>,
+    #[cfg(feature = "compression")]
+    deflate: Option<Box<DeflateSession>>,
+}
+
+/// The stream with its optional TLS layer, below compression
+struct Link<S: AsyncStream> {
+    stream: S,
+    #[cfg(feature = "rustls")]
+    tls: Option<Box<TlsSession>>,
+}
+
+impl<S: AsyncStream> Transport<S> {
+    /// Wrap a plain connected stream.
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            link: Link {
+                stream,
+                #[cfg(feature = "rustls")]
+                tls: None,
+            },
+            #[cfg(feature = "compression")]
+            deflate: None,
+        }
+    }
+
+    /// Get the underlying stream.
+    #[cfg(test)]
+    pub(crate) fn get_ref(&self) -> &S {
+        &self.link.stream
+    }
+
+    /// Read data, returning 0 at the end of the stream.
+    pub(crate) async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(feature = "compression")]
+        if let Some(deflate) = &mut self.deflate {
+            let mut compressed = [0u8; COMPRESSED_READ_SIZE];
+            loop {
+                let n = deflate.decompress(buf)?;
+                if n > 0 || buf.is_empty() || deflate.is_finished() {
+                    return Ok(n);
+                }
+                let n = self.link.read(&mut compressed).await?;
+                if n == 0 {
+                    return Ok(0);
+                }
+                deflate.feed(&compressed[..n]);
+            }
+        }
+        self.link.read(buf).await
+    }
+
+    /// Write all data.
+    pub(crate) async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "compression")]
+        if let Some(deflate) = &mut self.deflate {
+            let compressed = deflate.compress(buf)?;
+            return self.link.write_all(&compressed).await;
+        }
+        self.link.write_all(buf).await
+    }
+
+    /// Close the layers and shut down the stream.
+    pub(crate) async fn shutdown(&mut self) -> io::Result<()> {
+        #[cfg(feature = "rustls")]
+        if let Some(tls) = &mut self.link.tls {
+            tls.close(&mut self.link.stream).await?;
+        }
+        self.link.stream.shutdown().await
+    }
+
+    /// Perform a TLS handshake and encrypt all further data.
+    #[cfg(feature = "rustls")]
+    pub(crate) async fn start_tls(&mut self, mut session: TlsSession) -> io::Result<()> {
+        session.handshake(&mut self.link.stream).await?;
+        self.link.tls = Some(Box::new(session));
+        Ok(())
+    }
+
+    /// Check if TLS is active.
+    #[cfg(feature = "rustls")]
+    pub(crate) fn is_tls(&self) -> bool {
+        self.link.tls.is_some()
+    }
+
+    /// Compress all further data. `buffered` is data already received
+    /// after compression was activated.
+    #[cfg(feature = "compression")]
+    pub(crate) fn start_compression(&mut self, buffered: &[u8]) {
+        self.deflate = Some(Box::new(DeflateSession::new(buffered)));
+    }
+
+    /// Byte counts of the compression layer, if active.
+    #[cfg(feature = "compression")]
+    pub(crate) fn compression_stats(&self) -> Option<CompressionStats> {
+        self.deflate.as_ref().map(|deflate| deflate.stats())
+    }
+}
+
+impl<S: AsyncStream> Link<S> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(feature = "rustls")]
+        if let Some(tls) = &mut self.tls {
+            return tls.read(&mut self.stream, buf).await;
+        }
+        self.stream.read(buf).await
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "rustls")]
+        if let Some(tls) = &mut self.tls {
+            return tls.write_all(&mut self.stream, buf).await;
+        }
+        self.stream.write_all(buf).await
+    }
+}
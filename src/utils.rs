@@ -1,5 +1,19 @@
 //! Utility functions for NNTP article processing.
 
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+
+use crate::ident::MessageId;
+use crate::response::decode_text_with_encoding;
+
+/// Base64 engine for encoded words, which often lack their padding.
+const ENCODED_WORD_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
 /// Parse a References header into a list of Message-IDs (oldest first).
 ///
 /// The References header contains space-separated Message-IDs representing
@@ -17,16 +31,15 @@
 /// assert!(parse_references(None).is_empty());
 /// assert!(parse_references(Some("")).is_empty());
 ///
-/// // Invalid entries (not wrapped in <>) are filtered out
-/// let refs = parse_references(Some("<valid@x.com> invalid <also-valid@y.com>"));
+/// // Malformed entries are filtered out
+/// let refs = parse_references(Some("<valid@x.com> invalid <in>valid> <also-valid@y.com>"));
 /// assert_eq!(refs, vec!["<valid@x.com>", "<also-valid@y.com>"]);
 /// ```
-pub fn parse_references(references: Option<&str>) -> Vec<String> {
+pub fn parse_references(references: Option<&str>) -> Vec<MessageId> {
     references
         .map(|refs| {
             refs.split_whitespace()
-                .filter(|s| s.starts_with('<') && s.ends_with('>'))
-                .map(|s| s.to_string())
+                .filter_map(|s| MessageId::new(s).ok())
                 .collect()
         })
         .unwrap_or_default()
@@ -34,8 +47,8 @@ pub fn parse_references(references: Option<&str>) -> Vec<String> {
 
 /// Normalize a subject line by removing Re:, Fwd:, etc. prefixes.
 ///
-/// This is useful for grouping articles by their base subject when
-/// implementing threading algorithms.
+/// This is useful for grouping articles by their base subject, as the
+/// [`threading`](crate::threading) module does.
 ///
 /// # Example
 ///
@@ -89,6 +102,125 @@ pub fn normalize_subject(subject: &str) -> String {
     normalized
 }
 
+/// Decode RFC 2047 encoded words in a header value.
+///
+/// Encoded words such as `=?utf-8?B?...?=` and `=?iso-8859-1?Q?...?=` are
+/// replaced by the text they encode. Whitespace between two adjacent
+/// encoded words is dropped, so a long value split across several words
+/// reads as one; whitespace next to plain text is kept. Adjacent words in
+/// the same charset are joined before decoding, which keeps multi-byte
+/// characters that straddle two words intact.
+///
+/// Charsets are looked up through `encoding_rs`. An unknown or mislabelled
+/// charset falls back to the same detection used for server replies
+/// (UTF-8, then Windows-1252 and friends). Malformed encoded words are left
+/// as they are.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::utils::decode_encoded_words;
+///
+/// assert_eq!(decode_encoded_words("=?utf-8?B?Q2Fmw6k=?= news"), "Café news");
+/// assert_eq!(decode_encoded_words("=?iso-8859-1?Q?J=F6rg?= <j@x>"), "Jörg <j@x>");
+/// assert_eq!(decode_encoded_words("=?utf-8?Q?a?= =?utf-8?Q?b?="), "ab");
+/// assert_eq!(decode_encoded_words("Plain subject"), "Plain subject");
+/// ```
+pub fn decode_encoded_words(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    // Bytes of the current run of adjacent encoded words in one charset
+    let mut pending: Option<(&str, Vec<u8>)> = None;
+    // Text seen since the last encoded word
+    let mut gap_start = 0;
+    let mut pos = 0;
+
+    while let Some(offset) = value[pos..].find("=?") {
+        let start = pos + offset;
+        let Some((charset, bytes, end)) = parse_encoded_word(value, start) else {
+            pos = start + 2;
+            continue;
+        };
+
+        let gap = &value[gap_start..start];
+        if !gap.trim().is_empty() || pending.is_none() {
+            flush_encoded(&mut output, pending.take());
+            output.push_str(gap);
+        }
+        match &mut pending {
+            Some((run_charset, run)) if run_charset.eq_ignore_ascii_case(charset) => {
+                run.extend_from_slice(&bytes);
+            }
+            _ => {
+                flush_encoded(&mut output, pending.take());
+                pending = Some((charset, bytes));
+            }
+        }
+        gap_start = end;
+        pos = end;
+    }
+
+    flush_encoded(&mut output, pending);
+    output.push_str(&value[gap_start..]);
+    output
+}
+
+/// Parse the encoded word starting at `start`, returning its charset, the
+/// bytes it encodes and the offset just past it.
+fn parse_encoded_word(value: &str, start: usize) -> Option<(&str, Vec<u8>, usize)> {
+    let rest = &value[start + 2..];
+    let (charset, rest) = rest.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let text_len = rest.find("?=")?;
+    let text = &rest[..text_len];
+
+    if charset.is_empty()
+        || text.contains(|c: char| c.is_ascii_whitespace() || c == '?')
+        || charset.contains(|c: char| c.is_ascii_whitespace())
+    {
+        return None;
+    }
+    // RFC 2231 allows a language suffix such as `utf-8*en`
+    let charset = charset.split_once('*').map_or(charset, |(name, _)| name);
+
+    let bytes = match encoding {
+        "B" | "b" => ENCODED_WORD_BASE64.decode(text).ok()?,
+        "Q" | "q" => decode_q(text)?,
+        _ => return None,
+    };
+    let end = value.len() - rest.len() + text_len + 2;
+    Some((charset, bytes, end))
+}
+
+/// Decode the Q encoding: quoted-printable with `_` for space.
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    Some(bytes)
+}
+
+/// Append the text of a run of encoded words in one charset.
+fn flush_encoded(output: &mut String, run: Option<(&str, Vec<u8>)>) {
+    let Some((charset, bytes)) = run else {
+        return;
+    };
+    let decoded = encoding_rs::Encoding::for_label(charset.as_bytes())
+        .map(|encoding| encoding.decode_without_bom_handling(&bytes))
+        .filter(|(_, had_errors)| !had_errors)
+        .map(|(text, _)| text.into_owned());
+    output.push_str(&decoded.unwrap_or_else(|| decode_text_with_encoding(&bytes)));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,6 +247,9 @@ mod tests {
     fn test_parse_references_filters_invalid() {
         let refs = parse_references(Some("<valid@x.com> invalid <also-valid@y.com>"));
         assert_eq!(refs, vec!["<valid@x.com>", "<also-valid@y.com>"]);
+
+        let refs = parse_references(Some("<a>b@x.com> <>\r\n\t<folded@x.com>"));
+        assert_eq!(refs, vec!["<folded@x.com>"]);
     }
 
     #[test]
@@ -160,4 +295,61 @@ mod tests {
             "Hello World"
         );
     }
+
+    #[test]
+    fn test_decode_encoded_words_whitespace() {
+        // Whitespace, including folding, between encoded words is dropped
+        assert_eq!(
+            decode_encoded_words("=?utf-8?Q?a?=\r\n =?utf-8?Q?b?=\t=?iso-8859-1?Q?c?="),
+            "abc"
+        );
+        // Whitespace next to plain text is kept
+        assert_eq!(
+            decode_encoded_words("Re: =?utf-8?Q?a?= and =?utf-8?Q?b?= end"),
+            "Re: a and b end"
+        );
+        // Underscores in Q encoding are spaces that survive joining
+        assert_eq!(
+            decode_encoded_words("=?utf-8?Q?one_?= =?utf-8?Q?two?="),
+            "one two"
+        );
+    }
+
+    #[test]
+    fn test_decode_encoded_words_split_character() {
+        // "é" is 0xC3 0xA9, split across two words
+        assert_eq!(
+            decode_encoded_words("=?UTF-8?Q?caf=C3?= =?utf-8?Q?=A9?="),
+            "café"
+        );
+        // Unpadded base64 and RFC 2231 language tags
+        assert_eq!(decode_encoded_words("=?utf-8*fr?b?w6k?="), "é");
+    }
+
+    #[test]
+    fn test_decode_encoded_words_charsets() {
+        assert_eq!(decode_encoded_words("=?koi8-r?B?8NLJ18XU?="), "Привет");
+        assert_eq!(decode_encoded_words("=?windows-1252?Q?=80?="), "€");
+        // Unknown charsets fall back to detection
+        assert_eq!(decode_encoded_words("=?x-unknown?Q?caf=C3=A9?="), "café");
+        assert_eq!(decode_encoded_words("=?x-unknown?Q?caf=E9?="), "café");
+        // A mislabelled charset is detected as well
+        assert_eq!(decode_encoded_words("=?utf-8?Q?caf=E9?="), "café");
+    }
+
+    #[test]
+    fn test_decode_encoded_words_malformed() {
+        for value in [
+            "=?utf-8?Q?unterminated",
+            "=?utf-8?X?unknown?=",
+            "=?utf-8?Q?bad=ZZhex?=",
+            "=?utf-8?B?!!!?=",
+            "=??Q?nocharset?=",
+            "=?utf-8?Q?has space?=",
+            "a =? b ?= c",
+        ] {
+            assert_eq!(decode_encoded_words(value), value);
+        }
+        assert_eq!(decode_encoded_words("=?bad =?utf-8?Q?ok?="), "=?bad ok");
+    }
 }
@@ -0,0 +1,327 @@
+//! Wildmat patterns (RFC 3977 Section 4).
+//!
+//! A wildmat is a comma-separated list of patterns matched against newsgroup
+//! names, such as `comp.*,!comp.sys.*`. Within a pattern, `*` matches any
+//! sequence of characters and `?` any single character. A pattern preceded
+//! by `!` is negated. The rightmost pattern that matches a name decides the
+//! result, so later patterns override earlier ones.
+//!
+//! Character classes such as `[a-z]` or `[^0-9]` are also understood. RFC
+//! 3977 reserves the brackets for them, and servers like INN accept them.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::Wildmat;
+//!
+//! let wildmat: Wildmat = "comp.*,!comp.sys.*,comp.sys.mac.*".parse().unwrap();
+//! assert!(wildmat.matches("comp.lang.rust"));
+//! assert!(!wildmat.matches("comp.sys.amiga"));
+//! assert!(wildmat.matches("comp.sys.mac.apps"));
+//! assert!(!wildmat.matches("alt.test"));
+//! ```
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+use crate::{Error, Result};
+
+/// A parsed wildmat, matching UTF-8 newsgroup names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Wildmat {
+    source: String,
+    patterns: Vec<Pattern>,
+}
+
+/// One comma-separated pattern of a wildmat
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Pattern {
+    negated: bool,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Item {
+    Char(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    AnyString,
+    /// `[...]`, holding inclusive character ranges
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Wildmat {
+    /// Parse and validate a wildmat.
+    ///
+    /// Fails if a pattern is empty, if the first pattern is negated, if a
+    /// character class is not closed, or if a character outside the RFC 3977
+    /// syntax appears, such as whitespace or `\`.
+    pub fn new(wildmat: &str) -> Result<Self> {
+        let error = |reason: &str| Error::Parse(format!("Invalid wildmat {wildmat:?}: {reason}"));
+
+        let mut patterns = Vec::new();
+        let mut chars = wildmat.chars().peekable();
+        let mut more = true;
+        while more {
+            let negated = chars.next_if_eq(&'!').is_some();
+            if negated && patterns.is_empty() {
+                return Err(error("the first pattern cannot be negated"));
+            }
+
+            let mut items = Vec::new();
+            more = false;
+            while let Some(c) = chars.next() {
+                let item = match c {
+                    ',' => {
+                        more = true;
+                        break;
+                    }
+                    '*' => Item::AnyString,
+                    '?' => Item::AnyChar,
+                    '[' => {
+                        parse_class(&mut chars).ok_or_else(|| error("invalid character class"))?
+                    }
+                    c if is_exact(c) => Item::Char(c),
+                    c => return Err(error(&format!("unexpected character {c:?}"))),
+                };
+                items.push(item);
+            }
+            if items.is_empty() {
+                return Err(error("empty pattern"));
+            }
+            patterns.push(Pattern { negated, items });
+        }
+
+        Ok(Wildmat {
+            source: wildmat.to_string(),
+            patterns,
+        })
+    }
+
+    /// Check whether a name matches the wildmat.
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(&name))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+
+    /// Get the wildmat as it is sent on the wire.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Pattern {
+    fn matches(&self, name: &[char]) -> bool {
+        let (mut i, mut n) = (0, 0);
+        // Position after the last `*` and where its match currently ends
+        let mut backtrack = None;
+        while n < name.len() {
+            match self.items.get(i) {
+                Some(Item::AnyString) => {
+                    i += 1;
+                    backtrack = Some((i, n));
+                    continue;
+                }
+                Some(item) if item.matches(name[n]) => {
+                    i += 1;
+                    n += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            // Let the last `*` swallow one more character
+            match backtrack {
+                Some((star_i, star_n)) => {
+                    i = star_i;
+                    n = star_n + 1;
+                    backtrack = Some((star_i, n));
+                }
+                None => return false,
+            }
+        }
+        self.items[i..]
+            .iter()
+            .all(|item| matches!(item, Item::AnyString))
+    }
+}
+
+impl Item {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Item::Char(expected) => c == *expected,
+            Item::AnyChar => true,
+            Item::AnyString => false,
+            Item::Class { negated, ranges } => {
+                ranges
+                    .iter()
+                    .any(|&(first, last)| (first..=last).contains(&c))
+                    != *negated
+            }
+        }
+    }
+}
+
+/// Check whether a character may appear literally in a pattern
+/// (`wildmat-exact` in RFC 3977)
+pub(crate) fn is_exact(c: char) -> bool {
+    matches!(c, '\x22'..='\x29' | '\x2b' | '\x2d'..='\x3e' | '\x40'..='\x5a' | '\x5e'..='\x7e')
+        || (!c.is_ascii() && !c.is_control())
+}
+
+/// Parse a character class after its opening `[`.
+///
+/// A `]` right after the `[` or `[^` is literal, and so is a `-` at either
+/// end of the class.
+fn parse_class(chars: &mut Peekable<Chars<'_>>) -> Option<Item> {
+    let negated = chars.next_if_eq(&'^').is_some();
+    let mut ranges = Vec::new();
+    loop {
+        let first = chars.next()?;
+        if first == ']' && !ranges.is_empty() {
+            return Some(Item::Class { negated, ranges });
+        }
+        if first.is_control() || first.is_whitespace() {
+            return None;
+        }
+
+        let mut last = first;
+        if chars.next_if_eq(&'-').is_some() {
+            match chars.peek() {
+                Some(']') | None => ranges.push(('-', '-')),
+                Some(&end) => {
+                    if end < first || end.is_control() || end.is_whitespace() {
+                        return None;
+                    }
+                    chars.next();
+                    last = end;
+                }
+            }
+        }
+        ranges.push((first, last));
+    }
+}
+
+impl fmt::Display for Wildmat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Wildmat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Wildmat::new(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wildmat(s: &str) -> Wildmat {
+        Wildmat::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let w = wildmat("comp.*");
+        assert!(w.matches("comp.lang.rust"));
+        assert!(w.matches("comp."));
+        assert!(!w.matches("comp"));
+        assert!(!w.matches("alt.comp.lang"));
+
+        let w = wildmat("a?c*x*z");
+        assert!(w.matches("abcxz"));
+        assert!(w.matches("abc-x-y-z"));
+        assert!(w.matches("a.cxxzxz"));
+        assert!(!w.matches("acxz"));
+        assert!(!w.matches("abcxzy"));
+
+        assert!(wildmat("*").matches(""));
+        assert!(wildmat("**").matches("anything"));
+        assert!(!wildmat("?").matches(""));
+    }
+
+    #[test]
+    fn test_last_match_wins() {
+        let w = wildmat("*,!*.binaries.*,alt.binaries.rust");
+        assert!(w.matches("comp.lang.rust"));
+        assert!(!w.matches("alt.binaries.pictures"));
+        assert!(w.matches("alt.binaries.rust"));
+
+        // A negated pattern alone only excludes
+        let w = wildmat("comp.*,!comp.sys.*");
+        assert!(!w.matches("comp.sys.mac"));
+        assert!(!w.matches("misc.test"));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let w = wildmat("comp.lang.[a-c]*");
+        assert!(w.matches("comp.lang.c"));
+        assert!(w.matches("comp.lang.basic"));
+        assert!(!w.matches("comp.lang.rust"));
+
+        let w = wildmat("test[^0-9]");
+        assert!(w.matches("testx"));
+        assert!(!w.matches("test5"));
+
+        let w = wildmat("[]-]x[a-]");
+        assert!(w.matches("]xa"));
+        assert!(w.matches("-x-"));
+        assert!(!w.matches("ax]"));
+
+        // Commas inside a class do not separate patterns
+        assert!(wildmat("a[,.]b").matches("a,b"));
+    }
+
+    #[test]
+    fn test_utf8_names() {
+        let w = wildmat("de.comp.?bersicht,fr.*.élève*");
+        assert!(w.matches("de.comp.übersicht"));
+        assert!(w.matches("fr.education.élèves"));
+        assert!(!w.matches("de.comp.uebersicht"));
+        assert!(wildmat("x.[à-ÿ]").matches("x.é"));
+    }
+
+    #[test]
+    fn test_invalid_wildmats() {
+        for invalid in [
+            "",
+            "comp.*,",
+            ",comp.*",
+            "comp.*,,alt.*",
+            "!comp.*",
+            "comp.*,!",
+            "comp *",
+            "comp.\\*",
+            "comp.]",
+            "comp.[a-z",
+            "comp.[z-a]",
+            "comp.!x",
+            "comp.\r\n",
+        ] {
+            assert!(
+                matches!(Wildmat::new(invalid), Err(Error::Parse(_))),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let w: Wildmat = "comp.*,!comp.sys.*".parse().unwrap();
+        assert_eq!(w.to_string(), "comp.*,!comp.sys.*");
+        assert_eq!(w.as_str(), "comp.*,!comp.sys.*");
+    }
+}
@@ -0,0 +1,103 @@
+//! COMPRESS DEFLATE tests against a local server.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use nntp_rs::net_client::NntpClient;
+use nntp_rs::runtime::TokioStream;
+use nntp_rs::Error;
+
+/// Number of lines in the server's HELP text
+const HELP_LINES: usize = 2000;
+
+/// Run a server that activates compression on request and then answers
+/// HELP, DATE and QUIT over the compressed stream.
+fn spawn_server(compress_reply: &'static str) -> (String, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        let (mut tcp, _) = listener.accept().unwrap();
+        tcp.write_all(b"200 Ready\r\n").unwrap();
+
+        let mut reader = BufReader::new(tcp.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "COMPRESS DEFLATE\r\n");
+        tcp.write_all(compress_reply.as_bytes()).unwrap();
+        if !compress_reply.starts_with("206") {
+            return;
+        }
+
+        let mut reader = BufReader::new(DeflateDecoder::new(reader));
+        let mut writer = DeflateEncoder::new(tcp, Compression::default());
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).unwrap() == 0 {
+                return;
+            }
+            let reply = match line.trim_end() {
+                "HELP" => {
+                    let mut reply = "100 Help text follows\r\n".to_string();
+                    for i in 0..HELP_LINES {
+                        reply.push_str(&format!("  HELP line {i} of the server documentation\r\n"));
+                    }
+                    reply + ".\r\n"
+                }
+                "DATE" => "111 20240101000000\r\n".to_string(),
+                "QUIT" => "205 Bye\r\n".to_string(),
+                other => panic!("unexpected command {other:?}"),
+            };
+            writer.write_all(reply.as_bytes()).unwrap();
+            // Sync flush, so the client can decode the whole reply
+            writer.flush().unwrap();
+            if reply.starts_with("205") {
+                return;
+            }
+        }
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn test_compress_deflate() {
+    let (addr, handle) = spawn_server("206 Compression active\r\n");
+
+    let mut client = NntpClient::<TokioStream>::connect(&addr).await.unwrap();
+    assert!(!client.is_compressed());
+    assert!(client.compression_stats().is_none());
+
+    client.compress().await.unwrap();
+    assert!(client.is_compressed());
+    assert_eq!(client.date().await.unwrap().to_string(), "20240101000000");
+
+    let help = client.help().await.unwrap();
+    assert_eq!(help.len(), HELP_LINES);
+    assert_eq!(help[7], "  HELP line 7 of the server documentation");
+
+    let stats = client.compression_stats().unwrap();
+    assert_eq!(stats.sent_uncompressed, "DATE\r\nHELP\r\n".len() as u64);
+    assert!(stats.received_uncompressed > 80_000);
+    assert!(stats.receive_ratio().unwrap() > 5.0);
+
+    assert!(matches!(
+        client.compress().await,
+        Err(Error::InvalidCommand(_))
+    ));
+    client.quit().await.unwrap();
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_compress_refused() {
+    let (addr, handle) = spawn_server("403 Unable to activate compression\r\n");
+
+    let mut client = NntpClient::<TokioStream>::connect(&addr).await.unwrap();
+    let result = client.compress().await;
+    assert!(matches!(result, Err(Error::Protocol { code: 403, .. })));
+    assert!(!client.is_compressed());
+    handle.join().unwrap();
+}
@@ -4,7 +4,10 @@
 //! using the mock server infrastructure.
 
 use nntp_rs::mock::ClientMockTest;
-use nntp_rs::{ArticleSpec, Command, ListVariant, Response};
+use nntp_rs::response::OverviewFormat;
+use nntp_rs::{
+    ArticleRange, ArticleSpec, Command, ListVariant, OverviewSchema, Response, Timestamp,
+};
 
 /// Test basic connection and capabilities exchange as per RFC3977 Section 5.1
 #[test]
@@ -63,7 +66,7 @@ fn test_rfc3977_mode_reader() {
 #[test]
 fn test_rfc3977_group_selection() {
     let interactions = vec![(
-        Command::Group("misc.test".to_string()),
+        Command::Group("misc.test".parse().unwrap()),
         Response::GroupSelected {
             count: 3000,
             first: 3000,
@@ -75,7 +78,7 @@ fn test_rfc3977_group_selection() {
     let mut test = ClientMockTest::new(interactions);
 
     let response = test
-        .send_command(Command::Group("misc.test".to_string()))
+        .send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     if let Response::GroupSelected {
@@ -104,7 +107,7 @@ fn test_rfc3977_article_by_number() {
     let interactions = vec![
         // First select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -114,7 +117,7 @@ fn test_rfc3977_article_by_number() {
         ),
         // Then retrieve an article
         (
-            Command::Article(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Article(ArticleSpec::number_in_group("misc.test".parse().unwrap(), 3000)),
             Response::Article {
                 number: Some(3000),
                 message_id: "<45223423@example.com>".to_string(),
@@ -126,13 +129,13 @@ fn test_rfc3977_article_by_number() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Retrieve article
     let response = test
         .send_command(Command::Article(ArticleSpec::number_in_group(
-            "misc.test",
+            "misc.test".parse().unwrap(),
             3000,
         )))
         .unwrap();
@@ -160,7 +163,7 @@ fn test_rfc3977_article_by_number() {
 fn test_rfc3977_article_by_message_id() {
     let interactions = vec![
         (
-            Command::Article(ArticleSpec::MessageId("<45223423@example.com>".to_string())),
+            Command::Article(ArticleSpec::MessageId("<45223423@example.com>".parse().unwrap())),
             Response::Article {
                 number: Some(3000),
                 message_id: "<45223423@example.com>".to_string(),
@@ -173,7 +176,7 @@ fn test_rfc3977_article_by_message_id() {
 
     let response = test
         .send_command(Command::Article(ArticleSpec::MessageId(
-            "<45223423@example.com>".to_string(),
+            "<45223423@example.com>".parse().unwrap(),
         )))
         .unwrap();
 
@@ -198,7 +201,7 @@ fn test_rfc3977_article_by_message_id() {
 fn test_rfc3977_head_command() {
     let interactions = vec![
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -207,7 +210,7 @@ fn test_rfc3977_head_command() {
             },
         ),
         (
-            Command::Head(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Head(ArticleSpec::number_in_group("misc.test".parse().unwrap(), 3000)),
             Response::Article {
                 number: Some(3000),
                 message_id: "<45223423@example.com>".to_string(),
@@ -219,13 +222,13 @@ fn test_rfc3977_head_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Get headers
     let response = test
         .send_command(Command::Head(ArticleSpec::number_in_group(
-            "misc.test",
+            "misc.test".parse().unwrap(),
             3000,
         )))
         .unwrap();
@@ -248,7 +251,7 @@ fn test_rfc3977_head_command() {
 fn test_rfc3977_body_command() {
     let interactions = vec![
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -257,7 +260,10 @@ fn test_rfc3977_body_command() {
             },
         ),
         (
-            Command::Body(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Body(ArticleSpec::number_in_group(
+                "misc.test".parse().unwrap(),
+                3000,
+            )),
             Response::Article {
                 number: Some(3000),
                 message_id: "<45223423@example.com>".to_string(),
@@ -269,13 +275,13 @@ fn test_rfc3977_body_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Get body
     let response = test
         .send_command(Command::Body(ArticleSpec::number_in_group(
-            "misc.test",
+            "misc.test".parse().unwrap(),
             3000,
         )))
         .unwrap();
@@ -298,7 +304,7 @@ fn test_rfc3977_body_command() {
 fn test_rfc3977_stat_command() {
     let interactions = vec![
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -307,7 +313,10 @@ fn test_rfc3977_stat_command() {
             },
         ),
         (
-            Command::Stat(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Stat(ArticleSpec::number_in_group(
+                "misc.test".parse().unwrap(),
+                3000,
+            )),
             Response::ArticleStatus {
                 number: 3000,
                 message_id: "<45223423@example.com>".to_string(),
@@ -318,13 +327,13 @@ fn test_rfc3977_stat_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Get status
     let response = test
         .send_command(Command::Stat(ArticleSpec::number_in_group(
-            "misc.test",
+            "misc.test".parse().unwrap(),
             3000,
         )))
         .unwrap();
@@ -421,14 +430,26 @@ fn test_rfc3977_post_sequence() {
 fn test_rfc3977_error_responses() {
     let interactions = vec![
         (
-            Command::Group("nonexistent.group".to_string()),
+            Command::Group("nonexistent.group".parse().unwrap()),
             Response::Error {
                 code: 411,
                 message: "No such newsgroup".to_string(),
             },
         ),
         (
-            Command::Article(ArticleSpec::number_in_group("misc.test", 999999)),
+            Command::Group("misc.test".parse().unwrap()),
+            Response::GroupSelected {
+                count: 3,
+                first: 3000,
+                last: 3002,
+                name: "misc.test".to_string(),
+            },
+        ),
+        (
+            Command::Article(ArticleSpec::number_in_group(
+                "misc.test".parse().unwrap(),
+                999999,
+            )),
             Response::Error {
                 code: 423,
                 message: "No article with that number".to_string(),
@@ -440,7 +461,7 @@ fn test_rfc3977_error_responses() {
 
     // Test nonexistent group
     let response = test
-        .send_command(Command::Group("nonexistent.group".to_string()))
+        .send_command(Command::Group("nonexistent.group".parse().unwrap()))
         .unwrap();
 
     if let Response::Error { code, message } = response {
@@ -450,10 +471,12 @@ fn test_rfc3977_error_responses() {
         panic!("Expected Error response with code 411");
     }
 
-    // Test nonexistent article
+    // Test nonexistent article in the selected group
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
+        .unwrap();
     let response = test
         .send_command(Command::Article(ArticleSpec::number_in_group(
-            "misc.test",
+            "misc.test".parse().unwrap(),
             999999,
         )))
         .unwrap();
@@ -475,7 +498,7 @@ fn test_rfc3977_ihave_command() {
         // Offer article
         (
             Command::Ihave {
-                message_id: "<new_article@example.com>".to_string(),
+                message_id: "<new_article@example.com>".parse().unwrap(),
             },
             Response::ArticleWanted,
         ),
@@ -486,7 +509,7 @@ fn test_rfc3977_ihave_command() {
 
     let response = test
         .send_command(Command::Ihave {
-            message_id: "<new_article@example.com>".to_string(),
+            message_id: "<new_article@example.com>".parse().unwrap(),
         })
         .unwrap();
 
@@ -504,7 +527,7 @@ fn test_rfc3977_ihave_command() {
 fn test_rfc3977_ihave_rejection() {
     let interactions = vec![(
         Command::Ihave {
-            message_id: "<existing_article@example.com>".to_string(),
+            message_id: "<existing_article@example.com>".parse().unwrap(),
         },
         Response::ArticleNotWanted,
     )];
@@ -513,7 +536,7 @@ fn test_rfc3977_ihave_rejection() {
 
     let response = test
         .send_command(Command::Ihave {
-            message_id: "<existing_article@example.com>".to_string(),
+            message_id: "<existing_article@example.com>".parse().unwrap(),
         })
         .unwrap();
 
@@ -534,7 +557,7 @@ fn test_rfc3977_over_command() {
     let interactions = vec![
         // First select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -545,7 +568,7 @@ fn test_rfc3977_over_command() {
         // Get overview data for range
         (
             Command::Over {
-                range: Some("3000-3001".to_string()),
+                range: Some(ArticleRange::Between(3000, 3001)),
             },
             Response::OverviewData(vec![
                 OverviewEntry {
@@ -559,6 +582,7 @@ fn test_rfc3977_over_command() {
                         "1234".to_string(),
                         "42".to_string(),
                     ],
+                    ..Default::default()
                 },
                 OverviewEntry {
                     fields: vec![
@@ -571,6 +595,7 @@ fn test_rfc3977_over_command() {
                         "2345".to_string(),
                         "56".to_string(),
                     ],
+                    ..Default::default()
                 },
             ]),
         ),
@@ -579,13 +604,13 @@ fn test_rfc3977_over_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group first
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Test OVER command
     let response = test
         .send_command(Command::Over {
-            range: Some("3000-3001".to_string()),
+            range: Some(ArticleRange::Between(3000, 3001)),
         })
         .unwrap();
 
@@ -612,7 +637,7 @@ fn test_rfc3977_hdr_command() {
     let interactions = vec![
         // First select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -624,7 +649,7 @@ fn test_rfc3977_hdr_command() {
         (
             Command::Hdr {
                 field: "Subject".to_string(),
-                range: Some("3000-3002".to_string()),
+                range: Some(ArticleRange::Between(3000, 3002)),
             },
             Response::HeaderData(vec![
                 HeaderEntry {
@@ -646,14 +671,14 @@ fn test_rfc3977_hdr_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group first
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Test HDR command
     let response = test
         .send_command(Command::Hdr {
             field: "Subject".to_string(),
-            range: Some("3000-3002".to_string()),
+            range: Some(ArticleRange::Between(3000, 3002)),
         })
         .unwrap();
 
@@ -678,7 +703,7 @@ fn test_rfc3977_last_command() {
     let interactions = vec![
         // First select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -699,7 +724,7 @@ fn test_rfc3977_last_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group first
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Test LAST command
@@ -720,7 +745,7 @@ fn test_rfc3977_next_command() {
     let interactions = vec![
         // First select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -741,7 +766,7 @@ fn test_rfc3977_next_command() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group first
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Test NEXT command
@@ -759,14 +784,15 @@ fn test_rfc3977_next_command() {
 /// Test DATE command as per RFC 3977 Section 7.1
 #[test]
 fn test_rfc3977_date_command() {
-    let interactions = vec![(Command::Date, Response::Date("20231106123456".to_string()))];
+    let now = Timestamp::from_utc(2023, 11, 6, 12, 34, 56).unwrap();
+    let interactions = vec![(Command::Date, Response::Date(now))];
 
     let mut test = ClientMockTest::new(interactions);
 
     let response = test.send_command(Command::Date).unwrap();
     if let Response::Date(date) = response {
-        assert_eq!(date.len(), 14); // YYYYMMDDHHMMSS format
-        assert!(date.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(date.to_string(), "20231106123456"); // YYYYMMDDHHMMSS format
+        assert_eq!(date, now);
     } else {
         panic!("Expected Date response");
     }
@@ -864,7 +890,7 @@ fn test_rfc3977_over_command_flexible_format() {
         ),
         // Then select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -875,7 +901,7 @@ fn test_rfc3977_over_command_flexible_format() {
         // Get overview data with the custom format
         (
             Command::Over {
-                range: Some("3000".to_string()),
+                range: Some(ArticleRange::Number(3000)),
             },
             Response::OverviewData(vec![OverviewEntry {
                 fields: vec![
@@ -889,6 +915,7 @@ fn test_rfc3977_over_command_flexible_format() {
                     "25".to_string(),
                     "misc.test:3000".to_string(), // Custom Xref field
                 ],
+                ..Default::default()
             }]),
         ),
     ];
@@ -899,21 +926,22 @@ fn test_rfc3977_over_command_flexible_format() {
     let response = test
         .send_command(Command::List(ListVariant::OverviewFmt))
         .unwrap();
-    if let Response::OverviewFormat(format_fields) = response {
+    let schema = if let Response::OverviewFormat(format_fields) = response {
         assert_eq!(format_fields.len(), 8); // 7 standard + 1 custom
         assert_eq!(format_fields[7], "Xref:full");
+        OverviewSchema::from(&OverviewFormat(format_fields))
     } else {
         panic!("Expected OverviewFormat response");
-    }
+    };
 
     // Select group
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Test OVER command with custom format
     let response = test
         .send_command(Command::Over {
-            range: Some("3000".to_string()),
+            range: Some(ArticleRange::Number(3000)),
         })
         .unwrap();
 
@@ -931,6 +959,9 @@ fn test_rfc3977_over_command_flexible_format() {
 
         // Test access to custom field by index
         assert_eq!(entry.get_field(8), Some("misc.test:3000"));
+        // And by name through the announced format
+        assert_eq!(schema.get(entry, "Xref"), Some("misc.test:3000"));
+        assert_eq!(schema.get(entry, ":lines"), Some("25"));
 
         // Verify we have all 9 fields (8 + article number)
         assert_eq!(entry.fields.len(), 9);
@@ -963,7 +994,7 @@ fn test_rfc3977_complete_session() {
         ),
         // Select a group
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -973,7 +1004,7 @@ fn test_rfc3977_complete_session() {
         ),
         // Get article status
         (
-            Command::Stat(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Stat(ArticleSpec::number_in_group("misc.test".parse().unwrap(), 3000)),
             Response::ArticleStatus {
                 number: 3000,
                 message_id: "<45223423@example.com>".to_string(),
@@ -981,7 +1012,7 @@ fn test_rfc3977_complete_session() {
         ),
         // Retrieve article headers
         (
-            Command::Head(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Head(ArticleSpec::number_in_group("misc.test".parse().unwrap(), 3000)),
             Response::Article {
                 number: Some(3000),
                 message_id: "<45223423@example.com>".to_string(),
@@ -1004,18 +1035,18 @@ fn test_rfc3977_complete_session() {
     test.send_command(Command::ModeReader).unwrap();
     assert_eq!(test.client().state(), "reader");
 
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
     assert_eq!(test.client().state(), "group_selected");
 
     test.send_command(Command::Stat(ArticleSpec::number_in_group(
-        "misc.test",
+        "misc.test".parse().unwrap(),
         3000,
     )))
     .unwrap();
 
     test.send_command(Command::Head(ArticleSpec::number_in_group(
-        "misc.test",
+        "misc.test".parse().unwrap(),
         3000,
     )))
     .unwrap();
@@ -1031,7 +1062,7 @@ fn test_rfc3977_complete_session() {
 fn test_rfc3977_article_mail_parser_integration() {
     let interactions = vec![
         (
-            Command::Group("misc.test".to_string()),
+            Command::Group("misc.test".parse().unwrap()),
             Response::GroupSelected {
                 count: 3000,
                 first: 3000,
@@ -1040,7 +1071,7 @@ fn test_rfc3977_article_mail_parser_integration() {
             },
         ),
         (
-            Command::Article(ArticleSpec::number_in_group("misc.test", 3000)),
+            Command::Article(ArticleSpec::number_in_group("misc.test".parse().unwrap(), 3000)),
             Response::Article {
                 number: Some(3000),
                 message_id: "<45223423@example.com>".to_string(),
@@ -1052,13 +1083,13 @@ fn test_rfc3977_article_mail_parser_integration() {
     let mut test = ClientMockTest::new(interactions);
 
     // Select group
-    test.send_command(Command::Group("misc.test".to_string()))
+    test.send_command(Command::Group("misc.test".parse().unwrap()))
         .unwrap();
 
     // Retrieve article
     let response = test
         .send_command(Command::Article(ArticleSpec::number_in_group(
-            "misc.test",
+            "misc.test".parse().unwrap(),
             3000,
         )))
         .unwrap();
@@ -4,7 +4,7 @@
 //! LIST command variants, command validation, specific error codes, and state management.
 
 use nntp_rs::mock::ClientMockTest;
-use nntp_rs::{Command, ListVariant, Response};
+use nntp_rs::{Command, ListVariant, Response, Timestamp};
 
 /// Test LIST ACTIVE command variant as per RFC 3977 Section 7.6.3
 #[test]
@@ -144,7 +144,7 @@ fn test_rfc3977_command_length_validation() {
     // Create a command that would exceed 512 octets (510 + CRLF)
     // GROUP command = "GROUP " + name, so need name > 504 chars
     let very_long_group_name = "a".repeat(505);
-    let cmd = Command::Group(very_long_group_name);
+    let cmd = Command::Group(very_long_group_name.parse().unwrap());
 
     // Command should fail validation during encoding
     assert!(cmd.encode().is_err());
@@ -154,7 +154,7 @@ fn test_rfc3977_command_length_validation() {
 #[test]
 fn test_rfc3977_specific_error_codes() {
     let interactions = vec![(
-        Command::Group("nonexistent.group".to_string()),
+        Command::Group("nonexistent.group".parse().unwrap()),
         Response::Error {
             code: 411,
             message: "No such newsgroup".to_string(),
@@ -165,7 +165,7 @@ fn test_rfc3977_specific_error_codes() {
 
     // Test 411 - No such newsgroup
     let response = test
-        .send_command(Command::Group("nonexistent.group".to_string()))
+        .send_command(Command::Group("nonexistent.group".parse().unwrap()))
         .unwrap();
     if let Response::Error { code, message } = response {
         assert_eq!(code, 411);
@@ -182,9 +182,7 @@ fn test_rfc3977_specific_error_codes() {
 fn test_rfc3977_newgroups_with_distributions() {
     let interactions = vec![(
         Command::NewGroups {
-            date: "231106".to_string(),
-            time: "120000".to_string(),
-            gmt: true,
+            since: Timestamp::from_utc(2023, 11, 6, 12, 0, 0).unwrap(),
             distributions: Some("world".to_string()),
         },
         Response::NewNewsgroups(vec![]),
@@ -193,9 +191,7 @@ fn test_rfc3977_newgroups_with_distributions() {
     let mut test = ClientMockTest::new(interactions);
     let response = test
         .send_command(Command::NewGroups {
-            date: "231106".to_string(),
-            time: "120000".to_string(),
-            gmt: true,
+            since: Timestamp::from_utc(2023, 11, 6, 12, 0, 0).unwrap(),
             distributions: Some("world".to_string()),
         })
         .unwrap();
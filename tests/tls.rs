@@ -0,0 +1,223 @@
+//! TLS tests against a local server with a self-signed certificate.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use nntp_rs::net_client::NntpClient;
+use nntp_rs::runtime::TokioStream;
+use nntp_rs::tls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use nntp_rs::tls::rustls::server::WebPkiClientVerifier;
+use nntp_rs::tls::rustls::{self, RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+use nntp_rs::tls::TlsConfig;
+use nntp_rs::Error;
+
+/// A self-signed certificate and its private key.
+struct Identity {
+    cert: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl Identity {
+    fn generate(name: &str) -> Self {
+        let certified = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+        Identity {
+            cert: certified.cert.der().clone(),
+            key: PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der()).into(),
+        }
+    }
+
+    fn roots(&self) -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        roots.add(self.cert.clone()).unwrap();
+        roots
+    }
+}
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn server_config(server: &Identity, client_roots: Option<RootCertStore>) -> Arc<ServerConfig> {
+    let builder = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = match client_roots {
+        Some(roots) => builder.with_client_cert_verifier(
+            WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider())
+                .build()
+                .unwrap(),
+        ),
+        None => builder.with_no_client_auth(),
+    };
+    Arc::new(
+        builder
+            .with_single_cert(vec![server.cert.clone()], server.key.clone_key())
+            .unwrap(),
+    )
+}
+
+/// Answer each command line with `111 <date>` until QUIT.
+fn serve_dates<S: Read + Write>(stream: S, date: &str) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).unwrap() == 0 {
+            return;
+        }
+        let reply = match line.trim_end() {
+            "QUIT" => "205 Bye\r\n".to_string(),
+            _ => format!("111 {date}\r\n"),
+        };
+        let stream = reader.get_mut();
+        stream.write_all(reply.as_bytes()).unwrap();
+        stream.flush().unwrap();
+        if reply.starts_with("205") {
+            return;
+        }
+    }
+}
+
+/// Run an implicit TLS server for one connection.
+fn spawn_nntps_server(config: Arc<ServerConfig>) -> (String, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+    let handle = thread::spawn(move || {
+        let (tcp, _) = listener.accept().unwrap();
+        let mut tls = StreamOwned::new(ServerConnection::new(config).unwrap(), tcp);
+        if tls.write_all(b"200 NNTPS ready\r\n").is_err() {
+            return;
+        }
+        serve_dates(tls, "20240101000000");
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn test_connect_tls() {
+    let server = Identity::generate("localhost");
+    let (addr, handle) = spawn_nntps_server(server_config(&server, None));
+
+    let config = TlsConfig::with_root_certificates(server.roots());
+    let mut client = NntpClient::<TokioStream>::connect_tls(&addr, &config)
+        .await
+        .unwrap();
+    assert!(client.is_tls());
+    assert!(client.is_posting_allowed());
+    assert_eq!(client.date().await.unwrap().to_string(), "20240101000000");
+    client.quit().await.unwrap();
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_connect_tls_rejects_untrusted_certificate() {
+    let server = Identity::generate("localhost");
+    let (addr, handle) = spawn_nntps_server(server_config(&server, None));
+
+    let other = Identity::generate("localhost");
+    let config = TlsConfig::with_root_certificates(other.roots());
+    let result = NntpClient::<TokioStream>::connect_tls(&addr, &config).await;
+    assert!(matches!(result, Err(Error::Connection(_))));
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_connect_tls_checks_server_name() {
+    let server = Identity::generate("news.example.com");
+    let (addr, handle) = spawn_nntps_server(server_config(&server, None));
+
+    let config = TlsConfig::with_root_certificates(server.roots());
+    let result = NntpClient::<TokioStream>::connect_tls(&addr, &config).await;
+    assert!(matches!(result, Err(Error::Connection(_))));
+    handle.join().unwrap();
+
+    let (addr, handle) = spawn_nntps_server(server_config(&server, None));
+    let config = TlsConfig {
+        server_name: Some("news.example.com".to_string()),
+        ..TlsConfig::with_root_certificates(server.roots())
+    };
+    let client = NntpClient::<TokioStream>::connect_tls(&addr, &config)
+        .await
+        .unwrap();
+    client.quit().await.unwrap();
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_connect_tls_with_client_certificate() {
+    let server = Identity::generate("localhost");
+    let user = Identity::generate("reader");
+    let (addr, handle) = spawn_nntps_server(server_config(&server, Some(user.roots())));
+
+    let config = TlsConfig {
+        client_certificate: Some((vec![user.cert.clone()], user.key.clone_key())),
+        ..TlsConfig::with_root_certificates(server.roots())
+    };
+    let mut client = NntpClient::<TokioStream>::connect_tls(&addr, &config)
+        .await
+        .unwrap();
+    assert_eq!(client.date().await.unwrap().to_string(), "20240101000000");
+    client.quit().await.unwrap();
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_starttls_discards_injected_plaintext() {
+    let server = Identity::generate("localhost");
+    let config = server_config(&server, None);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+    let handle = thread::spawn(move || {
+        let (mut tcp, _) = listener.accept().unwrap();
+        tcp.write_all(b"200 Plain text ready\r\n").unwrap();
+
+        let mut reader = BufReader::new(tcp.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "STARTTLS\r\n");
+        // A reply injected by an attacker must not survive the upgrade
+        tcp.write_all(b"382 Continue with TLS negotiation\r\n111 19990101000000\r\n")
+            .unwrap();
+
+        let tls = StreamOwned::new(ServerConnection::new(config).unwrap(), tcp);
+        serve_dates(tls, "20240101000000");
+    });
+
+    let mut client = NntpClient::<TokioStream>::connect(&addr).await.unwrap();
+    assert!(!client.is_tls());
+    client
+        .starttls(&TlsConfig::with_root_certificates(server.roots()))
+        .await
+        .unwrap();
+    assert!(client.is_tls());
+    assert_eq!(client.date().await.unwrap().to_string(), "20240101000000");
+    client.quit().await.unwrap();
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_starttls_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+    let handle = thread::spawn(move || {
+        let (mut tcp, _) = listener.accept().unwrap();
+        tcp.write_all(b"200 Plain text ready\r\n").unwrap();
+        let mut reader = BufReader::new(tcp.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        tcp.write_all(b"580 Can not initiate TLS negotiation\r\n")
+            .unwrap();
+        let _ = TcpStream::shutdown(&tcp, std::net::Shutdown::Both);
+    });
+
+    let mut client = NntpClient::<TokioStream>::connect(&addr).await.unwrap();
+    let server = Identity::generate("localhost");
+    let result = client
+        .starttls(&TlsConfig::with_root_certificates(server.roots()))
+        .await;
+    assert!(matches!(result, Err(Error::Protocol { code: 580, .. })));
+    assert!(!client.is_tls());
+    handle.join().unwrap();
+}